@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use txcv_core::fuzzing::{join, split, Block, Directives, Format};
+
+fuzz_target!(|data: &[u8]| {
+    let content = match std::str::from_utf8(data) {
+        Err(_) => return,
+        Ok(content) => content,
+    };
+
+    let directives = Directives::parse(content).unwrap_or_default();
+    let blocks = split(content, &directives, &Format::Markdown.default_markers());
+    let texts = blocks.iter().map(Block::text).collect::<Vec<_>>();
+
+    // crlf is normalized to lf
+    if !content.contains("\r\n") {
+        assert_eq!(join(&texts, content), content);
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use txcv_core::fuzzing::{emoji_spans, protect, restore, restore_whitespace, strip_whitespace};
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Err(_) => return,
+        Ok(text) => text,
+    };
+
+    let (core, layout) = strip_whitespace(text);
+    assert_eq!(restore_whitespace(&core, &layout), text);
+
+    if !text.contains("{{") {
+        let protected = protect(text, emoji_spans(text));
+        assert_eq!(restore(&protected.text, &protected), text);
+    }
+});
@@ -1,4 +0,0 @@
-#[async_std::main]
-async fn main() -> anyhow::Result<()> {
-    txcv::run().await
-}
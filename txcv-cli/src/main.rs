@@ -0,0 +1,1255 @@
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use clap::builder::styling::AnsiColor;
+use clap::builder::Styles;
+use clap::{Parser, Subcommand};
+
+use txcv_core::backend::Backend;
+use txcv_core::card::Cards;
+use txcv_core::color::Color;
+use txcv_core::config::{Config, RateLimit};
+use txcv_core::corpus::{CorpusFormat, Pair};
+#[cfg(feature = "credential-store")]
+use txcv_core::credential::{CredentialStore, Profile};
+use txcv_core::credential::{Credentials, Overrides};
+use txcv_core::daemon::Request;
+use txcv_core::dict::Dict;
+use txcv_core::document::FormatMarkers;
+use txcv_core::encoding::{BomStyle, NewlineStyle};
+use txcv_core::exec::ChildFailed;
+use txcv_core::explain::Explainer;
+use txcv_core::filter::Filter;
+use txcv_core::glossary::{Glossary, Term};
+use txcv_core::history::{History, HistoryFormat};
+use txcv_core::inline_image::ImageProtocol;
+use txcv_core::interrupt::{Interrupted, INTERRUPTED_EXIT_CODE};
+use txcv_core::lang::{AutoTarget, Language};
+use txcv_core::languages::LanguagesFormat;
+use txcv_core::memory::{MemoryMode, TranslationMemory};
+use txcv_core::normalize::Normalization;
+use txcv_core::output::Output;
+#[cfg(feature = "wasm-plugin")]
+use txcv_core::plugin;
+use txcv_core::qa::{QaFailed, QaFormat};
+use txcv_core::retry::RetryPolicy;
+use txcv_core::slang::{Slang, SlangMode};
+#[cfg(feature = "tui")]
+use txcv_core::terminal;
+use txcv_core::translate::{Mode, Options, Translate};
+use txcv_core::usage::Usage;
+use txcv_core::{
+    bench, cache, corpus, credential, daemon, diagnostic, history, interrupt, languages, output,
+    status, usage, Error,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about,
+styles = Styles::styled()
+.header(AnsiColor::Yellow.on_default())
+.usage(AnsiColor::Green.on_default())
+.literal(AnsiColor::Green.on_default())
+.placeholder(AnsiColor::Green.on_default()))]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    words: Vec<String>,
+
+    /// config file, default is txcv/config.toml under the config dir
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// normalize source text before translating, separated by comma
+    #[arg(long, value_delimiter = ',')]
+    normalize: Vec<Normalization>,
+
+    /// keep markdown code blocks and inline code untranslated
+    #[arg(long)]
+    preserve_code: bool,
+
+    /// join the hard wrapped lines of paragraphs before translating, like in readme files and
+    /// commit messages
+    #[arg(long)]
+    unwrap_lines: bool,
+
+    /// keep urls and emails untranslated
+    #[arg(long)]
+    preserve_urls: bool,
+
+    /// keep format placeholders like `{name}`, `%s` and `{{count}}` untranslated, always on for
+    /// json, yaml and po resource files
+    #[arg(long)]
+    preserve_placeholders: bool,
+
+    /// where the texts are used, like `checkout page button`, to disambiguate short texts like
+    /// `Book` or `May`, sent as the context by deepl and translated with the texts by tmt
+    #[arg(long, value_name = "HINT")]
+    context: Option<String>,
+
+    /// expand or annotate internet slang like `brb` and `yyds` before translating, custom slang
+    /// can be added in the `slang` table of config
+    #[arg(long)]
+    slang: Option<SlangMode>,
+
+    /// briefly explain grammar points and idioms of the source text below the translation, by
+    /// the llm in the `llm` table of config
+    #[arg(long)]
+    explain: bool,
+
+    /// show the pronunciation, the parts of speech, the senses and the examples of a single
+    /// english word below the translation, by the free dictionary api or the `card-api` of config
+    #[arg(long)]
+    card: bool,
+
+    /// translate single words by the offline dictionary only, english and chinese are supported
+    #[arg(long)]
+    offline: bool,
+
+    /// translate the words or stdin by the running `txcv serve`, print a translation per line
+    #[arg(long, conflicts_with_all = ["dir", "file"])]
+    daemon: bool,
+
+    /// offline dictionary, an ecdict csv or the `.ifo` file of a stardict dictionary, default is
+    /// `ecdict.csv` under the txcv data dir if exists, also used when the network is unavailable
+    #[arg(long)]
+    dict: Option<PathBuf>,
+
+    /// where the credentials are stored
+    #[cfg(feature = "credential-store")]
+    #[arg(long, default_value_t)]
+    credential_store: CredentialStore,
+
+    /// use the credentials of the profile, like `work`, profiles are added by `txcv auth set`,
+    /// default is the `default` profile
+    #[cfg(feature = "credential-store")]
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// warn when credentials are older than these days
+    #[cfg(feature = "credential-store")]
+    #[arg(long, default_value_t = DEFAULT_CREDENTIAL_MAX_AGE_DAYS)]
+    credential_max_age: u64,
+
+    /// tencent cloud secret id, takes precedence over TENCENTCLOUD_SECRET_ID and the credential
+    /// store
+    #[arg(long)]
+    secret_id: Option<String>,
+
+    /// tencent cloud secret key, takes precedence over TENCENTCLOUD_SECRET_KEY and the credential
+    /// store, prefer the environment variable since the command line is visible to other users
+    #[arg(long)]
+    secret_key: Option<String>,
+
+    /// tencent cloud region, takes precedence over TENCENTCLOUD_REGION, the region of config and
+    /// the credential store
+    #[arg(long)]
+    region: Option<String>,
+
+    /// never write to credential store and never ask credentials, for credentials provisioned by others
+    #[cfg(feature = "credential-store")]
+    #[arg(long)]
+    read_only: bool,
+
+    /// don't save the asked credentials to credential store, use them for this session only
+    #[cfg(feature = "credential-store")]
+    #[arg(long)]
+    no_auto_save_credentials: bool,
+
+    /// restore a terminal which is left in a bad state, like raw mode or hidden cursor
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    reset_terminal: bool,
+
+    /// source language, default is auto detect
+    #[arg(short, long)]
+    source: Option<Language>,
+
+    /// target language, default is auto detect
+    #[arg(short, long)]
+    target: Option<Language>,
+
+    /// target languages of the detected source languages when target is not set, like
+    /// `ja=zh,ko=en,*=en`, `*` matches the other languages, default translates chinese to english
+    /// and others to chinese
+    #[arg(long, value_delimiter = ',')]
+    auto_target: Vec<AutoTarget>,
+
+    /// translate output color, default is auto
+    #[arg(long)]
+    color: Option<Color>,
+
+    /// print the counts of translated, cached and failed texts, the characters sent and the time
+    /// after batch runs and --input-file, to stderr
+    #[arg(long)]
+    summary: bool,
+
+    /// print the planned api requests, their action, languages and characters, instead of sending
+    /// them, no credentials are needed and nothing is cached or written
+    #[arg(
+        long,
+        conflicts_with_all = ["daemon", "file", "image", "audio", "explain", "card", "segment_metadata"]
+    )]
+    dry_run: bool,
+
+    /// print the api requests and their latencies, the retries, the rate limiter waits and the
+    /// cache hits to stderr, -vv also prints the texts sent and received
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// give up a rate limited request after retrying this many times, overrides the config
+    #[arg(long)]
+    max_retries: Option<u32>,
+
+    /// max concurrent requests, also the burst of the rate limiter, overrides the config
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_concurrent: Option<u32>,
+
+    /// requests per second the rate limiter allows, like 20 for a higher tmt quota or 0.5 for a
+    /// shared account, overrides the refill interval of the config
+    #[arg(long, value_parser = qps)]
+    qps: Option<f64>,
+
+    /// give up a request after this many seconds, including the wait for the rate limit and the
+    /// retries, in batch, input file, daemon and watch modes
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    /// if specifies, only print the translated result
+    #[arg(long)]
+    concise: bool,
+
+    /// reuse similar translations from local translation memory
+    #[arg(long, default_value_t)]
+    memory: MemoryMode,
+
+    /// minimum similarity percent of translation memory match
+    #[arg(long, default_value_t = 90, value_parser = clap::value_parser!(u8).range(1..=100))]
+    memory_threshold: u8,
+
+    /// don't record translated queries to the history
+    #[arg(long)]
+    no_history: bool,
+
+    /// don't read or write the local translation cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// glossary file, each line is `term,translation`, or a `.toml` file with a `[terms]` table,
+    /// the terms are kept from the translation engine and translated as defined, default is
+    /// `glossary.toml` or `glossary.csv` under the txcv config dir if exists
+    #[arg(long)]
+    glossary: Option<PathBuf>,
+
+    /// check translated segments for common machine translation defects
+    #[arg(long, num_args = 0..=1, default_missing_value = "plain")]
+    qa: Option<QaFormat>,
+
+    /// ci mode, disable prompts and color, read credentials from --secret-id, --secret-key and
+    /// --region or TENCENTCLOUD_SECRET_ID, TENCENTCLOUD_SECRET_KEY and TENCENTCLOUD_REGION, print
+    /// results as json lines, exit with code 2 when qa finds issues
+    #[arg(long)]
+    ci: bool,
+
+    /// translate all files in the directory, files can override options by directives like
+    /// `<!-- txcv: target=ja glossary=api.csv skip-lines=1-10 -->`
+    #[arg(long)]
+    dir: Option<PathBuf>,
+
+    /// translate a docx, pdf or xlsx document by tencent cloud document translation, the
+    /// translated document is written next to it as `<name>.<target>.<ext>`
+    #[arg(long, conflicts_with = "dir")]
+    file: Option<PathBuf>,
+
+    /// translate a text file by paragraphs, the blank lines between paragraphs are kept, the
+    /// string values of json and yaml files and the untranslated msgids of po files are
+    /// translated in place
+    #[arg(long, conflicts_with_all = ["dir", "file"])]
+    input_file: Option<PathBuf>,
+
+    /// recognize and translate the text lines of a png or jpg image, like a screenshot or a photo
+    #[arg(long, conflicts_with_all = ["dir", "file", "input_file"])]
+    image: Option<PathBuf>,
+
+    /// recognize and translate the speech of a 16k sample rate, 16 bit, mono wav file, like a
+    /// voice memo, needs --source
+    #[arg(long, conflicts_with_all = ["dir", "file", "input_file", "image"])]
+    audio: Option<PathBuf>,
+
+    /// write the translation of --input-file to the file instead of stdout, in the encoding
+    /// style of the input
+    #[arg(long, requires = "input_file")]
+    output_file: Option<PathBuf>,
+
+    /// output directory of directory translation
+    #[arg(long, requires = "dir")]
+    out_dir: Option<PathBuf>,
+
+    /// output format, json and tsv print a line of source text, source language, target language
+    /// and translated text for every translation, diff prints unified diffs of directory
+    /// translation instead of writing files
+    #[arg(long, default_value_t)]
+    output: Output,
+
+    /// flush stdout after every translation, so the reader of a pipe gets it immediately
+    #[arg(long)]
+    flush: bool,
+
+    /// printed after every translation instead of the newline, like `\0` or `\x1e`, so the
+    /// translations with newlines can be split
+    #[arg(long, value_name = "SEP")]
+    record_sep: Option<String>,
+
+    /// print json lines of translated segments in directory translation, with file, line and byte
+    /// positions, source, target and provider
+    #[arg(long)]
+    segment_metadata: bool,
+
+    /// review every translated segment of directory translation, accept, edit or retranslate it
+    /// before the file is written, the edits are saved to translation memory
+    #[cfg(feature = "interactive")]
+    #[arg(long, requires = "dir", conflicts_with = "ci")]
+    review: bool,
+
+    /// markers of the regions which are not translated, like `markdown=<!-- off -->,<!-- on -->`,
+    /// default is `txcv:off` and `txcv:on` in comment
+    #[arg(long)]
+    ignore_markers: Vec<FormatMarkers>,
+
+    /// translation provider, default is tmt
+    #[arg(long)]
+    backend: Option<Backend>,
+
+    /// tencent terminology repository id, can be specified multiple times, the repository is
+    /// created in the tmt console
+    #[arg(long = "term-repo")]
+    term_repos: Vec<String>,
+
+    /// write translated files in the original encoding, like GBK or Shift-JIS, default is utf-8
+    #[arg(long)]
+    keep_encoding: bool,
+
+    /// newline style of translated files, default keeps the original style
+    #[arg(long, default_value_t)]
+    newline: NewlineStyle,
+
+    /// bom of translated files, default keeps the original bom
+    #[arg(long, default_value_t)]
+    bom: BomStyle,
+
+    /// translate the clipboard, text lines in a clipboard image like a screenshot are recognized
+    /// and translated
+    #[cfg(feature = "clipboard")]
+    #[arg(long)]
+    from_clipboard: bool,
+
+    /// put the translations on the clipboard, a translation per line
+    #[cfg(feature = "clipboard")]
+    #[arg(long)]
+    copy: bool,
+
+    /// draw the image of --image or the clipboard in the terminal before its numbered text
+    /// regions, the protocol is detected from the terminal by default
+    #[arg(long, num_args = 0..=1, default_missing_value = "auto")]
+    show_image: Option<ImageProtocol>,
+
+    /// show the translations as desktop notifications, useful with `txcv watch` and
+    /// --from-clipboard
+    #[cfg(feature = "notify")]
+    #[arg(long)]
+    notify: bool,
+
+    /// sandboxed wasm plugin which filters text before and after translating, it can't parse
+    /// file formats, can be specified multiple times
+    #[cfg(feature = "wasm-plugin")]
+    #[arg(long = "plugin")]
+    plugins: Vec<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// export local data
+    #[command(subcommand)]
+    Export(Export),
+
+    /// manage authentication
+    #[cfg(feature = "credential-store")]
+    #[command(subcommand)]
+    Auth(Auth),
+
+    /// list, search or export translated queries
+    History {
+        /// only show the queries whose input or output contains the keyword, case insensitive
+        #[arg(long)]
+        search: Option<String>,
+
+        /// only show the newest queries
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// output format
+        #[arg(long, default_value_t)]
+        format: HistoryFormat,
+    },
+
+    /// manage the local translation cache
+    #[command(subcommand)]
+    Cache(Cache),
+
+    /// show the characters sent to tmt and deepl per month, against their free tiers
+    Stats,
+
+    /// check the credentials, the endpoint and the latency of tmt and deepl, and show their usage
+    /// of this month and rate limits
+    Status,
+
+    /// list the supported languages and the providers which translate them, json has the
+    /// capabilities of the providers and every supported language pair
+    Languages {
+        /// output format
+        #[arg(long, default_value_t)]
+        output: LanguagesFormat,
+    },
+
+    /// manage the terms of --glossary, or the default glossary
+    #[command(subcommand)]
+    Glossary(GlossaryCommand),
+
+    /// run the command and translate its stderr line by line, like `txcv exec -- make`, the exit
+    /// code of the command is kept
+    Exec {
+        /// translate stdout too
+        #[arg(long)]
+        all: bool,
+
+        /// the command and its arguments
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// print the translation of every newly copied text until interrupted, for reading foreign
+    /// documents
+    #[cfg(feature = "clipboard")]
+    Watch {
+        /// watch the x11 primary selection, the text is copied by selecting it, linux only
+        #[arg(long)]
+        primary: bool,
+
+        /// milliseconds the copied text must be unchanged before translating
+        #[arg(long, default_value_t = 500)]
+        debounce: u64,
+
+        /// skip the copied text longer than these characters
+        #[arg(long, default_value_t = 2000)]
+        max_chars: usize,
+    },
+
+    /// keep txcv running and translate the json line requests of a local socket, like
+    /// `{"text":"hello","target":"ja"}`, for editor integrations, query it by `--daemon`
+    Serve,
+
+    /// measure throughput of batch translation with a mock provider
+    Bench {
+        /// number of words to translate
+        #[arg(long, default_value_t = 100)]
+        words: u32,
+
+        /// max concurrent requests, also the rate limiter capacity
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        concurrency: u32,
+
+        /// rate limiter refill interval in milliseconds
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+        refill_interval: u64,
+
+        /// mock provider response latency in milliseconds
+        #[arg(long, default_value_t = 200)]
+        latency: u64,
+    },
+}
+
+#[cfg(feature = "credential-store")]
+#[derive(Debug, Subcommand)]
+enum Auth {
+    /// show credentials status and age of --profile
+    Status,
+
+    /// save the credentials of a profile, the ones not passed by --secret-id, --secret-key,
+    /// --region or their environment variables are asked, the old ones are replaced
+    #[command(visible_alias = "add")]
+    Set {
+        /// profile name, like `work` or `personal`, default is --profile
+        name: Option<String>,
+    },
+
+    /// show the credentials used with --profile and where they come from, the secrets are masked
+    Show,
+
+    /// validate the credentials used with --profile by a language detection request
+    Test,
+
+    /// list the profiles which have credentials
+    List,
+
+    /// remove the credentials of a profile
+    #[command(visible_alias = "remove")]
+    Clear {
+        /// profile name, default is --profile
+        name: Option<String>,
+    },
+
+    /// move the credentials of all profiles from --credential-store to another store, the source
+    /// is deleted after the destination is verified
+    Migrate {
+        /// the destination store
+        #[arg(long)]
+        to: CredentialStore,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum Cache {
+    /// remove all cached translations
+    Clear,
+
+    /// check the translation cache and the history, remove the entries broken by a crash
+    Verify,
+}
+
+#[derive(Debug, Subcommand)]
+enum GlossaryCommand {
+    /// add a term, the term is replaced if exists
+    Add {
+        /// the term, like `pull request`
+        term: String,
+
+        /// the fixed translation of the term
+        translation: String,
+    },
+
+    /// list the terms
+    List,
+
+    /// remove a term
+    Remove { term: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum Export {
+    /// dump aligned source and target segments from translation memory
+    Corpus {
+        /// language pair like en-zh, default exports all pairs
+        #[arg(long)]
+        pair: Option<Pair>,
+
+        /// output format
+        #[arg(long, default_value_t)]
+        format: CorpusFormat,
+    },
+}
+
+#[cfg(feature = "credential-store")]
+const DEFAULT_CREDENTIAL_MAX_AGE_DAYS: u64 = 90;
+#[cfg(feature = "credential-store")]
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// exit code when qa finds issues in ci mode
+const QA_FAILED_EXIT_CODE: i32 = 2;
+/// exit code of the other errors, same as returning an error from main
+const FAILED_EXIT_CODE: i32 = 1;
+
+#[async_std::main]
+async fn main() -> Result<(), Error> {
+    run().await
+}
+
+async fn run() -> Result<(), Error> {
+    let args = Args::parse();
+    diagnostic::set_verbosity(args.verbose);
+    #[cfg(feature = "tui")]
+    {
+        if args.reset_terminal {
+            return Ok(terminal::reset()?);
+        }
+
+        terminal::install_guard();
+    }
+    install_interrupt_handler()?;
+
+    let ci = args.ci;
+    let json = args.output == Output::Json;
+
+    match run_with_args(args).await {
+        Err(err) if err.is::<ChildFailed>() => {
+            let ChildFailed(code) = err.downcast()?;
+
+            process::exit(code)
+        }
+
+        // the results already obtained are printed
+        Err(err) if err.is::<Interrupted>() => {
+            let _ = io::stdout().flush();
+
+            process::exit(INTERRUPTED_EXIT_CODE)
+        }
+
+        Err(err) if ci && err.is::<QaFailed>() => {
+            diagnostic::error(err);
+
+            process::exit(QA_FAILED_EXIT_CODE)
+        }
+
+        Err(err) => {
+            let _ = io::stdout().flush();
+            let err = Error::from(err);
+            if json {
+                // a json line with the category, so scripts can match on it
+                eprintln!(
+                    "{}",
+                    serde_json::json!({ "error": format!("{err:#}"), "kind": err.kind() })
+                );
+            } else {
+                diagnostic::error(format!("{err:#}"));
+            }
+
+            process::exit(FAILED_EXIT_CODE)
+        }
+
+        Ok(()) => Ok(()),
+    }
+}
+
+async fn run_with_args(mut args: Args) -> anyhow::Result<()> {
+    // exec, serve and watch translate like the default command
+    let mode = match args.command.take() {
+        Some(Command::Exec { all, command }) => Some(Mode::Exec { command, all }),
+        Some(Command::Serve) => Some(Mode::Serve),
+        #[cfg(feature = "clipboard")]
+        Some(Command::Watch {
+            primary,
+            debounce,
+            max_chars,
+        }) => Some(Mode::Watch {
+            primary,
+            debounce: Duration::from_millis(debounce),
+            max_chars,
+        }),
+        Some(command) => return run_command(command, &args).await,
+        None => None,
+    };
+
+    if args.dry_run && mode.is_some() {
+        return Err(anyhow::anyhow!(
+            "--dry-run can't be used with exec, serve or watch"
+        ));
+    }
+    #[cfg(feature = "interactive")]
+    if args.dry_run && args.review {
+        return Err(anyhow::anyhow!("--dry-run can't be used with --review"));
+    }
+
+    if args.dir.is_some() && args.out_dir.is_none() && args.output != Output::Diff && !args.dry_run
+    {
+        return Err(anyhow::anyhow!(
+            "--dir requires --out-dir, or print diffs by --output diff"
+        ));
+    }
+
+    if args.explain && (args.output != Output::Plain || args.ci) {
+        return Err(anyhow::anyhow!("--explain only works with plain output"));
+    }
+    if args.card && (args.output != Output::Plain || args.ci) {
+        return Err(anyhow::anyhow!("--card only works with plain output"));
+    }
+    if args.record_sep.is_some() && (args.explain || args.card) {
+        return Err(anyhow::anyhow!(
+            "--record-sep can't be used with --explain or --card"
+        ));
+    }
+    let record_sep = args
+        .record_sep
+        .as_deref()
+        .map(output::record_separator)
+        .transpose()?;
+
+    let config = Config::load(args.config.as_deref())?;
+    args.source = args.source.or(config.source);
+    args.target = args.target.or(config.target);
+    args.color = args.color.or(config.color);
+    args.concise |= config.concise.unwrap_or(false);
+
+    if args.daemon {
+        return forward(&args).await;
+    }
+
+    // the default glossary is optional
+    let glossary_path = args.glossary.clone().or_else(Glossary::default_path);
+    let glossary = match &glossary_path {
+        Some(path) if args.glossary.is_some() || path.exists() => Some(Glossary::load(path)?),
+        _ => None,
+    };
+
+    // the default dictionary is optional
+    let dict = args
+        .dict
+        .clone()
+        .or(config.dict.clone())
+        .or_else(|| Dict::default_path().filter(|path| path.exists()));
+
+    // the region of config only overrides the stored region
+    let mut credentials = Overrides::new(
+        args.secret_id.take(),
+        args.secret_key.take(),
+        args.region.take(),
+    );
+    credentials.region = credentials.region.or(config.region.clone());
+
+    let filters = [
+        (args.unwrap_lines, Filter::Unwrap),
+        (args.preserve_code, Filter::Code),
+        (args.preserve_urls, Filter::Urls),
+        (args.preserve_placeholders, Filter::Placeholders),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, filter)| enabled.then_some(filter))
+    .collect();
+
+    let backend = args.backend.or(config.backend).unwrap_or_default();
+    let rate_limit = rate_limit(&args, &config, backend);
+
+    let from_stdin = !io::stdin().is_terminal();
+    let mut translate = Translate::new(
+        from_stdin,
+        Options {
+            color: args.color.unwrap_or_default(),
+            concise: args.concise,
+            // the texts of a dry run aren't translated, they are kept out of the memory, the
+            // history and the cache
+            memory_mode: if args.dry_run {
+                MemoryMode::Off
+            } else {
+                args.memory
+            },
+            memory_threshold: args.memory_threshold,
+            glossary,
+            #[cfg(feature = "interactive")]
+            glossary_path,
+            qa: args.qa,
+            ci: args.ci,
+            ignore_markers: args.ignore_markers,
+            backend,
+            term_repos: args.term_repos,
+            keep_encoding: args.keep_encoding,
+            newline: args.newline,
+            bom: args.bom,
+            #[cfg(feature = "wasm-plugin")]
+            plugins: args
+                .plugins
+                .iter()
+                .map(|path| plugin::WasmPlugin::load(path))
+                .collect::<anyhow::Result<_>>()?,
+            #[cfg(feature = "credential-store")]
+            profile: Profile::new(args.credential_store, args.profile.as_deref())?,
+            #[cfg(feature = "credential-store")]
+            save_credentials: !args.no_auto_save_credentials,
+            #[cfg(feature = "credential-store")]
+            credential_max_age: credential_max_age(args.credential_max_age),
+            #[cfg(feature = "credential-store")]
+            read_only: args.read_only,
+            normalizations: args.normalize,
+            filters,
+            context: args.context,
+            slang: args.slang.map(|mode| Slang::new(mode, config.slang)),
+            explainer: args.explain.then(|| Explainer::new(&config.llm)),
+            cards: args.card.then(|| Cards::new(config.card_api)),
+            offline: args.offline,
+            dict,
+            substitutions: config.replace,
+            // the command line rules take precedence
+            auto_targets: args
+                .auto_target
+                .into_iter()
+                .chain(config.auto_target)
+                .collect(),
+            #[cfg(feature = "clipboard")]
+            copy: args.copy && !args.dry_run,
+            show_image: args.show_image,
+            #[cfg(feature = "notify")]
+            notify: args.notify,
+            segment_metadata: args.segment_metadata,
+            output: args.output,
+            flush: args.flush,
+            record_sep,
+            credentials,
+            history: !args.no_history && !args.ci && !args.dry_run,
+            cache: !args.no_cache && !args.dry_run,
+            cache_ttl_days: config.cache_ttl.unwrap_or(cache::DEFAULT_TTL_DAYS),
+            rate_limit,
+            max_chars: config.max_chars,
+            retry: RetryPolicy {
+                max_retries: args.max_retries.unwrap_or(config.retry.max_retries),
+                ..config.retry
+            },
+            timeout: args.timeout.map(Duration::from_secs),
+            summary: args.summary,
+            dry_run: args.dry_run,
+            usage_warning: config.usage_warning.unwrap_or(usage::DEFAULT_WARNING),
+            #[cfg(feature = "interactive")]
+            review: args.review,
+        },
+    )
+    .await?;
+    if let Some(mode) = mode {
+        return translate.run(mode, args.source, args.target).await;
+    }
+
+    if let Some(input) = args.dir {
+        let output = args.out_dir.filter(|_| args.output != Output::Diff);
+
+        return translate
+            .run(Mode::Dir { input, output }, args.source, args.target)
+            .await;
+    }
+
+    if let Some(input) = args.input_file {
+        return translate
+            .run(
+                Mode::InputFile {
+                    input,
+                    output: args.output_file,
+                },
+                args.source,
+                args.target,
+            )
+            .await;
+    }
+
+    if let Some(input) = args.file {
+        return translate
+            .run(Mode::File(input), args.source, args.target)
+            .await;
+    }
+
+    if let Some(input) = args.image {
+        return translate
+            .run(Mode::Image(input), args.source, args.target)
+            .await;
+    }
+
+    if let Some(input) = args.audio {
+        return translate
+            .run(Mode::Audio(input), args.source, args.target)
+            .await;
+    }
+
+    #[cfg(feature = "clipboard")]
+    if args.from_clipboard {
+        return translate
+            .run(Mode::Clipboard, args.source, args.target)
+            .await;
+    }
+
+    if from_stdin {
+        return translate
+            .run(Mode::FromStdin, args.source, args.target)
+            .await;
+    }
+
+    if args.words.is_empty() {
+        translate
+            .run(Mode::Interact, args.source, args.target)
+            .await
+    } else {
+        translate
+            .run(Mode::Batch(args.words), args.source, args.target)
+            .await
+    }
+}
+
+/// translate by the running daemon, every word is a request, or the whole stdin
+async fn forward(args: &Args) -> anyhow::Result<()> {
+    let texts = if io::stdin().is_terminal() {
+        args.words.clone()
+    } else {
+        vec![io::read_to_string(io::stdin())?]
+    };
+    if texts.iter().all(|text| text.trim().is_empty()) {
+        return Err(anyhow::anyhow!("--daemon needs words or stdin text"));
+    }
+
+    let requests = texts
+        .into_iter()
+        .map(|text| Request::new(text, args.source, args.target))
+        .collect();
+    for translation in daemon::forward(requests).await? {
+        println!("{}", translation.text);
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "credential-store"), allow(unused_variables))]
+async fn run_command(command: Command, args: &Args) -> anyhow::Result<()> {
+    match command {
+        Command::Bench {
+            words,
+            concurrency,
+            refill_interval,
+            latency,
+        } => {
+            let report = bench::run(
+                words,
+                concurrency,
+                Duration::from_millis(refill_interval),
+                Duration::from_millis(latency),
+            )
+            .await;
+
+            println!(
+                "{} words in {:.2}s, {:.2} words/s",
+                report.words,
+                report.elapsed.as_secs_f64(),
+                report.throughput()
+            );
+
+            Ok(())
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Status) => {
+            let status = credential::status(&Profile::new(
+                args.credential_store,
+                args.profile.as_deref(),
+            )?)?;
+            println!("store: {}", status.store);
+            println!("profile: {}", status.profile);
+            for (name, set) in status.set {
+                println!("{name}: {}", if set { "set" } else { "not set" });
+            }
+
+            match status.age {
+                None => println!("created: unknown"),
+                Some(age) => {
+                    println!("created: {} days ago", age.as_secs() / SECS_PER_DAY);
+                    credential::warn_age(age, credential_max_age(args.credential_max_age));
+                }
+            }
+
+            Ok(())
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Set { name }) => {
+            if args.ci || args.read_only {
+                return Err(anyhow::anyhow!(
+                    "ci and read-only mode never write to the credential store"
+                ));
+            }
+            let name = name.as_deref().or(args.profile.as_deref());
+
+            credential::set(
+                &Profile::new(args.credential_store, name)?,
+                &credential_overrides(args)?,
+            )
+            .await
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Show) => {
+            let profile = Profile::new(args.credential_store, args.profile.as_deref())?;
+            println!("profile: {}", profile.name());
+            for shown in credential::show(&profile, &credential_overrides(args)?)? {
+                match shown.value {
+                    None => println!("{}: not set", shown.name),
+                    Some(value) => println!("{}: {value} ({})", shown.name, shown.from),
+                }
+            }
+
+            Ok(())
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Test) => {
+            let credentials = tmt_credentials(args)?;
+            let region = credentials.2.clone();
+            credential::test(credentials).await?;
+            println!("credentials are valid, region: {region}");
+
+            Ok(())
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::List) => {
+            for name in credential::list(args.credential_store)? {
+                println!("{name}");
+            }
+
+            Ok(())
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Clear { name }) => {
+            if args.ci || args.read_only {
+                return Err(anyhow::anyhow!(
+                    "ci and read-only mode never write to the credential store"
+                ));
+            }
+            let name = name.as_deref().or(args.profile.as_deref());
+
+            credential::remove(&Profile::new(args.credential_store, name)?)
+        }
+
+        #[cfg(feature = "credential-store")]
+        Command::Auth(Auth::Migrate { to }) => {
+            if args.ci || args.read_only {
+                return Err(anyhow::anyhow!(
+                    "ci and read-only mode never write to the credential store"
+                ));
+            }
+
+            let from = args.credential_store.available();
+            for name in credential::migrate(from, to)? {
+                println!("{name}: migrated");
+            }
+            println!("credentials are moved from the {from} store to the {to} store");
+
+            Ok(())
+        }
+
+        Command::Cache(Cache::Clear) => cache::clear(),
+
+        Command::Cache(Cache::Verify) => {
+            let verified = cache::verify()?;
+            println!(
+                "cache: {} entries, {} broken entries removed",
+                verified.entries, verified.removed
+            );
+
+            let (entries, removed) = History::open()?.verify()?;
+            println!("history: {entries} entries, {removed} broken lines removed");
+
+            Ok(())
+        }
+
+        Command::Status => {
+            let config = Config::load(args.config.as_deref())?;
+            let configured = args.backend.or(config.backend).unwrap_or_default();
+            let usage = Usage::open()?;
+
+            let mut statuses = vec![];
+            for (backend, client) in [
+                (Backend::Tmt, tmt_credentials(args).map(status::tmt)),
+                (Backend::Deepl, deepl_api_key(args).map(status::deepl)),
+            ] {
+                // the flags and config only set the rate limit of the configured backend
+                let rate_limit = if backend == configured {
+                    rate_limit(args, &config, backend)
+                } else {
+                    None
+                };
+                let capabilities = backend.capabilities();
+                statuses.push(
+                    status::check(
+                        capabilities.provider,
+                        client,
+                        rate_limit.unwrap_or(capabilities.rate_limit),
+                        &usage,
+                    )
+                    .await,
+                );
+            }
+
+            status::print(&statuses, io::stdout().lock())
+        }
+
+        Command::Stats => {
+            let warning = Config::load(args.config.as_deref())?
+                .usage_warning
+                .unwrap_or(usage::DEFAULT_WARNING);
+
+            Usage::open()?.print(warning, io::stdout().lock())
+        }
+
+        Command::Glossary(command) => {
+            let path = args
+                .glossary
+                .clone()
+                .or_else(Glossary::default_path)
+                .ok_or_else(|| anyhow::anyhow!("can't find config dir"))?;
+            let mut glossary = Glossary::load_or_default(&path)?;
+
+            match command {
+                GlossaryCommand::List => {
+                    for term in glossary.terms() {
+                        println!("{} -> {}", term.source, term.target);
+                    }
+
+                    return Ok(());
+                }
+
+                _ if args.ci || read_only(args) => {
+                    return Err(anyhow::anyhow!(
+                        "ci and read-only mode never write to the glossary"
+                    ));
+                }
+
+                GlossaryCommand::Add { term, translation } => glossary.add(Term {
+                    source: term.trim().to_string(),
+                    target: translation.trim().to_string(),
+                }),
+
+                GlossaryCommand::Remove { term } => {
+                    if !glossary.remove(term.trim()) {
+                        return Err(anyhow::anyhow!(
+                            "term {term} is not in glossary {}",
+                            path.display()
+                        ));
+                    }
+                }
+            }
+
+            glossary.save(&path)
+        }
+
+        Command::Exec { .. } | Command::Serve => {
+            unreachable!("exec and serve run with the translate options")
+        }
+        #[cfg(feature = "clipboard")]
+        Command::Watch { .. } => unreachable!("watch runs with the translate options"),
+
+        Command::History {
+            search,
+            limit,
+            format,
+        } => history::print(
+            &History::open()?.entries()?,
+            search.as_deref(),
+            limit,
+            format,
+            io::stdout().lock(),
+        ),
+
+        Command::Languages { output } => languages::print(output, io::stdout().lock()),
+
+        Command::Export(Export::Corpus { pair, format }) => {
+            let memory = TranslationMemory::open()?;
+
+            corpus::export(
+                memory.segments(),
+                pair.as_ref(),
+                format,
+                io::stdout().lock(),
+            )
+        }
+    }
+}
+
+/// the flags override the rate limit of config, or of the backend
+fn rate_limit(args: &Args, config: &Config, backend: Backend) -> Option<RateLimit> {
+    match (args.max_concurrent, args.qps) {
+        (None, None) => config.rate_limit,
+        (max_concurrent, qps) => {
+            let rate_limit = config
+                .rate_limit
+                .unwrap_or(backend.capabilities().rate_limit);
+
+            Some(RateLimit {
+                max_concurrent: max_concurrent.unwrap_or(rate_limit.max_concurrent),
+                refill_interval: qps.map_or(rate_limit.refill_interval, RateLimit::qps_interval),
+                ..rate_limit
+            })
+        }
+    }
+}
+
+/// positive requests per second, at most one request per millisecond
+fn qps(value: &str) -> anyhow::Result<f64> {
+    let qps = value.parse::<f64>()?;
+    if !qps.is_finite() || qps <= 0.0 || qps > 1000.0 {
+        return Err(anyhow::anyhow!("qps must be positive and at most 1000"));
+    }
+
+    Ok(qps)
+}
+
+/// read-only mode exists only with a credential store
+#[cfg(feature = "credential-store")]
+fn read_only(args: &Args) -> bool {
+    args.read_only
+}
+
+#[cfg(not(feature = "credential-store"))]
+fn read_only(_args: &Args) -> bool {
+    false
+}
+
+/// see `interrupt::interrupt`, the terminal is restored before exiting
+fn install_interrupt_handler() -> anyhow::Result<()> {
+    ctrlc::set_handler(|| {
+        if !interrupt::interrupt() {
+            #[cfg(feature = "tui")]
+            terminal::restore();
+
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    })?;
+
+    Ok(())
+}
+
+#[cfg(feature = "credential-store")]
+fn credential_max_age(days: u64) -> Duration {
+    Duration::from_secs(days * SECS_PER_DAY)
+}
+
+/// the credentials passed by the flags, the environment variables and the region of config, same
+/// as the ones used for translating
+fn credential_overrides(args: &Args) -> anyhow::Result<Overrides> {
+    let mut overrides = Overrides::new(
+        args.secret_id.clone(),
+        args.secret_key.clone(),
+        args.region.clone(),
+    );
+    overrides.region = overrides
+        .region
+        .or(Config::load(args.config.as_deref())?.region);
+
+    Ok(overrides)
+}
+
+/// the tmt credentials of the overrides or the store, never asked
+#[cfg(feature = "credential-store")]
+fn tmt_credentials(args: &Args) -> anyhow::Result<Credentials> {
+    let overrides = credential_overrides(args)?;
+    match overrides.complete() {
+        Some(credentials) => Ok(credentials),
+        None => credential::stored(
+            &Profile::new(args.credential_store, args.profile.as_deref())?,
+            &overrides,
+        ),
+    }
+}
+
+#[cfg(not(feature = "credential-store"))]
+fn tmt_credentials(args: &Args) -> anyhow::Result<Credentials> {
+    credential_overrides(args)?.require()
+}
+
+/// the deepl api key of DEEPL_API_KEY or the store, never asked
+#[cfg(feature = "credential-store")]
+fn deepl_api_key(args: &Args) -> anyhow::Result<String> {
+    credential::deepl_from_env().or_else(|_| {
+        credential::stored_deepl(&Profile::new(
+            args.credential_store,
+            args.profile.as_deref(),
+        )?)
+    })
+}
+
+#[cfg(not(feature = "credential-store"))]
+fn deepl_api_key(_args: &Args) -> anyhow::Result<String> {
+    credential::deepl_from_env()
+}
@@ -0,0 +1,343 @@
+//! the responses ignore unknown fields, and the fields which tmt may omit or set to null are
+//! defaulted, so new fields or dropped optional fields of tmt don't break the deployed binaries
+
+use serde::{Deserialize, Deserializer};
+
+/// define a tmt action, the unit struct of the action named as the action, the request and the
+/// response, the fields are renamed to PascalCase like `source_text` to `SourceText`, an explicit
+/// `#[serde(rename = "..")]` overrides it
+macro_rules! tencent_api {
+    (
+        $(#[$meta:meta])*
+        $api:ident {
+            $(#[$request_meta:meta])*
+            request $request:ident {
+                $($(#[$request_field_meta:meta])* $request_field:ident: $request_type:ty),* $(,)?
+            }
+
+            $(#[$response_meta:meta])*
+            response $response:ident {
+                $($(#[$response_field_meta:meta])* $response_field:ident: $response_type:ty),* $(,)?
+            }
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone)]
+        pub struct $api;
+
+        $(#[$request_meta])*
+        #[derive(Debug, Clone, ::serde::Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        pub struct $request {
+            $($(#[$request_field_meta])* pub $request_field: $request_type,)*
+        }
+
+        $(#[$response_meta])*
+        #[derive(Debug, Clone, ::serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        pub struct $response {
+            $($(#[$response_field_meta])* pub $response_field: $response_type,)*
+        }
+
+        impl ::tencentcloud::api::Api for $api {
+            type Request = $request;
+            type Response = $response;
+            const VERSION: &'static str = "2018-03-21";
+            const ACTION: &'static str = stringify!($api);
+            const SERVICE: &'static str = "tmt";
+            const HOST: &'static str = "tmt.tencentcloudapi.com";
+        }
+    };
+}
+
+/// the default value when the field is null, use with `#[serde(default)]` for a missing field
+fn null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+pub mod text_translate {
+    tencent_api! {
+        TextTranslate {
+            request TextTranslateRequest {
+                source_text: String,
+                source: String,
+                target: String,
+                project_id: i64,
+                /// terminology repositories created in the tmt console
+                #[serde(rename = "TermRepoIDList", skip_serializing_if = "Vec::is_empty")]
+                term_repo_ids: Vec<String>,
+            }
+
+            response TextTranslateResponse {
+                #[serde(default, deserialize_with = "super::null_default")]
+                source: String,
+                #[serde(default, deserialize_with = "super::null_default")]
+                target: String,
+                target_text: String,
+            }
+        }
+    }
+}
+
+pub mod text_translate_batch {
+    tencent_api! {
+        /// translate several texts of the same language in a request
+        TextTranslateBatch {
+            request TextTranslateBatchRequest {
+                source: String,
+                target: String,
+                project_id: i64,
+                source_text_list: Vec<String>,
+            }
+
+            /// the translations are in the order of the texts
+            response TextTranslateBatchResponse {
+                #[serde(default, deserialize_with = "super::null_default")]
+                source: String,
+                #[serde(default, deserialize_with = "super::null_default")]
+                target: String,
+                target_text_list: Vec<String>,
+            }
+        }
+    }
+}
+
+pub mod language_detect {
+    tencent_api! {
+        LanguageDetect {
+            request LanguageDetectRequest {
+                text: String,
+                project_id: i64,
+            }
+
+            response LanguageDetectResponse {
+                lang: String,
+            }
+        }
+    }
+}
+
+pub mod image_translate {
+    use serde::Deserialize;
+
+    tencent_api! {
+        ImageTranslate {
+            request ImageTranslateRequest {
+                session_uuid: String,
+                /// only `doc` is supported
+                scene: String,
+                /// base64 encoded png or jpg image
+                data: String,
+                source: String,
+                target: String,
+                project_id: i64,
+            }
+
+            response ImageTranslateResponse {
+                image_record: ImageRecord,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    pub struct ImageRecord {
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub value: Vec<ItemValue>,
+    }
+
+    /// a recognized text line
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    pub struct ItemValue {
+        pub source_text: String,
+        pub target_text: String,
+        /// the region of the text line in the image, in pixels
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub x: i64,
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub y: i64,
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub w: i64,
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub h: i64,
+    }
+}
+
+pub mod speech_translate {
+    /// pcm, 16k sample rate, 16 bit, mono
+    pub const AUDIO_FORMAT_PCM: i64 = 1;
+    /// the recognition of the session is finished
+    pub const RECOGNIZE_STATUS_DONE: i64 = 0;
+
+    tencent_api! {
+        /// recognize and translate a chunk of audio, the chunks of a session are sent in order
+        SpeechTranslate {
+            request SpeechTranslateRequest {
+                session_uuid: String,
+                source: String,
+                target: String,
+                audio_format: i64,
+                /// counted from 0
+                seq: i64,
+                /// 1 for the last chunk
+                is_end: i64,
+                /// base64 encoded audio chunk
+                data: String,
+                project_id: i64,
+            }
+
+            /// the recognized and translated text of the current sentence
+            response SpeechTranslateResponse {
+                /// 1 while recognizing, 0 when done
+                recognize_status: i64,
+                #[serde(default, deserialize_with = "super::null_default")]
+                source_text: String,
+                #[serde(default, deserialize_with = "super::null_default")]
+                target_text: String,
+                /// the sentence number, increased when a pause is detected
+                #[serde(default, deserialize_with = "super::null_default")]
+                vad_seq: i64,
+            }
+        }
+    }
+}
+
+pub mod file_translate {
+    use serde::Deserialize;
+
+    /// source type of the document is base64 encoded data instead of url
+    pub const SOURCE_TYPE_DATA: i64 = 1;
+
+    tencent_api! {
+        /// submit a document translation task
+        FileTranslate {
+            request FileTranslateRequest {
+                source: String,
+                target: String,
+                /// file extension, like `docx`, `pdf` or `xlsx`
+                document_type: String,
+                source_type: i64,
+                /// base64 encoded document
+                data: String,
+                project_id: i64,
+            }
+
+            response FileTranslateResponse {
+                data: Task,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    pub struct Task {
+        pub task_id: String,
+    }
+
+    tencent_api! {
+        /// query the status of a document translation task
+        GetFileTranslate {
+            request GetFileTranslateRequest {
+                task_id: String,
+            }
+
+            response GetFileTranslateResponse {
+                data: TaskStatus,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    pub struct TaskStatus {
+        pub status: TaskState,
+        /// base64 encoded translated document, only set when succeeded
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub file_data: String,
+        /// failure reason
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub message: String,
+        /// percent
+        #[serde(default, deserialize_with = "super::null_default")]
+        pub progress: i64,
+    }
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum TaskState {
+        Init,
+        Wait,
+        Success,
+        Fail,
+        /// the states added by tmt later, treated as in progress
+        #[serde(other)]
+        Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tencentcloud::api::Api;
+
+    use super::file_translate::{GetFileTranslateResponse, TaskState};
+    use super::image_translate::ImageTranslateResponse;
+    use super::text_translate::*;
+
+    #[test]
+    fn rename_fields() {
+        let request = TextTranslateRequest {
+            source_text: "hello".to_string(),
+            source: "en".to_string(),
+            target: "zh".to_string(),
+            project_id: 0,
+            term_repo_ids: vec!["repo".to_string()],
+        };
+
+        assert_eq!(
+            serde_json::to_value(request).unwrap(),
+            serde_json::json!({
+                "SourceText": "hello",
+                "Source": "en",
+                "Target": "zh",
+                "ProjectId": 0,
+                "TermRepoIDList": ["repo"],
+            })
+        );
+        assert_eq!(TextTranslate::ACTION, "TextTranslate");
+    }
+
+    /// the fixtures have unknown fields, null and missing optional fields, which tmt may return
+    /// in the future
+    #[test]
+    fn tolerate_response_changes() {
+        let resp = serde_json::from_str::<TextTranslateResponse>(include_str!(
+            "fixtures/text_translate.json"
+        ))
+        .unwrap();
+        assert_eq!(resp.target_text, "你好");
+        assert_eq!(resp.source, "en");
+        assert_eq!(resp.target, "");
+
+        let resp = serde_json::from_str::<ImageTranslateResponse>(include_str!(
+            "fixtures/image_translate.json"
+        ))
+        .unwrap();
+        let lines = resp.image_record.value;
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].x, lines[0].w), (12, 86));
+        assert_eq!((lines[1].x, lines[1].w), (0, 0));
+
+        let resp = serde_json::from_str::<GetFileTranslateResponse>(include_str!(
+            "fixtures/get_file_translate.json"
+        ))
+        .unwrap();
+        assert_eq!(resp.data.status, TaskState::Unknown);
+        assert_eq!(resp.data.file_data, "");
+        assert_eq!(resp.data.progress, 40);
+    }
+}
@@ -0,0 +1,89 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// distinguishes the temporary files of concurrent writes in the process
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// replace the file by a synced temporary file in the same dir, readers and a crash see either
+/// the old or the new content, never a partial one
+pub fn write(path: &Path, data: &[u8]) -> io::Result<()> {
+    write_with(path, data, false)
+}
+
+/// same as write, only the user can read the file
+pub fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    write_with(path, data, true)
+}
+
+/// lock `<path>.lock` exclusively until the returned file is dropped, other txcv processes wait
+/// for it, so a read-modify-write of the file doesn't lose their updates
+pub fn lock(path: &Path) -> io::Result<fs::File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut lock_path = OsString::from(path.as_os_str());
+    lock_path.push(".lock");
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(PathBuf::from(lock_path))?;
+    file.lock()?;
+
+    Ok(file)
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_with(path: &Path, data: &[u8], private: bool) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    // concurrent writers write their own temporary files
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(format!(
+        ".{}-{}.tmp",
+        process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = PathBuf::from(tmp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+
+    let result = options
+        .open(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+
+    sync_dir(dir)
+}
+
+/// persist the rename
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,379 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::Serialize;
+use tencentcloud::Client;
+
+use crate::api::language_detect::{LanguageDetect, LanguageDetectRequest};
+use crate::api::text_translate::{TextTranslate, TextTranslateRequest};
+use crate::api::text_translate_batch::{TextTranslateBatch, TextTranslateBatchRequest};
+use crate::config::{RateLimit, DEFAULT_RATE_LIMIT};
+use crate::translate::{Provider, Translation};
+use crate::{deepl, diagnostic};
+
+/// tmt rejects the text of 6000 or more characters
+const TMT_MAX_CHARS: usize = 5000;
+/// texts of a tmt batch request, their total characters are also limited by TMT_MAX_CHARS
+const TMT_MAX_BATCH: usize = 50;
+
+pub const TMT_CAPABILITIES: Capabilities = Capabilities {
+    provider: Provider::Tmt,
+    languages: &[
+        "zh", "zh-TW", "en", "jp", "ko", "fr", "es", "it", "de", "tr", "ru", "pt", "vi", "id",
+        "th", "ms", "ar", "hi",
+    ],
+    max_chars: Some(TMT_MAX_CHARS),
+    max_batch: TMT_MAX_BATCH,
+    rate_limit: DEFAULT_RATE_LIMIT,
+    context: true,
+    image: true,
+    document: true,
+    offline: false,
+};
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Backend {
+    /// tencent machine translation
+    #[default]
+    Tmt,
+    /// deepl, needs a deepl api key
+    Deepl,
+}
+
+impl Backend {
+    pub fn capabilities(self) -> &'static Capabilities {
+        match self {
+            Backend::Tmt => &TMT_CAPABILITIES,
+            Backend::Deepl => &deepl::CAPABILITIES,
+        }
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Tmt => f.write_str("tmt"),
+            Backend::Deepl => f.write_str("deepl"),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tmt" => Ok(Backend::Tmt),
+            "deepl" => Ok(Backend::Deepl),
+            _ => Err(anyhow::anyhow!(
+                "invalid backend {s}, should be tmt or deepl"
+            )),
+        }
+    }
+}
+
+/// a machine translation provider, languages are passed as language codes
+pub trait TranslateBackend: Debug + Send + Sync {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>>;
+
+    /// translate the text with a hint of where it's used, like `checkout page button`, so short
+    /// texts like `Book` or `May` are disambiguated, the hint is ignored by default
+    fn translate_in_context<'a>(
+        &'a self,
+        text: &'a str,
+        _context: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        self.translate(text, source, target)
+    }
+
+    /// translate the texts of the same language, the translations are in the order of the texts,
+    /// translated one by one if the backend has no batch api
+    fn translate_batch<'a>(
+        &'a self,
+        texts: &'a [String],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<Translation>>> {
+        async move {
+            let mut translations = Vec::with_capacity(texts.len());
+            for text in texts {
+                translations.push(self.translate(text, source, target).await?);
+            }
+
+            Ok(translations)
+        }
+        .boxed()
+    }
+
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>>;
+
+    /// max texts of a batch request, 1 if the backend has no batch api
+    fn max_batch(&self) -> usize {
+        1
+    }
+
+    /// codes of the target languages
+    fn supported_languages(&self) -> &'static [&'static str];
+
+    fn provider(&self) -> Provider;
+
+    /// used when the rate limit is not configured
+    fn rate_limit(&self) -> RateLimit {
+        RateLimit::default()
+    }
+
+    /// max characters of a request, used when it's not configured, none is unlimited
+    fn max_chars(&self) -> Option<usize> {
+        None
+    }
+
+    /// the tencent cloud client for the apis only tmt provides, like image and document
+    /// translation
+    fn tencent_client(&self) -> Option<&Client> {
+        None
+    }
+}
+
+/// what a provider supports, known without its credentials, so the languages can be listed
+/// before logging in
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    pub provider: Provider,
+    /// codes of the languages translated from and to each other
+    pub languages: &'static [&'static str],
+    /// max characters of a request, none is unlimited
+    pub max_chars: Option<usize>,
+    /// max texts of a request
+    pub max_batch: usize,
+    /// used when the rate limit is not configured
+    #[serde(skip)]
+    pub rate_limit: RateLimit,
+    /// `--context` hints are used
+    pub context: bool,
+    /// images are translated
+    pub image: bool,
+    /// documents are translated by the provider
+    pub document: bool,
+    /// works without network
+    pub offline: bool,
+}
+
+/// the backend rejects the request because of its rate limit, the request can be retried
+#[derive(Debug)]
+pub struct RateLimited;
+
+impl Display for RateLimited {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("too many requests")
+    }
+}
+
+impl Error for RateLimited {}
+
+/// the backend rejects the request because the text is too long, the text can be split and
+/// retried
+#[derive(Debug)]
+pub struct TextTooLong;
+
+impl Display for TextTooLong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("text is too long")
+    }
+}
+
+impl Error for TextTooLong {}
+
+/// tencent machine translation
+#[derive(Debug)]
+pub struct Tmt {
+    client: Client,
+    /// terminology repositories created in the tmt console
+    term_repos: Vec<String>,
+}
+
+impl Tmt {
+    pub fn new(client: Client, term_repos: Vec<String>) -> Self {
+        Self { client, term_repos }
+    }
+}
+
+impl TranslateBackend for Tmt {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        async move {
+            let action = format!(
+                "tmt TextTranslate {source} -> {target}, {} characters",
+                text.chars().count()
+            );
+            let request = TextTranslateRequest {
+                source_text: text.to_string(),
+                source: source.to_string(),
+                target: target.to_string(),
+                project_id: 0,
+                term_repo_ids: self.term_repos.clone(),
+            };
+            let resp = match diagnostic::timed(action, self.client.send::<TextTranslate>(&request))
+                .await
+            {
+                Err(tencentcloud::Error::Api { err, .. })
+                    if err.code == "UnsupportedOperation.TextTooLong" =>
+                {
+                    return Err(TextTooLong.into())
+                }
+
+                Err(err) => return Err(err.into()),
+                Ok((resp, _)) => resp,
+            };
+
+            diagnostic::trace(format_args!("{text:?} -> {:?}", resp.target_text));
+
+            Ok(Translation {
+                source_lang: resp.source,
+                target_lang: resp.target,
+                text: resp.target_text,
+            })
+        }
+        .boxed()
+    }
+
+    /// tmt has no context field, the context is translated as the line before the text and the
+    /// translation of the last line is taken, the text is translated alone if the lines of the
+    /// translation don't match
+    fn translate_in_context<'a>(
+        &'a self,
+        text: &'a str,
+        context: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        async move {
+            if !text.contains('\n') && !context.contains('\n') {
+                let contextual = format!("{context}\n{text}");
+                let translation = self.translate(&contextual, source, target).await?;
+                if let [_, line] = translation.text.lines().collect::<Vec<_>>()[..] {
+                    return Ok(Translation {
+                        text: line.trim().to_string(),
+                        ..translation
+                    });
+                }
+            }
+
+            self.translate(text, source, target).await
+        }
+        .boxed()
+    }
+
+    fn translate_batch<'a>(
+        &'a self,
+        texts: &'a [String],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<Translation>>> {
+        async move {
+            let action = format!(
+                "tmt TextTranslateBatch {source} -> {target}, {} texts",
+                texts.len()
+            );
+            let request = TextTranslateBatchRequest {
+                source: source.to_string(),
+                target: target.to_string(),
+                project_id: 0,
+                source_text_list: texts.to_vec(),
+            };
+            let resp =
+                match diagnostic::timed(action, self.client.send::<TextTranslateBatch>(&request))
+                    .await
+                {
+                    Err(tencentcloud::Error::Api { err, .. })
+                        if err.code == "UnsupportedOperation.TextTooLong" =>
+                    {
+                        return Err(TextTooLong.into())
+                    }
+
+                    Err(err) => return Err(err.into()),
+                    Ok((resp, _)) => resp,
+                };
+            if resp.target_text_list.len() != texts.len() {
+                return Err(anyhow::anyhow!(
+                    "batch translation returns {} texts for {} texts",
+                    resp.target_text_list.len(),
+                    texts.len()
+                ));
+            }
+
+            for (text, translated) in texts.iter().zip(&resp.target_text_list) {
+                diagnostic::trace(format_args!("{text:?} -> {translated:?}"));
+            }
+
+            Ok(resp
+                .target_text_list
+                .into_iter()
+                .map(|text| Translation {
+                    source_lang: resp.source.clone(),
+                    target_lang: resp.target.clone(),
+                    text,
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        async move {
+            let request = LanguageDetectRequest {
+                text: text.to_string(),
+                project_id: 0,
+            };
+            match diagnostic::timed(
+                "tmt LanguageDetect",
+                self.client.send::<LanguageDetect>(&request),
+            )
+            .await
+            {
+                Err(tencentcloud::Error::Api { err, .. })
+                    if err.code == "FailedOperation.LanguageRecognitionErr" =>
+                {
+                    Ok("zh".to_string())
+                }
+
+                Err(err) => Err(err.into()),
+                Ok((resp, _)) => Ok(resp.lang),
+            }
+        }
+        .boxed()
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        TMT_CAPABILITIES.languages
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Tmt
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        Some(TMT_MAX_CHARS)
+    }
+
+    fn max_batch(&self) -> usize {
+        TMT_MAX_BATCH
+    }
+
+    fn tencent_client(&self) -> Option<&Client> {
+        Some(&self.client)
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+use async_std::task;
+use futures_util::{stream, StreamExt};
+
+use crate::rate_limit::LeakyBucket;
+
+#[derive(Debug)]
+pub struct BenchReport {
+    pub words: u32,
+    pub elapsed: Duration,
+}
+
+impl BenchReport {
+    pub fn throughput(&self) -> f64 {
+        self.words as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// translate words with a mock provider which responds after latency, the requests are limited
+/// by concurrency and the rate limiter like batch mode
+pub async fn run(
+    words: u32,
+    concurrency: u32,
+    refill_interval: Duration,
+    latency: Duration,
+) -> BenchReport {
+    let bucket = LeakyBucket::builder()
+        .max(concurrency)
+        .refill_interval(refill_interval)
+        .tokens(concurrency)
+        .build();
+
+    let start = Instant::now();
+    stream::iter(0..words)
+        .map(|_| async {
+            // the bucket is never closed
+            let _permit = bucket.acquire_one().await;
+
+            task::sleep(latency).await;
+        })
+        .buffered(concurrency as _)
+        .for_each(|_| async {})
+        .await;
+
+    BenchReport {
+        words,
+        elapsed: start.elapsed(),
+    }
+}
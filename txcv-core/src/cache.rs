@@ -0,0 +1,264 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+
+const CACHE_DIR: &str = "cache";
+/// the cache database of old versions, removed by `txcv cache clear`
+const LEGACY_CACHE_DB: &str = "cache.sled";
+pub const DEFAULT_TTL_DAYS: u64 = 30;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+/// the temporary files older than it are left by crashes
+const TMP_FILE_MAX_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// the full key, different keys may have the same hash
+    key: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub text: String,
+    /// unix timestamp
+    created_at: u64,
+}
+
+/// translation results cached under the cache dir, keyed by source text, source language,
+/// target language, terminology repositories and context
+///
+/// every entry is a file named by the key hash and replaced atomically, so concurrent txcv
+/// share the cache without locks, the last write wins
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// result of verifying the cache
+#[derive(Debug, Default)]
+pub struct Verified {
+    pub entries: usize,
+    /// entries which can't be parsed and temporary files left by crashes
+    pub removed: usize,
+}
+
+impl Cache {
+    pub fn open(ttl_days: u64) -> anyhow::Result<Self> {
+        let dir = path()?;
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("open translation cache {} failed", dir.display()))?;
+
+        Ok(Self {
+            dir,
+            ttl: Duration::from_secs(ttl_days * SECS_PER_DAY),
+        })
+    }
+
+    /// the cache under the dir instead of the cache dir
+    #[cfg(test)]
+    pub fn in_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            ttl: Duration::from_secs(DEFAULT_TTL_DAYS * SECS_PER_DAY),
+        }
+    }
+
+    /// get the cached translation, the expired one is removed, a broken entry is a miss
+    pub fn get(&self, key: &Key) -> anyhow::Result<Option<Entry>> {
+        let key = key.encode();
+        let path = self.entry_path(&key);
+        let entry = match fs::read(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+            Ok(data) => match serde_json::from_slice::<Entry>(&data) {
+                Ok(entry) if entry.key == key => entry,
+                _ => return Ok(None),
+            },
+        };
+
+        let age = now()?.saturating_sub(entry.created_at);
+        if age > self.ttl.as_secs() {
+            // another txcv may remove it at the same time
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some(entry))
+    }
+
+    pub fn insert(
+        &self,
+        key: &Key,
+        source_lang: &str,
+        target_lang: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let key = key.encode();
+        let path = self.entry_path(&key);
+        let entry = Entry {
+            key,
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+            text: text.to_string(),
+            created_at: now()?,
+        };
+        atomic::write(&path, &serde_json::to_vec(&entry)?)?;
+
+        Ok(())
+    }
+
+    /// `<dir>/<first 2 hex digits>/<hash>.json`
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let hash = format!("{:016x}", fnv1a(key.as_bytes()));
+
+        self.dir.join(&hash[..2]).join(format!("{hash}.json"))
+    }
+}
+
+/// what a translation depends on
+#[derive(Debug)]
+pub struct Key<'a> {
+    pub text: &'a str,
+    /// `auto` when detected
+    pub source: &'a str,
+    /// `auto` when decided by the source language
+    pub target: &'a str,
+    pub term_repos: &'a [String],
+    /// the hint of where the text is used, empty if none
+    pub context: &'a str,
+}
+
+impl Key<'_> {
+    fn encode(&self) -> String {
+        let term_repos = self.term_repos.join(",");
+        let mut parts = vec![self.source, self.target, &term_repos];
+        // the keys without context are the same as before, so the old entries are still hit
+        if !self.context.is_empty() {
+            parts.push(self.context);
+        }
+        parts.push(self.text);
+
+        parts.join("\0")
+    }
+}
+
+/// check every cached translation can be read, remove the broken entries and the temporary files
+/// left by crashes, the entries are replaced atomically so a crash never breaks the others
+pub fn verify() -> anyhow::Result<Verified> {
+    let mut verified = Verified::default();
+    let dir = path()?;
+    let shards = match fs::read_dir(&dir) {
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(verified),
+        result => result.with_context(|| format!("read {} failed", dir.display()))?,
+    };
+
+    for shard in shards {
+        let shard = shard?.path();
+        if !shard.is_dir() {
+            continue;
+        }
+
+        for file in fs::read_dir(&shard)? {
+            let file = file?.path();
+            if valid_entry(&file) {
+                verified.entries += 1;
+                continue;
+            }
+            if writing(&file) {
+                continue;
+            }
+
+            match fs::remove_file(&file) {
+                Err(err) if err.kind() != ErrorKind::NotFound => {
+                    return Err(err).with_context(|| format!("remove {} failed", file.display()))
+                }
+                _ => verified.removed += 1,
+            }
+        }
+    }
+
+    Ok(verified)
+}
+
+fn valid_entry(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+        && fs::read(path).is_ok_and(|data| serde_json::from_slice::<Entry>(&data).is_ok())
+}
+
+/// the temporary file is being written by a running txcv
+fn writing(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "tmp")
+        && fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().unwrap_or_default() < TMP_FILE_MAX_AGE)
+}
+
+/// remove all cached translations
+pub fn clear() -> anyhow::Result<()> {
+    let path = path()?;
+    let legacy_path = path.with_file_name(LEGACY_CACHE_DB);
+
+    for path in [path, legacy_path] {
+        match fs::remove_dir_all(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            result => result
+                .with_context(|| format!("remove translation cache {} failed", path.display()))?,
+        }
+    }
+
+    Ok(())
+}
+
+fn path() -> anyhow::Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .context("can't find cache dir")?
+        .join("txcv")
+        .join(CACHE_DIR))
+}
+
+/// 64 bits fnv-1a, stable across builds unlike the std hasher
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn key_with_context() {
+        let mut key = Key {
+            text: "Book",
+            source: "en",
+            target: "zh",
+            term_repos: &[],
+            context: "",
+        };
+        assert_eq!(key.encode(), "en\0zh\0\0Book");
+
+        key.context = "checkout page button";
+        assert_eq!(key.encode(), "en\0zh\0\0checkout page button\0Book");
+    }
+}
@@ -0,0 +1,179 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use async_std::channel::{self, Receiver, Sender};
+use futures_timer::Delay;
+use futures_util::future::{self, Either, FutureExt};
+use futures_util::pin_mut;
+
+/// why a task is cancelled
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Cancelled {
+    /// the token or one of its parents is cancelled, like shutting down by ctrl-c
+    Cancelled,
+    /// the deadline of the token or one of its parents has passed
+    DeadlineExceeded,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cancelled::Cancelled => f.write_str("cancelled"),
+            Cancelled::DeadlineExceeded => f.write_str("deadline exceeded"),
+        }
+    }
+}
+
+impl Error for Cancelled {}
+
+/// cancels a tree of tasks, cancelling a token cancels all its children but not its parent, a
+/// child inherits the deadline of its parent and may have an earlier one, the tasks run by the
+/// token are dropped when cancelled, which releases their rate limit permits and stops their
+/// retries
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    /// the channels of all its parents and the token at last, a channel is closed when its token
+    /// is cancelled, nothing is sent, the parent senders are kept so dropping a parent doesn't
+    /// close them
+    channels: Vec<(Sender<()>, Receiver<()>)>,
+    deadline: Option<Instant>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self {
+            channels: vec![channel::bounded(1)],
+            deadline: None,
+        }
+    }
+
+    pub fn child(&self) -> Self {
+        let mut channels = self.channels.clone();
+        channels.push(channel::bounded(1));
+
+        Self {
+            channels,
+            deadline: self.deadline,
+        }
+    }
+
+    /// a child which also expires after the timeout, none keeps the deadline of the parent
+    pub fn child_with_timeout(&self, timeout: Option<Duration>) -> Self {
+        let mut child = self.child();
+        if let Some(timeout) = timeout {
+            let deadline = Instant::now() + timeout;
+            child.deadline = Some(
+                child
+                    .deadline
+                    .map_or(deadline, |parent| parent.min(deadline)),
+            );
+        }
+
+        child
+    }
+
+    /// cancel the token and its children, false if it's cancelled already
+    pub fn cancel(&self) -> bool {
+        self.channels
+            .last()
+            .is_some_and(|(sender, _)| sender.close())
+    }
+
+    /// the token is cancelled or expired
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self
+            .channels
+            .iter()
+            .any(|(_, receiver)| receiver.is_closed())
+        {
+            return Err(Cancelled::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Cancelled::DeadlineExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// wait until the token is cancelled or expired
+    pub async fn cancelled(&self) -> Cancelled {
+        let cancelled = future::select_all(
+            self.channels
+                .iter()
+                .map(|(_, receiver)| receiver.recv().boxed()),
+        );
+        let Some(deadline) = self.deadline else {
+            let _ = cancelled.await;
+
+            return Cancelled::Cancelled;
+        };
+
+        let expired = Delay::new(deadline.saturating_duration_since(Instant::now()));
+        match future::select(cancelled, expired).await {
+            Either::Left(_) => Cancelled::Cancelled,
+            Either::Right(_) => Cancelled::DeadlineExceeded,
+        }
+    }
+
+    /// run the future until it's done, or drop it when the token is cancelled or expired
+    pub async fn run<F: Future>(&self, future: F) -> Result<F::Output, Cancelled> {
+        self.check()?;
+
+        let cancelled = self.cancelled();
+        pin_mut!(future, cancelled);
+        match future::select(future, cancelled).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right((reason, _)) => Err(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn cancel_children() {
+        let parent = CancelToken::new();
+        let child = parent.child();
+        let sibling = parent.child();
+
+        assert!(child.cancel());
+        assert!(!child.cancel());
+        assert_eq!(
+            child.run(future::pending::<()>()).await,
+            Err(Cancelled::Cancelled)
+        );
+        assert_eq!(sibling.run(future::ready(1)).await, Ok(1));
+
+        let (cancelled, _) = future::join(sibling.run(future::pending::<()>()), async {
+            parent.cancel();
+        })
+        .await;
+        assert_eq!(cancelled, Err(Cancelled::Cancelled));
+        assert_eq!(parent.child().check(), Err(Cancelled::Cancelled));
+    }
+
+    #[async_std::test]
+    async fn deadline_is_inherited() {
+        let parent = CancelToken::new().child_with_timeout(Some(Duration::from_millis(10)));
+        let child = parent.child_with_timeout(Some(Duration::from_secs(60)));
+
+        assert_eq!(
+            child.run(future::pending::<()>()).await,
+            Err(Cancelled::DeadlineExceeded)
+        );
+        assert_eq!(child.check(), Err(Cancelled::DeadlineExceeded));
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::{Debug, Formatter};
+
+use async_std::task;
+use serde::Deserialize;
+
+/// the free dictionary api, english only
+const DEFAULT_API: &str = "https://api.dictionaryapi.dev/api/v2/entries/en/{word}";
+const NOT_FOUND: u16 = 404;
+/// senses listed for a part of speech
+const MAX_SENSES: usize = 3;
+const CARD_INDENT: &str = "    ";
+
+#[derive(Debug, Deserialize)]
+struct DictionaryEntry {
+    #[serde(default)]
+    phonetic: Option<String>,
+    #[serde(default)]
+    phonetics: Vec<Phonetic>,
+    #[serde(default)]
+    meanings: Vec<Meaning>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Phonetic {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meaning {
+    part_of_speech: String,
+    #[serde(default)]
+    definitions: Vec<Definition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Definition {
+    definition: String,
+    #[serde(default)]
+    example: Option<String>,
+}
+
+/// the dictionary details of a word, for language learners
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Card {
+    pub pronunciation: Option<String>,
+    /// the part of speech and its senses
+    pub parts: Vec<(String, Vec<Sense>)>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Sense {
+    pub definition: String,
+    pub example: Option<String>,
+}
+
+impl Card {
+    /// merge the homographs, the first pronunciation is used
+    fn from_entries(entries: Vec<DictionaryEntry>) -> Self {
+        let mut card = Card::default();
+        for entry in entries {
+            if card.pronunciation.is_none() {
+                card.pronunciation = entry
+                    .phonetic
+                    .into_iter()
+                    .chain(
+                        entry
+                            .phonetics
+                            .into_iter()
+                            .filter_map(|phonetic| phonetic.text),
+                    )
+                    .find(|text| !text.trim().is_empty());
+            }
+
+            for meaning in entry.meanings {
+                let senses = meaning.definitions.into_iter().map(|definition| Sense {
+                    definition: definition.definition,
+                    example: definition.example,
+                });
+
+                match card
+                    .parts
+                    .iter_mut()
+                    .find(|(part, _)| *part == meaning.part_of_speech)
+                {
+                    Some((_, part_senses)) => part_senses.extend(senses),
+                    None => card.parts.push((meaning.part_of_speech, senses.collect())),
+                }
+            }
+        }
+
+        card
+    }
+
+    /// the multi line card shown below the translation
+    pub fn render(&self) -> String {
+        let mut lines = vec![];
+        if let Some(pronunciation) = &self.pronunciation {
+            lines.push(format!("{CARD_INDENT}{pronunciation}"));
+        }
+
+        for (part, senses) in &self.parts {
+            lines.push(format!("{CARD_INDENT}{part}"));
+            for (index, sense) in senses.iter().take(MAX_SENSES).enumerate() {
+                lines.push(format!(
+                    "{CARD_INDENT}  {}. {}",
+                    index + 1,
+                    sense.definition
+                ));
+                if let Some(example) = &sense.example {
+                    lines.push(format!("{CARD_INDENT}     e.g. {example}"));
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// look up the word cards of single word queries by a dictionary api which responds like the free
+/// dictionary api
+#[derive(Clone)]
+pub struct Cards {
+    agent: ureq::Agent,
+    /// `{word}` is replaced by the word
+    api: String,
+}
+
+impl Debug for Cards {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cards").field("api", &self.api).finish()
+    }
+}
+
+impl Cards {
+    pub fn new(api: Option<String>) -> Self {
+        Self {
+            agent: ureq::Agent::new(),
+            api: api.unwrap_or_else(|| DEFAULT_API.to_string()),
+        }
+    }
+
+    /// return none when the dictionary doesn't have the word
+    pub async fn lookup(&self, word: &str) -> anyhow::Result<Option<Card>> {
+        let cards = self.clone();
+        let url = self.api.replace("{word}", &word.to_lowercase());
+
+        task::spawn_blocking(move || {
+            let resp = match cards.agent.get(&url).call() {
+                Err(ureq::Error::Status(NOT_FOUND, _)) => return Ok(None),
+                Err(ureq::Error::Status(code, resp)) => {
+                    let message = resp.into_string().unwrap_or_default();
+
+                    return Err(anyhow::anyhow!("dictionary api error {code}: {message}"));
+                }
+                result => result?,
+            };
+            let card = Card::from_entries(resp.into_json()?);
+
+            Ok((!card.parts.is_empty()).then_some(card))
+        })
+        .await
+    }
+}
+
+/// a single english word, like `apple` or `well-being`
+pub fn is_word(text: &str) -> bool {
+    let text = text.trim();
+
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || matches!(c, '-' | '\''))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_card() {
+        let entries = serde_json::from_str::<Vec<DictionaryEntry>>(
+            r#"[
+                {
+                    "word": "book",
+                    "phonetics": [{ "text": "" }, { "text": "/bʊk/", "audio": "" }],
+                    "meanings": [
+                        {
+                            "partOfSpeech": "noun",
+                            "definitions": [
+                                { "definition": "A collection of sheets of paper.", "example": "I read a book." },
+                                { "definition": "A record of bets." }
+                            ]
+                        },
+                        {
+                            "partOfSpeech": "verb",
+                            "definitions": [{ "definition": "To reserve.", "synonyms": [] }]
+                        }
+                    ]
+                },
+                {
+                    "word": "book",
+                    "meanings": [
+                        { "partOfSpeech": "noun", "definitions": [{ "definition": "A libretto." }] }
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Card::from_entries(entries).render(),
+            "    /bʊk/\n    noun\n      1. A collection of sheets of paper.\n         e.g. I read a book.\n      2. A record of bets.\n      3. A libretto.\n    verb\n      1. To reserve."
+        );
+        assert!(is_word("well-being"));
+        assert!(!is_word("good morning"));
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(windows)]
+use std::thread;
+#[cfg(windows)]
+use std::time::Duration;
+
+use arboard::Clipboard;
+
+/// other programs open the clipboard exclusively for a moment on windows
+#[cfg(windows)]
+const OCCUPIED_RETRIES: u32 = 10;
+#[cfg(windows)]
+const OCCUPIED_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug)]
+pub enum Content {
+    Text(String),
+    /// png encoded image
+    Image(Vec<u8>),
+}
+
+/// read the clipboard, image is preferred because screenshots usually also have a text
+/// representation like the file name
+pub fn read() -> anyhow::Result<Content> {
+    let mut clipboard = Clipboard::new()?;
+
+    if let Ok(image) = unoccupied(|| clipboard.get_image()) {
+        let mut data = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut data, image.width as _, image.height as _);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.write_header()?.write_image_data(&image.bytes)?;
+        }
+
+        return Ok(Content::Image(data));
+    }
+
+    let text = unoccupied(|| clipboard.get_text())
+        .map_err(|err| anyhow::anyhow!("clipboard has no text or image: {err}"))?;
+
+    Ok(Content::Text(text))
+}
+
+/// on linux the text is handed over to the clipboard manager when txcv exits
+pub fn write(text: &str) -> anyhow::Result<()> {
+    let mut clipboard = Clipboard::new()?;
+
+    unoccupied(|| clipboard.set_text(text))
+        .map_err(|err| anyhow::anyhow!("write clipboard failed: {err}"))
+}
+
+/// retry while another program holds the clipboard
+#[cfg(windows)]
+pub fn unoccupied<T, F>(mut f: F) -> Result<T, arboard::Error>
+where
+    F: FnMut() -> Result<T, arboard::Error>,
+{
+    let mut retries = 0;
+    loop {
+        match f() {
+            Err(arboard::Error::ClipboardOccupied) if retries < OCCUPIED_RETRIES => {
+                thread::sleep(OCCUPIED_RETRY_INTERVAL);
+                retries += 1;
+            }
+
+            result => return result,
+        }
+    }
+}
+
+/// the clipboard is never occupied by others on the other platforms
+#[cfg(not(windows))]
+pub fn unoccupied<T, F>(f: F) -> Result<T, arboard::Error>
+where
+    F: FnOnce() -> Result<T, arboard::Error>,
+{
+    f()
+}
+
+/// needs a desktop session, run by `cargo test --features clipboard,desktop-tests`
+#[cfg(all(test, feature = "desktop-tests"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read_text() {
+        write("txcv 剪贴板").unwrap();
+
+        assert!(matches!(read().unwrap(), Content::Text(text) if text == "txcv 剪贴板"));
+    }
+}
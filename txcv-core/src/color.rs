@@ -0,0 +1,77 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(feature = "color")]
+pub use colored::Colorize;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Color {
+    Always,
+    #[default]
+    Auto,
+    Disable,
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Always => f.write_str("always"),
+            Color::Auto => f.write_str("auto"),
+            Color::Disable => f.write_str("disable"),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "always" => Ok(Color::Always),
+            "auto" => Ok(Color::Auto),
+            "disable" => Ok(Color::Disable),
+            _ => Err(anyhow::anyhow!(
+                "invalid color {s}, should be always, auto or disable"
+            )),
+        }
+    }
+}
+
+/// the colors of the terminal output, the text is kept plain without the `color` feature
+#[cfg(not(feature = "color"))]
+pub trait Colorize {
+    fn red(self) -> String;
+    fn yellow(self) -> String;
+    fn green(self) -> String;
+    fn blue(self) -> String;
+    fn white(self) -> String;
+    fn dimmed(self) -> String;
+}
+
+#[cfg(not(feature = "color"))]
+impl Colorize for &str {
+    fn red(self) -> String {
+        self.to_string()
+    }
+
+    fn yellow(self) -> String {
+        self.to_string()
+    }
+
+    fn green(self) -> String {
+        self.to_string()
+    }
+
+    fn blue(self) -> String {
+        self.to_string()
+    }
+
+    fn white(self) -> String {
+        self.to_string()
+    }
+
+    fn dimmed(self) -> String {
+        self.to_string()
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use regex::Regex;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::backend::Backend;
+use crate::color::Color;
+use crate::lang::{AutoTarget, Language};
+use crate::retry::RetryPolicy;
+
+const CONFIG_FILE: &str = "config.toml";
+/// translate api rate limit is 5/s
+pub const DEFAULT_RATE_LIMIT: RateLimit = RateLimit {
+    max_concurrent: 5,
+    refill_interval: 100,
+    chars_per_token: None,
+};
+
+/// defaults of command line options, the options passed on command line take precedence
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(deserialize_with = "language")]
+    pub source: Option<Language>,
+    #[serde(deserialize_with = "language")]
+    pub target: Option<Language>,
+    #[serde(deserialize_with = "color")]
+    pub color: Option<Color>,
+    #[serde(deserialize_with = "backend")]
+    pub backend: Option<Backend>,
+    pub concise: Option<bool>,
+    /// overrides the region of the stored credentials
+    pub region: Option<String>,
+    /// default is the rate limit of the backend
+    pub rate_limit: Option<RateLimit>,
+    /// max characters of a request, longer text is split by sentences, default is the limit of
+    /// the backend
+    pub max_chars: Option<usize>,
+    /// backoff of the rate limited requests
+    pub retry: RetryPolicy,
+    /// days before a cached translation expires
+    pub cache_ttl: Option<u64>,
+    /// percent of the free characters of a month, a notice is printed after the run reaching it,
+    /// 0 disables the notice
+    pub usage_warning: Option<u8>,
+    /// custom slang and their expansions, used with `--slang`
+    pub slang: HashMap<String, String>,
+    /// the llm used by `--explain`
+    pub llm: Llm,
+    /// the dictionary api of `--card`, `{word}` is replaced by the word, it should respond like
+    /// the free dictionary api
+    pub card_api: Option<String>,
+    /// offline dictionary
+    pub dict: Option<PathBuf>,
+    /// regex replacements of translations, applied in order
+    pub replace: Vec<Substitution>,
+    /// target languages of the detected source languages, like `{ ja = "zh", "*" = "en" }`
+    #[serde(deserialize_with = "auto_target")]
+    pub auto_target: Vec<AutoTarget>,
+}
+
+/// replace the matches of pattern in translations of the language pair, all pairs if the
+/// languages are unset
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Substitution {
+    #[serde(default, deserialize_with = "language")]
+    pub source: Option<Language>,
+    #[serde(default, deserialize_with = "language")]
+    pub target: Option<Language>,
+    #[serde(deserialize_with = "regex")]
+    pub pattern: Regex,
+    /// can refer capture groups like `$1`
+    pub replacement: String,
+}
+
+/// openai compatible chat api
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Llm {
+    /// chat completions url
+    pub api_url: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RateLimit {
+    /// max concurrent requests, also the rate limiter capacity
+    pub max_concurrent: u32,
+    /// milliseconds to refill a token
+    pub refill_interval: u64,
+    /// characters counted as one token, a long request takes a token per started this many
+    /// characters, none counts one token per request
+    pub chars_per_token: Option<u32>,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        DEFAULT_RATE_LIMIT
+    }
+}
+
+impl RateLimit {
+    pub fn refill_interval(&self) -> Duration {
+        Duration::from_millis(self.refill_interval)
+    }
+
+    /// the refill interval in milliseconds of the requests per second
+    pub fn qps_interval(qps: f64) -> u64 {
+        (1000.0 / qps).round().max(1.0) as _
+    }
+}
+
+impl Config {
+    /// load config from path, or `txcv/config.toml` under the config dir if path is none, the
+    /// default config file is optional
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                None => return Ok(Self::default()),
+                Some(path) => (path, false),
+            },
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound && !required => {
+                return Ok(Self::default())
+            }
+            result => result.with_context(|| format!("read config {} failed", path.display()))?,
+        };
+
+        let config = toml::from_str::<Self>(&content)
+            .with_context(|| format!("parse config {} failed", path.display()))?;
+        if config.rate_limit.is_some_and(|rate_limit| {
+            rate_limit.max_concurrent == 0
+                || rate_limit.refill_interval == 0
+                || rate_limit.chars_per_token == Some(0)
+        }) {
+            return Err(anyhow::anyhow!(
+                "rate-limit max-concurrent, refill-interval and chars-per-token of config {} must \
+                 be positive",
+                path.display()
+            ));
+        }
+
+        if config.max_chars == Some(0) {
+            return Err(anyhow::anyhow!(
+                "max-chars of config {} must be positive",
+                path.display()
+            ));
+        }
+
+        if config.usage_warning.is_some_and(|warning| warning > 100) {
+            return Err(anyhow::anyhow!(
+                "usage-warning of config {} must be a percent between 0 and 100",
+                path.display()
+            ));
+        }
+
+        if config.retry.multiplier < 1.0 || !(0.0..=1.0).contains(&config.retry.jitter) {
+            return Err(anyhow::anyhow!(
+                "retry multiplier of config {} must be at least 1, and jitter must be between 0 \
+                 and 1",
+                path.display()
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("txcv").join(CONFIG_FILE))
+}
+
+fn language<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Language>, D::Error> {
+    let code = String::deserialize(deserializer)?;
+
+    Language::from_code(&code)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("unknown language {code}")))
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let color = String::deserialize(deserializer)?;
+
+    color.parse::<Color>().map(Some).map_err(D::Error::custom)
+}
+
+fn backend<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Backend>, D::Error> {
+    let backend = String::deserialize(deserializer)?;
+
+    backend
+        .parse::<Backend>()
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+
+    Regex::new(&pattern).map_err(D::Error::custom)
+}
+
+fn auto_target<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<AutoTarget>, D::Error> {
+    HashMap::<String, String>::deserialize(deserializer)?
+        .iter()
+        .map(|(source, target)| AutoTarget::new(source, target).map_err(D::Error::custom))
+        .collect()
+}
@@ -0,0 +1,98 @@
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::memory::Segment;
+use crate::output::escape_tsv;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CorpusFormat {
+    #[default]
+    Tsv,
+    Jsonl,
+}
+
+impl Display for CorpusFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorpusFormat::Tsv => f.write_str("tsv"),
+            CorpusFormat::Jsonl => f.write_str("jsonl"),
+        }
+    }
+}
+
+/// language pair like `en-zh`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Pair {
+    pub source: String,
+    pub target: String,
+}
+
+impl FromStr for Pair {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, target) = s
+            .split_once('-')
+            .with_context(|| format!("invalid language pair {s}, should be like en-zh"))?;
+
+        Ok(Self {
+            source: source.to_string(),
+            target: target.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    source_lang: &'a str,
+    target_lang: &'a str,
+    source: &'a str,
+    target: &'a str,
+}
+
+/// write aligned source and target segments
+pub fn export<'a, W: Write>(
+    segments: impl IntoIterator<Item = &'a Segment>,
+    pair: Option<&Pair>,
+    format: CorpusFormat,
+    mut writer: W,
+) -> anyhow::Result<()> {
+    let segments = segments.into_iter().filter(|segment| {
+        pair.is_none_or(|pair| {
+            segment.source_lang == pair.source && segment.target_lang == pair.target
+        })
+    });
+
+    for segment in segments {
+        match format {
+            CorpusFormat::Tsv => writeln!(
+                writer,
+                "{}\t{}",
+                escape_tsv(&segment.source),
+                escape_tsv(&segment.target)
+            )?,
+
+            CorpusFormat::Jsonl => {
+                serde_json::to_writer(
+                    &mut writer,
+                    &Record {
+                        source_lang: &segment.source_lang,
+                        target_lang: &segment.target_lang,
+                        source: &segment.source,
+                        target: &segment.target,
+                    },
+                )?;
+                writeln!(writer)?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,812 @@
+#[cfg(feature = "credential-store")]
+use std::fmt::{Display, Formatter};
+#[cfg(all(feature = "keyring", feature = "file-store"))]
+use std::sync::OnceLock;
+#[cfg(feature = "credential-store")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(all(feature = "credential-store", feature = "interactive"))]
+use async_std::task;
+#[cfg(feature = "keyring")]
+use keyring::Entry;
+#[cfg(all(feature = "credential-store", feature = "interactive"))]
+use requestty::Question;
+#[cfg(feature = "credential-store")]
+use tencentcloud::{Auth, Client};
+
+#[cfg(feature = "credential-store")]
+use crate::api::language_detect::{LanguageDetect, LanguageDetectRequest};
+#[cfg(feature = "credential-store")]
+use crate::diagnostic;
+use crate::error::Error;
+#[cfg(feature = "file-store")]
+use crate::file_store::FileStore;
+
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "txcv";
+/// entry of the credentials creation unix timestamp
+#[cfg(feature = "credential-store")]
+const CREATED_AT: &str = "created_at";
+/// entry of the deepl api key, it's read from DEEPL_API_KEY in ci
+const DEEPL_API_KEY: &str = "deepl_api_key";
+/// entry of the names of the profiles, a name per line
+#[cfg(feature = "credential-store")]
+const PROFILES: &str = "profiles";
+/// the profile used without --profile
+#[cfg(feature = "credential-store")]
+const DEFAULT_PROFILE: &str = "default";
+/// the entries of a profile
+#[cfg(feature = "credential-store")]
+const ENTRIES: [&str; 5] = [
+    "secret_id",
+    "secret_key",
+    "region",
+    CREATED_AT,
+    DEEPL_API_KEY,
+];
+#[cfg(feature = "credential-store")]
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+/// secrets shorter than it are masked entirely by `txcv auth show`
+#[cfg(feature = "credential-store")]
+const MASK_MIN_LEN: usize = 16;
+/// the language detection response is tiny
+#[cfg(feature = "credential-store")]
+const TEST_RESPONSE_SIZE: usize = 64 * 1024;
+#[cfg(feature = "credential-store")]
+const READ_ONLY_GUIDANCE: &str = "read-only mode never asks or saves credentials, please ask your \
+     administrator to provision them or set them by `txcv auth set`";
+
+/// secret id, secret key and region
+pub type Credentials = (String, String, String);
+
+/// where the credentials are stored
+#[cfg(feature = "credential-store")]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CredentialStore {
+    /// system keyring
+    #[cfg(feature = "keyring")]
+    #[cfg_attr(feature = "keyring", default)]
+    Keyring,
+    /// age encrypted file under the data dir, the passphrase is read from TXCV_PASSPHRASE or
+    /// the output of TXCV_PASSPHRASE_COMMAND
+    #[cfg(feature = "file-store")]
+    #[cfg_attr(not(feature = "keyring"), default)]
+    File,
+}
+
+#[cfg(feature = "credential-store")]
+impl Display for CredentialStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "keyring")]
+            CredentialStore::Keyring => f.write_str("keyring"),
+            #[cfg(feature = "file-store")]
+            CredentialStore::File => f.write_str("file"),
+        }
+    }
+}
+
+#[cfg(feature = "credential-store")]
+impl CredentialStore {
+    /// the keyring fails without a secret service, like on a headless server over ssh, then the
+    /// file store is used instead, the keyring is checked once
+    pub fn available(self) -> Self {
+        #[cfg(all(feature = "keyring", feature = "file-store"))]
+        if self == CredentialStore::Keyring {
+            static KEYRING_AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+            let available = *KEYRING_AVAILABLE.get_or_init(|| {
+                match Entry::new(SERVICE, PROFILES).and_then(|entry| entry.get_password()) {
+                    Err(
+                        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_),
+                    ) => {
+                        diagnostic::warn(
+                            "the system keyring is unavailable, use the encrypted credentials file",
+                        );
+
+                        false
+                    }
+
+                    _ => true,
+                }
+            });
+            if !available {
+                return CredentialStore::File;
+            }
+        }
+
+        self
+    }
+
+    /// get the entry, empty value is treated as not set
+    fn get(self, name: &str) -> anyhow::Result<Option<String>> {
+        let value = match self.available() {
+            #[cfg(feature = "keyring")]
+            CredentialStore::Keyring => match Entry::new(SERVICE, name)?.get_password() {
+                Err(keyring::Error::NoEntry) => None,
+                Err(err) => return Err(err.into()),
+                Ok(value) => Some(value),
+            },
+
+            #[cfg(feature = "file-store")]
+            CredentialStore::File => FileStore::shared()?.get(name).map(str::to_string),
+        };
+
+        Ok(value.filter(|value| !value.is_empty()))
+    }
+
+    fn set(self, entries: &[(&str, &str)]) -> anyhow::Result<()> {
+        self.update(entries, &[])
+    }
+
+    fn delete(self, names: &[&str]) -> anyhow::Result<()> {
+        self.update(&[], names)
+    }
+
+    /// set and delete the entries, the credentials file is written once
+    fn update(self, entries: &[(&str, &str)], deleted: &[&str]) -> anyhow::Result<()> {
+        match self.available() {
+            #[cfg(feature = "keyring")]
+            CredentialStore::Keyring => {
+                for (name, value) in entries {
+                    Entry::new(SERVICE, name)?.set_password(value)?;
+                }
+                for name in deleted {
+                    match Entry::new(SERVICE, name)?.delete_password() {
+                        Err(keyring::Error::NoEntry) | Ok(_) => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+
+                Ok(())
+            }
+
+            // the shared store is replaced only after the file is written
+            #[cfg(feature = "file-store")]
+            CredentialStore::File => {
+                let mut store = FileStore::shared()?;
+                let mut updated = store.clone();
+                for (name, value) in entries {
+                    updated.set(name, value);
+                }
+                for name in deleted {
+                    updated.remove(name);
+                }
+
+                updated.save()?;
+                *store = updated;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// the credentials of a named profile in the store, like `work` or `personal`, the entries of the
+/// default profile aren't prefixed, so the credentials saved before profiles are kept
+#[cfg(feature = "credential-store")]
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    store: CredentialStore,
+    /// none is the default profile
+    name: Option<String>,
+}
+
+#[cfg(feature = "credential-store")]
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            None => write!(f, "{}", self.store.available()),
+            Some(name) => write!(f, "{} profile {name}", self.store.available()),
+        }
+    }
+}
+
+#[cfg(feature = "credential-store")]
+impl Profile {
+    /// none or `default` is the default profile, a name has letters, digits, `-` and `_`
+    pub fn new(store: CredentialStore, name: Option<&str>) -> anyhow::Result<Self> {
+        let name = name.filter(|name| *name != DEFAULT_PROFILE);
+        if let Some(name) = name {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(anyhow::anyhow!(
+                    "invalid profile name {name}, only letters, digits, - and _ are allowed"
+                ));
+            }
+        }
+
+        Ok(Self {
+            store,
+            name: name.map(str::to_string),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    fn entry(&self, name: &str) -> String {
+        match &self.name {
+            None => name.to_string(),
+            Some(profile) => format!("{profile}.{name}"),
+        }
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<Option<String>> {
+        self.store.get(&self.entry(name))
+    }
+
+    /// the profile is remembered when its credentials are set
+    fn set(&self, entries: &[(&str, &str)]) -> anyhow::Result<()> {
+        let mut entries = entries
+            .iter()
+            .map(|(name, value)| (self.entry(name), value.to_string()))
+            .collect::<Vec<_>>();
+        if let Some(name) = &self.name {
+            let mut profiles = profiles(self.store)?;
+            if !profiles.contains(name) {
+                profiles.push(name.clone());
+                entries.push((PROFILES.to_string(), profiles.join("\n")));
+            }
+        }
+
+        self.store.set(
+            &entries
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// the names of the entries in the store
+    fn entries(&self, names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| self.entry(name)).collect()
+    }
+}
+
+/// a credential shown by `txcv auth show`
+#[cfg(feature = "credential-store")]
+#[derive(Debug, Clone)]
+pub struct Shown {
+    pub name: &'static str,
+    /// masked except the region, none if it's not set
+    pub value: Option<String>,
+    /// the store or the overrides
+    pub from: String,
+}
+
+/// the credentials of a profile, shown by `txcv auth status`
+#[cfg(feature = "credential-store")]
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub store: CredentialStore,
+    pub profile: String,
+    /// whether the entries are set
+    pub set: Vec<(&'static str, bool)>,
+    /// none if the creation time is unknown
+    pub age: Option<Duration>,
+}
+
+/// credentials passed by the command line or TENCENTCLOUD_SECRET_ID, TENCENTCLOUD_SECRET_KEY
+/// and TENCENTCLOUD_REGION, the precedence is command line, environment variables, then the
+/// store, the store isn't read if all of them are passed
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    pub secret_id: Option<String>,
+    pub secret_key: Option<String>,
+    pub region: Option<String>,
+}
+
+impl Overrides {
+    /// the unset flags fall back to the environment variables
+    pub fn new(
+        secret_id: Option<String>,
+        secret_key: Option<String>,
+        region: Option<String>,
+    ) -> Self {
+        let env = |name| env(name).ok().filter(|value| !value.is_empty());
+
+        Self {
+            secret_id: secret_id.or_else(|| env("secret_id")),
+            secret_key: secret_key.or_else(|| env("secret_key")),
+            region: region.or_else(|| env("region")),
+        }
+    }
+
+    /// all credentials are passed, the store isn't needed
+    pub fn complete(&self) -> Option<Credentials> {
+        Some((
+            self.secret_id.clone()?,
+            self.secret_key.clone()?,
+            self.region.clone()?,
+        ))
+    }
+
+    /// the credentials must be passed without a store
+    pub fn require(&self) -> anyhow::Result<Credentials> {
+        let require = |value: &Option<String>, name: &str| {
+            value.clone().ok_or_else(|| {
+                Error::Auth(anyhow::anyhow!(
+                    "must pass --{} or set TENCENTCLOUD_{} environment variable",
+                    name.replace('_', "-"),
+                    name.to_uppercase()
+                ))
+            })
+        };
+
+        Ok((
+            require(&self.secret_id, "secret_id")?,
+            require(&self.secret_key, "secret_key")?,
+            require(&self.region, "region")?,
+        ))
+    }
+
+    #[cfg(feature = "credential-store")]
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "secret_id" => self.secret_id.as_deref(),
+            "secret_key" => self.secret_key.as_deref(),
+            "region" => self.region.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// get the deepl api key from DEEPL_API_KEY
+pub fn deepl_from_env() -> anyhow::Result<String> {
+    env(DEEPL_API_KEY)
+}
+
+fn env(name: &str) -> anyhow::Result<String> {
+    let key = match name {
+        DEEPL_API_KEY => name.to_uppercase(),
+        _ => format!("TENCENTCLOUD_{}", name.to_uppercase()),
+    };
+
+    std::env::var(&key)
+        .map_err(|_| Error::Auth(anyhow::anyhow!("must set {key} environment variable")).into())
+}
+
+/// get credentials from the overrides or the store without asking user
+#[cfg(feature = "credential-store")]
+pub fn stored(profile: &Profile, overrides: &Overrides) -> anyhow::Result<Credentials> {
+    let get = |name: &str| -> anyhow::Result<String> {
+        if let Some(value) = overrides.get(name) {
+            return Ok(value.to_string());
+        }
+
+        let value = profile.get(name)?.ok_or_else(|| {
+            Error::Auth(anyhow::anyhow!("{name} is not set, {READ_ONLY_GUIDANCE}"))
+        })?;
+
+        Ok(value)
+    };
+
+    Ok((get("secret_id")?, get("secret_key")?, get("region")?))
+}
+
+#[cfg(feature = "credential-store")]
+pub fn stored_deepl(profile: &Profile) -> anyhow::Result<String> {
+    let key = profile.get(DEEPL_API_KEY)?.ok_or_else(|| {
+        Error::Auth(anyhow::anyhow!(
+            "{DEEPL_API_KEY} is not set, {READ_ONLY_GUIDANCE}"
+        ))
+    })?;
+
+    Ok(key)
+}
+
+/// get credentials from the overrides or the store, ask user for the unset ones and offer to
+/// save them when save is true, the overrides are never saved
+#[cfg(feature = "credential-store")]
+pub async fn get_or_ask(
+    profile: &Profile,
+    overrides: &Overrides,
+    from_stdin: bool,
+    save: bool,
+    max_age: Duration,
+) -> anyhow::Result<Credentials> {
+    let resolve = |name: &'static str| async move {
+        match overrides.get(name) {
+            Some(value) => Ok((value.to_string(), false)),
+            None => get(profile, from_stdin, name).await,
+        }
+    };
+    let (secret_id, secret_id_asked) = resolve("secret_id").await?;
+    let (secret_key, secret_key_asked) = resolve("secret_key").await?;
+    let (region, region_asked) = resolve("region").await?;
+
+    let mut asked = [
+        ("secret_id", secret_id.as_str(), secret_id_asked),
+        ("secret_key", secret_key.as_str(), secret_key_asked),
+        ("region", region.as_str(), region_asked),
+    ]
+    .into_iter()
+    .filter(|(_, _, asked)| *asked)
+    .map(|(name, value, _)| (name, value))
+    .collect::<Vec<_>>();
+    if !asked.is_empty() && save && ask_save(profile).await? {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .as_secs()
+            .to_string();
+        asked.push((CREATED_AT, &now));
+
+        profile.set(&asked)?;
+    } else if let Some(age) = age(profile)? {
+        warn_age(age, max_age);
+    }
+
+    Ok((secret_id, secret_key, region))
+}
+
+/// get the deepl api key from the store, ask user if not set and offer to save it when save is
+/// true
+#[cfg(feature = "credential-store")]
+pub async fn get_or_ask_deepl(
+    profile: &Profile,
+    from_stdin: bool,
+    save: bool,
+) -> anyhow::Result<String> {
+    let (api_key, asked) = get(profile, from_stdin, DEEPL_API_KEY).await?;
+    if asked && save && ask_save(profile).await? {
+        profile.set(&[(DEEPL_API_KEY, &api_key)])?;
+    }
+
+    Ok(api_key)
+}
+
+/// remove the credentials of the profile
+#[cfg(feature = "credential-store")]
+pub fn clear(profile: &Profile) -> anyhow::Result<()> {
+    let names = profile.entries(&ENTRIES);
+
+    profile
+        .store
+        .delete(&names.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+/// move the credentials of all profiles to another store and return the names of the migrated
+/// profiles, the entries are named by `Profile::entry` on both sides, so a changed naming scheme
+/// is migrated the same way. the source is deleted only after every entry is read back from the
+/// destination, and the different credentials already in the destination are never overwritten
+#[cfg(feature = "credential-store")]
+pub fn migrate(from: CredentialStore, to: CredentialStore) -> anyhow::Result<Vec<String>> {
+    if to.available() != to {
+        return Err(anyhow::anyhow!("the {to} credential store is unavailable"));
+    }
+    let from = from.available();
+    if from == to {
+        return Err(anyhow::anyhow!(
+            "the credentials are already in the {to} store"
+        ));
+    }
+
+    let mut migrated = vec![];
+    for name in std::iter::once(None).chain(profiles(from)?.into_iter().map(Some)) {
+        let source = Profile {
+            store: from,
+            name: name.clone(),
+        };
+        let destination = Profile { store: to, name };
+
+        let mut entries = vec![];
+        for entry in ENTRIES {
+            let Some(value) = source.get(entry)? else {
+                continue;
+            };
+            if destination
+                .get(entry)?
+                .is_some_and(|existing| existing != value)
+            {
+                return Err(anyhow::anyhow!(
+                    "{entry} of profile {} is already in the {to} store with another value, \
+                     remove it by `txcv --credential-store {to} auth clear {}` first",
+                    destination.name(),
+                    destination.name()
+                ));
+            }
+
+            entries.push((entry, value));
+        }
+        if !entries.is_empty() {
+            migrated.push((source, destination, entries));
+        }
+    }
+    if migrated.is_empty() {
+        return Err(anyhow::anyhow!("no credentials in the {from} store"));
+    }
+
+    for (_, destination, entries) in &migrated {
+        destination.set(
+            &entries
+                .iter()
+                .map(|(entry, value)| (*entry, value.as_str()))
+                .collect::<Vec<_>>(),
+        )?;
+    }
+    for (_, destination, entries) in &migrated {
+        for (entry, value) in entries {
+            if destination.get(entry)?.as_ref() != Some(value) {
+                return Err(anyhow::anyhow!(
+                    "verify {entry} of profile {} in the {to} store failed, the {from} store is \
+                     kept",
+                    destination.name()
+                ));
+            }
+        }
+    }
+
+    let mut deleted = migrated
+        .iter()
+        .flat_map(|(source, _, _)| source.entries(&ENTRIES))
+        .collect::<Vec<_>>();
+    deleted.push(PROFILES.to_string());
+    from.delete(&deleted.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+    Ok(migrated
+        .iter()
+        .map(|(source, _, _)| source.name().to_string())
+        .collect())
+}
+
+/// save the credentials of the profile, the ones not passed by the overrides are asked, the old
+/// ones are replaced
+#[cfg(feature = "credential-store")]
+pub async fn set(profile: &Profile, overrides: &Overrides) -> anyhow::Result<()> {
+    let resolve = |name: &'static str| async move {
+        match overrides.get(name) {
+            Some(value) => Ok(value.to_string()),
+            None => ask(name).await,
+        }
+    };
+    let secret_id = resolve("secret_id").await?;
+    let secret_key = resolve("secret_key").await?;
+    let region = resolve("region").await?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_secs()
+        .to_string();
+
+    profile.set(&[
+        ("secret_id", &secret_id),
+        ("secret_key", &secret_key),
+        ("region", &region),
+        (CREATED_AT, &now),
+    ])
+}
+
+/// the profiles which have credentials, the default profile first
+#[cfg(feature = "credential-store")]
+pub fn list(store: CredentialStore) -> anyhow::Result<Vec<String>> {
+    let mut names = vec![];
+    if store.get("secret_id")?.is_some() {
+        names.push(DEFAULT_PROFILE.to_string());
+    }
+    names.extend(profiles(store)?);
+
+    Ok(names)
+}
+
+/// remove the credentials of the profile and forget it
+#[cfg(feature = "credential-store")]
+pub fn remove(profile: &Profile) -> anyhow::Result<()> {
+    let Some(name) = &profile.name else {
+        // the default profile is never listed
+        return clear(profile);
+    };
+    let names = profiles(profile.store)?;
+    if !names.contains(name) {
+        return Err(anyhow::anyhow!("profile {name} doesn't exist"));
+    }
+
+    let names = names
+        .into_iter()
+        .filter(|profile| profile != name)
+        .collect::<Vec<_>>();
+    let deleted = profile.entries(&ENTRIES);
+    profile.store.update(
+        &[(PROFILES, &names.join("\n"))],
+        &deleted.iter().map(String::as_str).collect::<Vec<_>>(),
+    )
+}
+
+/// the names of the saved profiles except the default one
+#[cfg(feature = "credential-store")]
+fn profiles(store: CredentialStore) -> anyhow::Result<Vec<String>> {
+    Ok(store
+        .get(PROFILES)?
+        .map(|profiles| profiles.lines().map(str::to_string).collect())
+        .unwrap_or_default())
+}
+
+/// the credentials used with the profile and where they come from, the secrets are masked
+#[cfg(feature = "credential-store")]
+pub fn show(profile: &Profile, overrides: &Overrides) -> anyhow::Result<Vec<Shown>> {
+    let mut shown = vec![];
+    for name in ["secret_id", "secret_key", "region", DEEPL_API_KEY] {
+        let (value, from) = match overrides.get(name) {
+            Some(value) => (
+                Some(value.to_string()),
+                "flag, environment or config".to_string(),
+            ),
+            None => (profile.get(name)?, profile.to_string()),
+        };
+        let value = match value {
+            Some(value) if name != "region" => Some(mask(&value)),
+            value => value,
+        };
+
+        shown.push(Shown { name, value, from });
+    }
+
+    Ok(shown)
+}
+
+/// keep the first and the last 4 characters of a long secret so it can be recognized, short ones
+/// are masked entirely
+#[cfg(feature = "credential-store")]
+fn mask(secret: &str) -> String {
+    let chars = secret.chars().collect::<Vec<_>>();
+    let keep = if chars.len() >= MASK_MIN_LEN { 4 } else { 0 };
+
+    chars[..keep]
+        .iter()
+        .chain(std::iter::repeat_n(&'*', chars.len() - keep * 2))
+        .chain(&chars[chars.len() - keep..])
+        .collect()
+}
+
+/// validate the credentials by detecting the language of a word, which is the cheapest tmt request
+#[cfg(feature = "credential-store")]
+pub async fn test((secret_id, secret_key, region): Credentials) -> anyhow::Result<()> {
+    let client = Client::new(region, Auth::new(secret_key, secret_id), TEST_RESPONSE_SIZE);
+    let request = LanguageDetectRequest {
+        text: "hello".to_string(),
+        project_id: 0,
+    };
+
+    match client.send::<LanguageDetect>(&request).await {
+        Err(tencentcloud::Error::Api { err, .. }) if err.code.starts_with("AuthFailure") => {
+            Err(Error::Auth(anyhow::anyhow!(
+                "credentials are rejected by tencent cloud: {}",
+                err.code
+            ))
+            .into())
+        }
+        Err(err) => Err(anyhow::Error::from(err).context("test credentials failed")),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// which credentials of the profile are set and how old they are
+#[cfg(feature = "credential-store")]
+pub fn status(profile: &Profile) -> anyhow::Result<Status> {
+    let mut set = vec![];
+    for name in ["secret_id", "secret_key", "region", DEEPL_API_KEY] {
+        set.push((name, profile.get(name)?.is_some()));
+    }
+
+    Ok(Status {
+        store: profile.store.available(),
+        profile: profile.name().to_string(),
+        set,
+        age: age(profile)?,
+    })
+}
+
+#[cfg(feature = "credential-store")]
+fn age(profile: &Profile) -> anyhow::Result<Option<Duration>> {
+    let created_at = match profile.get(CREATED_AT)? {
+        None => return Ok(None),
+        Some(created_at) => created_at,
+    };
+    let created_at = UNIX_EPOCH + Duration::from_secs(created_at.parse()?);
+
+    Ok(SystemTime::now().duration_since(created_at).ok())
+}
+
+/// warn when the credentials are older than the max age
+#[cfg(feature = "credential-store")]
+pub fn warn_age(age: Duration, max_age: Duration) {
+    if age > max_age {
+        diagnostic::warn(format!(
+            "credentials are created {} days ago, please rotate your tencent cloud api keys",
+            age.as_secs() / SECS_PER_DAY
+        ));
+    }
+}
+
+/// get the credential from the store, ask user if not set, the returned bool is true when the
+/// value is asked
+#[cfg(feature = "credential-store")]
+async fn get(
+    profile: &Profile,
+    from_stdin: bool,
+    name: &'static str,
+) -> anyhow::Result<(String, bool)> {
+    match profile.get(name)? {
+        None => {
+            if from_stdin {
+                return Err(Error::Auth(anyhow::anyhow!(
+                    "read from stdin must set {name} at first, please set it by `txcv auth set`"
+                ))
+                .into());
+            }
+
+            Ok((ask(name).await?, true))
+        }
+
+        Some(value) => Ok((value, false)),
+    }
+}
+
+#[cfg(all(feature = "credential-store", feature = "interactive"))]
+async fn ask_save(profile: &Profile) -> anyhow::Result<bool> {
+    let message = format!("save credentials to {profile}, or use them for this session only");
+    task::spawn_blocking(move || {
+        let question = Question::confirm("save_credentials")
+            .message(message)
+            .default(true)
+            .build();
+        let answer = requestty::prompt_one(question)?;
+
+        Ok(answer.as_bool().unwrap_or(false))
+    })
+    .await
+}
+
+/// the credentials from environment variables are never saved
+#[cfg(all(feature = "credential-store", not(feature = "interactive")))]
+async fn ask_save(_profile: &Profile) -> anyhow::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(all(feature = "credential-store", feature = "interactive"))]
+async fn ask(name: &'static str) -> anyhow::Result<String> {
+    task::spawn_blocking(move || {
+        let message = name.replace('_', " ");
+        let question = if name == "secret_key" || name == DEEPL_API_KEY {
+            Question::password(name).message(message).build()
+        } else {
+            Question::input(name).message(message).build()
+        };
+        let value = requestty::prompt_one(question)?;
+        let value = value
+            .as_string()
+            .ok_or_else(|| anyhow::anyhow!("{name} is not string"))?;
+
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("{name} is empty"));
+        }
+
+        Ok(value.to_string())
+    })
+    .await
+}
+
+/// without prompts, fall back to the environment variable
+#[cfg(all(feature = "credential-store", not(feature = "interactive")))]
+async fn ask(name: &'static str) -> anyhow::Result<String> {
+    env(name)
+}
+
+#[cfg(all(test, feature = "credential-store"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_secret() {
+        assert_eq!(
+            mask("AKIDz8krbsJ5yKBZQpn74WFkmLPx3abc"),
+            "AKID************************3abc"
+        );
+        assert_eq!(mask("short"), "*****");
+        assert_eq!(mask(""), "");
+    }
+}
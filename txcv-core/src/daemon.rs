@@ -0,0 +1,323 @@
+use std::fs;
+#[cfg(unix)]
+use std::io::ErrorKind;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+#[cfg(not(unix))]
+use std::sync::Mutex;
+
+use anyhow::Context;
+use async_std::io::prelude::{BufReadExt, WriteExt};
+use async_std::io::BufReader;
+#[cfg(not(unix))]
+use async_std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use async_std::os::unix::net::{UnixListener, UnixStream};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(unix))]
+use crate::atomic;
+use crate::error::Error;
+use crate::lang::Language;
+use crate::translate::Translation;
+
+#[cfg(unix)]
+const SOCKET_FILE: &str = "txcv.sock";
+/// platforms without unix domain sockets listen on localhost
+#[cfg(not(unix))]
+const ADDRESS: &str = "127.0.0.1:47017";
+/// any local user can connect to localhost, so the requests carry the token in this file, which
+/// is under the local data dir of the user
+#[cfg(not(unix))]
+const TOKEN_FILE: &str = "daemon.token";
+
+/// the token written by `bind`
+#[cfg(not(unix))]
+static TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+#[cfg(unix)]
+pub type Listener = UnixListener;
+#[cfg(unix)]
+pub type Stream = UnixStream;
+#[cfg(not(unix))]
+pub type Listener = TcpListener;
+#[cfg(not(unix))]
+pub type Stream = TcpStream;
+
+/// a json line sent to `txcv serve`, the languages are codes like `en`, default is auto detect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// the token of the daemon, only checked over tcp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Request {
+    pub fn new(text: String, source: Option<Language>, target: Option<Language>) -> Self {
+        Self {
+            text,
+            source: source.map(|source| source.as_str().to_string()),
+            target: target.map(|target| target.as_str().to_string()),
+            token: None,
+        }
+    }
+
+    pub fn languages(&self) -> anyhow::Result<(Option<Language>, Option<Language>)> {
+        let parse = |code: &Option<String>| {
+            code.as_deref()
+                .map(|code| {
+                    Language::from_code(code).with_context(|| format!("unknown language {code}"))
+                })
+                .transpose()
+        };
+
+        Ok((parse(&self.source)?, parse(&self.target)?))
+    }
+}
+
+/// the json line answered for every request, in the order of the requests
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Response {
+    Translation(Translation),
+    Error {
+        error: String,
+        /// the category of the error, see `Error::kind`
+        #[serde(default)]
+        kind: String,
+    },
+}
+
+/// where the daemon listens, for the logs
+#[cfg(unix)]
+pub fn address() -> anyhow::Result<String> {
+    Ok(socket_path()?.display().to_string())
+}
+
+#[cfg(not(unix))]
+pub fn address() -> anyhow::Result<String> {
+    Ok(ADDRESS.to_string())
+}
+
+/// the socket is under the runtime dir, or the cache dir if there is no runtime dir
+#[cfg(unix)]
+fn socket_path() -> anyhow::Result<PathBuf> {
+    Ok(dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .context("can't find runtime dir")?
+        .join("txcv")
+        .join(SOCKET_FILE))
+}
+
+#[cfg(not(unix))]
+fn token_path() -> anyhow::Result<PathBuf> {
+    Ok(dirs::data_local_dir()
+        .context("can't find local data dir")?
+        .join("txcv")
+        .join(TOKEN_FILE))
+}
+
+/// listen on the socket, the socket left by a crashed daemon is replaced. the socket is created
+/// in a dir only the user can enter, so no one else can connect to it, even before it's created
+#[cfg(unix)]
+pub async fn bind() -> anyhow::Result<Listener> {
+    let path = socket_path()?;
+    if connect().await.is_ok() {
+        return Err(anyhow::anyhow!(
+            "txcv serve is already running on {}",
+            path.display()
+        ));
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+        // fails if the dir belongs to another user
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("make {} private failed", dir.display()))?;
+    }
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            return Err(err).with_context(|| format!("remove {} failed", path.display()))
+        }
+        _ => {}
+    }
+
+    UnixListener::bind(&path)
+        .await
+        .with_context(|| format!("listen on {} failed", path.display()))
+}
+
+/// listen on localhost and write a new random token, the requests without it are rejected
+#[cfg(not(unix))]
+pub async fn bind() -> anyhow::Result<Listener> {
+    let listener = TcpListener::bind(ADDRESS)
+        .await
+        .with_context(|| format!("listen on {ADDRESS} failed"))?;
+
+    let mut token = [0; 16];
+    getrandom::getrandom(&mut token).context("generate daemon token failed")?;
+    let token = token
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let path = token_path()?;
+    atomic::write_private(&path, token.as_bytes())
+        .with_context(|| format!("write {} failed", path.display()))?;
+    *TOKEN.lock().unwrap() = Some(token);
+
+    Ok(listener)
+}
+
+/// only the user can connect to the unix socket
+#[cfg(unix)]
+fn authorized(_request: &Request) -> bool {
+    true
+}
+
+#[cfg(not(unix))]
+fn authorized(request: &Request) -> bool {
+    let token = TOKEN.lock().unwrap();
+
+    token.is_some() && request.token == *token
+}
+
+#[cfg(unix)]
+fn token() -> anyhow::Result<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+fn token() -> anyhow::Result<Option<String>> {
+    let path = token_path()?;
+    let token = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "read the token of txcv serve from {} failed",
+            path.display()
+        )
+    })?;
+
+    Ok(Some(token.trim().to_string()))
+}
+
+#[cfg(unix)]
+async fn connect() -> anyhow::Result<Stream> {
+    Ok(UnixStream::connect(socket_path()?).await?)
+}
+
+#[cfg(not(unix))]
+async fn connect() -> anyhow::Result<Stream> {
+    Ok(TcpStream::connect(ADDRESS).await?)
+}
+
+/// answer the requests of the connection one by one until it's closed
+pub async fn serve<F, Fut>(stream: Stream, translate: F) -> anyhow::Result<()>
+where
+    F: Fn(Request) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<Translation>>,
+{
+    let mut lines = BufReader::new(&stream).lines();
+    let mut writer = &stream;
+
+    while let Some(line) = lines.next().await {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Err(err) => Response::Error {
+                error: format!("invalid request: {err}"),
+                kind: "invalid_request".to_string(),
+            },
+            Ok(request) if !authorized(&request) => Response::Error {
+                error: "invalid token of txcv serve".to_string(),
+                kind: Error::Auth(anyhow::anyhow!("invalid token"))
+                    .kind()
+                    .to_string(),
+            },
+            Ok(request) => match translate(request).await {
+                Err(err) => {
+                    let err = Error::from(err);
+
+                    Response::Error {
+                        error: format!("{err:#}"),
+                        kind: err.kind().to_string(),
+                    }
+                }
+                Ok(translation) => Response::Translation(translation),
+            },
+        };
+
+        let mut response = serde_json::to_vec(&response)?;
+        response.push(b'\n');
+        writer.write_all(&response).await?;
+    }
+
+    Ok(())
+}
+
+/// send the requests to the running daemon over one connection, skip the credentials and the
+/// tls handshake of a new process
+pub async fn forward(requests: Vec<Request>) -> anyhow::Result<Vec<Translation>> {
+    let stream = connect().await.with_context(|| {
+        format!(
+            "connect txcv serve on {} failed, start it by `txcv serve`",
+            address().unwrap_or_default()
+        )
+    })?;
+    let token = token()?;
+    let mut lines = BufReader::new(&stream).lines();
+    let mut writer = &stream;
+
+    let mut translations = vec![];
+    for mut request in requests {
+        request.token = token.clone();
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        writer.write_all(&line).await?;
+
+        let response = lines
+            .next()
+            .await
+            .context("txcv serve closed the connection")??;
+        match serde_json::from_str(&response)? {
+            Response::Translation(translation) => translations.push(translation),
+            Response::Error { error, .. } => return Err(anyhow::anyhow!(error)),
+        }
+    }
+
+    Ok(translations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines() {
+        let request = serde_json::from_str::<Request>(r#"{"text":"hello","target":"ja"}"#).unwrap();
+        assert_eq!(
+            request.languages().unwrap(),
+            (None, Some(Language::Japanese))
+        );
+
+        let response =
+            serde_json::from_str::<Response>(r#"{"error":"too many requests"}"#).unwrap();
+        assert!(matches!(response, Response::Error { error, .. } if error == "too many requests"));
+
+        let response = serde_json::from_str::<Response>(
+            r#"{"source_lang":"en","target_lang":"zh","text":"你好"}"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(response, Response::Translation(translation) if translation.text == "你好")
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt::{Debug, Formatter};
+
+use async_std::task;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{Capabilities, RateLimited, TextTooLong, TranslateBackend};
+use crate::config::RateLimit;
+use crate::diagnostic;
+use crate::translate::{Provider, Translation};
+
+const FREE_API: &str = "https://api-free.deepl.com/v2/translate";
+const PRO_API: &str = "https://api.deepl.com/v2/translate";
+/// keys of the free plan end with it
+const FREE_KEY_SUFFIX: &str = ":fx";
+/// deepl has no detection api, the detection translates a short prefix of the text
+const DETECT_PREFIX_CHARS: usize = 100;
+/// the request body is limited to 128 KiB, a cjk character is 3 bytes in json
+const MAX_CHARS: usize = 30000;
+const PAYLOAD_TOO_LARGE: u16 = 413;
+const TOO_MANY_REQUESTS: u16 = 429;
+const QUOTA_EXCEEDED: u16 = 456;
+
+/// txcv language code and deepl source and target language codes
+const LANGUAGES: &[(&str, &str, &str)] = &[
+    ("zh", "ZH", "ZH-HANS"),
+    ("zh-TW", "ZH", "ZH-HANT"),
+    ("en", "EN", "EN-US"),
+    ("jp", "JA", "JA"),
+    ("ko", "KO", "KO"),
+    ("fr", "FR", "FR"),
+    ("es", "ES", "ES"),
+    ("it", "IT", "IT"),
+    ("de", "DE", "DE"),
+    ("tr", "TR", "TR"),
+    ("ru", "RU", "RU"),
+    ("pt", "PT", "PT-BR"),
+    ("id", "ID", "ID"),
+    ("ar", "AR", "AR"),
+];
+
+pub const CAPABILITIES: Capabilities = Capabilities {
+    provider: Provider::Deepl,
+    languages: &[
+        "zh", "zh-TW", "en", "jp", "ko", "fr", "es", "it", "de", "tr", "ru", "pt", "id", "ar",
+    ],
+    max_chars: Some(MAX_CHARS),
+    max_batch: 1,
+    // the free plan allows few concurrent requests
+    rate_limit: RateLimit {
+        max_concurrent: 2,
+        refill_interval: 500,
+        chars_per_token: None,
+    },
+    context: true,
+    image: false,
+    document: false,
+    offline: false,
+};
+
+#[derive(Debug, Serialize)]
+struct TranslateRequest<'a> {
+    text: [&'a str; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<&'static str>,
+    target_lang: &'static str,
+    /// not translated, only helps to translate the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslateResponse {
+    translations: Vec<TextTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TextTranslation {
+    #[serde(default)]
+    detected_source_language: String,
+    text: String,
+}
+
+/// deepl translator, the free and pro plans are chosen by the api key
+#[derive(Clone)]
+pub struct Deepl {
+    agent: ureq::Agent,
+    api_key: String,
+}
+
+impl Debug for Deepl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Deepl").finish_non_exhaustive()
+    }
+}
+
+impl Deepl {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            agent: ureq::Agent::new(),
+            api_key,
+        }
+    }
+
+    /// the blocking http request runs in the blocking thread pool
+    async fn send(
+        &self,
+        text: &str,
+        context: Option<&str>,
+        source: Option<&'static str>,
+        target: &'static str,
+    ) -> anyhow::Result<TextTranslation> {
+        let deepl = self.clone();
+        let text = text.to_string();
+        let context = context.map(str::to_string);
+
+        task::spawn_blocking(move || {
+            let api = if deepl.api_key.ends_with(FREE_KEY_SUFFIX) {
+                FREE_API
+            } else {
+                PRO_API
+            };
+            let request = TranslateRequest {
+                text: [text.as_str()],
+                source_lang: source,
+                target_lang: target,
+                context: context.as_deref(),
+            };
+
+            let resp = match deepl
+                .agent
+                .post(api)
+                .set(
+                    "Authorization",
+                    &format!("DeepL-Auth-Key {}", deepl.api_key),
+                )
+                .send_json(&request)
+            {
+                Err(ureq::Error::Status(TOO_MANY_REQUESTS, _)) => return Err(RateLimited.into()),
+                Err(ureq::Error::Status(PAYLOAD_TOO_LARGE, _)) => return Err(TextTooLong.into()),
+                Err(ureq::Error::Status(QUOTA_EXCEEDED, _)) => {
+                    return Err(anyhow::anyhow!("deepl translation quota is exceeded"))
+                }
+                Err(ureq::Error::Status(code, resp)) => {
+                    let message = resp.into_string().unwrap_or_default();
+
+                    return Err(anyhow::anyhow!("deepl api error {code}: {message}"));
+                }
+                result => result?,
+            };
+
+            resp.into_json::<TranslateResponse>()?
+                .translations
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("deepl returns no translation"))
+        })
+        .await
+    }
+
+    async fn translate_with(
+        &self,
+        text: &str,
+        context: Option<&str>,
+        source: &str,
+        target: &str,
+    ) -> anyhow::Result<Translation> {
+        let source_lang = match source {
+            "auto" => None,
+            _ => Some(
+                source_code(source)
+                    .ok_or_else(|| anyhow::anyhow!("deepl doesn't support language {source}"))?,
+            ),
+        };
+        let target_lang = target_code(target)
+            .ok_or_else(|| anyhow::anyhow!("deepl doesn't support language {target}"))?;
+
+        let action = format!(
+            "deepl translate {source} -> {target}, {} characters",
+            text.chars().count()
+        );
+        let translation =
+            diagnostic::timed(action, self.send(text, context, source_lang, target_lang)).await?;
+        diagnostic::trace(format_args!("{text:?} -> {:?}", translation.text));
+
+        Ok(Translation {
+            source_lang: from_code(&translation.detected_source_language),
+            target_lang: target.to_string(),
+            text: translation.text,
+        })
+    }
+}
+
+impl TranslateBackend for Deepl {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        self.translate_with(text, None, source, target).boxed()
+    }
+
+    fn translate_in_context<'a>(
+        &'a self,
+        text: &'a str,
+        context: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        self.translate_with(text, Some(context), source, target)
+            .boxed()
+    }
+
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        async move {
+            let prefix = text.chars().take(DETECT_PREFIX_CHARS).collect::<String>();
+            let translation =
+                diagnostic::timed("deepl detect", self.send(&prefix, None, None, "EN-US")).await?;
+
+            Ok(from_code(&translation.detected_source_language))
+        }
+        .boxed()
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        CAPABILITIES.languages
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Deepl
+    }
+
+    fn rate_limit(&self) -> RateLimit {
+        CAPABILITIES.rate_limit
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        Some(MAX_CHARS)
+    }
+}
+
+fn source_code(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(txcv, _, _)| *txcv == code)
+        .map(|(_, source, _)| *source)
+}
+
+fn target_code(code: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(txcv, _, _)| *txcv == code)
+        .map(|(_, _, target)| *target)
+}
+
+/// the unknown deepl languages are kept in lowercase
+fn from_code(code: &str) -> String {
+    LANGUAGES
+        .iter()
+        .find(|(_, source, _)| source.eq_ignore_ascii_case(code))
+        .map_or_else(|| code.to_lowercase(), |(txcv, _, _)| txcv.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_code_mapping() {
+        assert_eq!(source_code("jp"), Some("JA"));
+        assert_eq!(target_code("en"), Some("EN-US"));
+        assert_eq!(target_code("zh-TW"), Some("ZH-HANT"));
+        assert_eq!(target_code("vi"), None);
+        assert_eq!(from_code("JA"), "jp");
+        assert_eq!(from_code("ZH"), "zh");
+        assert_eq!(from_code("NB"), "nb");
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::color::Colorize;
+
+/// set by `-v`, 1 prints the api requests and their latencies, the retries, the rate limiter
+/// waits and the cache hits, 2 also prints the texts sent and received
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// the shorter waits aren't noticed
+const MIN_NOTICED_WAIT: Duration = Duration::from_secs(1);
+
+/// the waits shown by the transient notice on the last line of stderr
+static NOTICED_WAITS: AtomicUsize = AtomicUsize::new(0);
+
+/// move to the line start and clear the line
+const ERASE_LINE: &str = "\r\x1b[2K";
+
+/// how serious a diagnostic is, shown as the prefix of its line
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+            Severity::Info => f.write_str("info"),
+            Severity::Debug => f.write_str("debug"),
+            Severity::Trace => f.write_str("trace"),
+        }
+    }
+}
+
+/// print the diagnostic to stderr with its severity prefix, stdout only has the translations, so
+/// piping txcv never mixes diagnostics into the results
+pub fn print(severity: Severity, message: impl Display) {
+    let line = format!("{severity}: {message}");
+    let line = match severity {
+        Severity::Error => line.red(),
+        Severity::Warning => line.yellow(),
+        Severity::Info | Severity::Debug | Severity::Trace => line.dimmed(),
+    };
+
+    erase_notice();
+    eprintln!("{line}");
+}
+
+/// erase the notice of the waits before printing a line, the notice isn't shown again
+pub fn erase_notice() {
+    if NOTICED_WAITS.load(Ordering::Relaxed) > 0 {
+        eprint!("{ERASE_LINE}");
+    }
+}
+
+pub fn error(message: impl Display) {
+    print(Severity::Error, message)
+}
+
+pub fn warn(message: impl Display) {
+    print(Severity::Warning, message)
+}
+
+pub fn info(message: impl Display) {
+    print(Severity::Info, message)
+}
+
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed)
+}
+
+/// printed with `-v`
+pub fn debug(message: impl Display) {
+    if VERBOSITY.load(Ordering::Relaxed) >= 1 {
+        print(Severity::Debug, message)
+    }
+}
+
+/// printed with `-vv`
+pub fn trace(message: impl Display) {
+    if VERBOSITY.load(Ordering::Relaxed) >= 2 {
+        print(Severity::Trace, message)
+    }
+}
+
+/// run the api request, the action and the latency are printed with `-v`
+pub async fn timed<T>(action: impl Display, request: impl Future<Output = T>) -> T {
+    let started = Instant::now();
+    let result = request.await;
+    debug(format_args!(
+        "{action} took {}ms",
+        started.elapsed().as_millis()
+    ));
+
+    result
+}
+
+/// erases the notice when the last noticed wait ends or is cancelled
+struct NoticedWait;
+
+impl Drop for NoticedWait {
+    fn drop(&mut self) {
+        if NOTICED_WAITS.fetch_sub(1, Ordering::Relaxed) == 1 {
+            eprint!("{ERASE_LINE}");
+        }
+    }
+}
+
+/// wait for the rate limit, a transient notice is shown on stderr while waiting long, so the long
+/// pauses of batch runs don't look like hangs
+pub async fn waiting<T>(duration: Duration, wait: impl Future<Output = T>) -> T {
+    if duration < MIN_NOTICED_WAIT || !io::stderr().is_terminal() {
+        return wait.await;
+    }
+
+    NOTICED_WAITS.fetch_add(1, Ordering::Relaxed);
+    let _noticed = NoticedWait;
+    let notice = format!("rate limited, waiting {:.1}s…", duration.as_secs_f64());
+    eprint!("{ERASE_LINE}{}", notice.dimmed());
+
+    wait.await
+}
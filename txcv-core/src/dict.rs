@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use futures_util::future::{self, BoxFuture, FutureExt};
+
+use crate::backend::{Capabilities, TranslateBackend};
+use crate::config::DEFAULT_RATE_LIMIT;
+use crate::translate::{Provider, Translation};
+
+const ECDICT_FILE: &str = "ecdict.csv";
+/// chinese lookups list at most these english words
+const MAX_REVERSE_WORDS: usize = 5;
+pub const CAPABILITIES: Capabilities = Capabilities {
+    provider: Provider::Dict,
+    languages: &["zh", "en"],
+    max_chars: None,
+    max_batch: 1,
+    rate_limit: DEFAULT_RATE_LIMIT,
+    context: false,
+    image: false,
+    document: false,
+    offline: true,
+};
+
+/// english chinese dictionary for offline single word lookups, loaded from an ecdict csv or an
+/// uncompressed stardict dictionary
+#[derive(Debug)]
+pub struct Dict {
+    /// lowercase headword to its definition
+    entries: HashMap<String, String>,
+    /// chinese meaning to the english headwords, built from the ecdict translations
+    reverse: HashMap<String, Vec<String>>,
+}
+
+impl Dict {
+    /// `ecdict.csv` under the txcv data dir
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("txcv").join(ECDICT_FILE))
+    }
+
+    /// load the ecdict csv, or the stardict dictionary when path is the `.ifo` file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let dict = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ifo") => Self::load_stardict(path),
+            _ => Self::load_ecdict(path),
+        };
+
+        dict.with_context(|| format!("load dictionary {} failed", path.display()))
+    }
+
+    fn load_ecdict(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut lines = content.lines();
+        let header = split_csv(lines.next().unwrap_or_default());
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .with_context(|| format!("no {name} column"))
+        };
+        let word_column = column("word")?;
+        let translation_column = column("translation")?;
+
+        let mut dict = Self {
+            entries: HashMap::new(),
+            reverse: HashMap::new(),
+        };
+        for line in lines {
+            let fields = split_csv(line);
+            let (Some(word), Some(translation)) =
+                (fields.get(word_column), fields.get(translation_column))
+            else {
+                continue;
+            };
+            if word.is_empty() || translation.is_empty() {
+                continue;
+            }
+
+            // ecdict escapes new lines, a sense per line
+            let translation = translation.replace("\\n", "\n");
+            for meaning in meanings(&translation) {
+                dict.reverse
+                    .entry(meaning.to_string())
+                    .or_default()
+                    .push(word.clone());
+            }
+            dict.entries.insert(word.to_lowercase(), translation);
+        }
+
+        Ok(dict)
+    }
+
+    /// the `.idx` and `.dict` files are next to the `.ifo` file, the compressed `.dict.dz` is not
+    /// supported
+    fn load_stardict(ifo: &Path) -> anyhow::Result<Self> {
+        let info = fs::read_to_string(ifo)?;
+        if info.lines().any(|line| line.trim() == "idxoffsetbits=64") {
+            return Err(anyhow::anyhow!("64 bits stardict index is not supported"));
+        }
+
+        let data_path = ifo.with_extension("dict");
+        if !data_path.exists() && ifo.with_extension("dict.dz").exists() {
+            return Err(anyhow::anyhow!(
+                "compressed .dict.dz is not supported, decompress it by `gzip -d -S .dz`"
+            ));
+        }
+        let index = fs::read(ifo.with_extension("idx"))?;
+        let data = fs::read(&data_path)?;
+
+        let mut entries = HashMap::new();
+        let mut rest = index.as_slice();
+        while let Some(end) = rest.iter().position(|b| *b == 0) {
+            let word = String::from_utf8_lossy(&rest[..end]).to_lowercase();
+            let location = rest
+                .get(end + 1..end + 9)
+                .context("truncated stardict index")?;
+            let offset = u32::from_be_bytes(location[..4].try_into()?) as usize;
+            let size = u32::from_be_bytes(location[4..].try_into()?) as usize;
+            let definition = data
+                .get(offset..offset + size)
+                .context("stardict index is out of the dictionary")?;
+
+            entries.insert(word, String::from_utf8_lossy(definition).trim().to_string());
+            rest = &rest[end + 9..];
+        }
+
+        Ok(Self {
+            entries,
+            reverse: HashMap::new(),
+        })
+    }
+
+    /// english words are looked up case-insensitively, chinese words fall back to the meanings of
+    /// english words
+    fn lookup(&self, word: &str) -> Option<String> {
+        if let Some(definition) = self.entries.get(&word.to_lowercase()) {
+            return Some(definition.clone());
+        }
+
+        self.reverse.get(word).map(|words| {
+            words
+                .iter()
+                .take(MAX_REVERSE_WORDS)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+}
+
+impl TranslateBackend for Dict {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        _source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        let word = text.trim();
+        let translation = if word.split_whitespace().nth(1).is_some() {
+            Err(anyhow::anyhow!(
+                "offline dictionary only translates single words"
+            ))
+        } else {
+            self.lookup(word)
+                .map(|text| {
+                    let source_lang = source_lang(word);
+                    let target_lang = match (target, source_lang) {
+                        ("auto", "en") => "zh",
+                        ("auto", _) => "en",
+                        (target, _) => target,
+                    };
+
+                    Translation {
+                        source_lang: source_lang.to_string(),
+                        target_lang: target_lang.to_string(),
+                        text,
+                    }
+                })
+                .with_context(|| format!("{word} is not in the offline dictionary"))
+        };
+
+        future::ready(translation).boxed()
+    }
+
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        future::ready(Ok(source_lang(text).to_string())).boxed()
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        CAPABILITIES.languages
+    }
+
+    fn provider(&self) -> Provider {
+        Provider::Dict
+    }
+}
+
+/// the dictionary is english chinese, so text without ascii letters is chinese
+fn source_lang(text: &str) -> &'static str {
+    if text.chars().any(|c| c.is_ascii_alphabetic()) {
+        "en"
+    } else {
+        "zh"
+    }
+}
+
+/// meanings of an ecdict translation like `n. 苹果, 苹果树\na. 苹果的`
+fn meanings(translation: &str) -> impl Iterator<Item = &str> {
+    translation
+        .split(['\n', ';', ',', '；', '，'])
+        .map(|meaning| {
+            // strip the part of speech
+            match meaning.trim().split_once(". ") {
+                Some((pos, meaning)) if pos.chars().all(|c| c.is_ascii_alphabetic()) => meaning,
+                _ => meaning,
+            }
+            .trim()
+        })
+        .filter(|meaning| !meaning.is_empty())
+}
+
+/// split a csv line, quoted fields may contain commas and escaped quotes
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ecdict_lookup_both_ways() {
+        let dir = std::env::temp_dir().join(format!("txcv-dict-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(ECDICT_FILE);
+        fs::write(
+            &path,
+            "word,phonetic,definition,translation\n\
+             apple,'æpl,\"n. fruit, tree\",n. 苹果\\nn. 苹果树\n\
+             Pear,peə,n. fruit,n. 梨\n",
+        )
+        .unwrap();
+
+        let dict = Dict::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(dict.lookup("Apple").unwrap(), "n. 苹果\nn. 苹果树");
+        assert_eq!(dict.lookup("pear").unwrap(), "n. 梨");
+        assert_eq!(dict.lookup("苹果").unwrap(), "apple");
+        assert!(dict.lookup("banana").is_none());
+    }
+
+    #[test]
+    fn split_quoted_csv() {
+        assert_eq!(
+            split_csv(r#"a,"b, ""c""",,d"#),
+            vec!["a", r#"b, "c""#, "", "d"]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Write;
+
+/// lines of unchanged context around changes
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// line operations of replacing every old block with its new block, unchanged blocks are equal
+pub fn block_ops<'a, I>(blocks: I) -> Vec<Op<'a>>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut ops = vec![];
+    for (old, new) in blocks {
+        if old == new {
+            ops.extend(old.split('\n').map(Op::Equal));
+        } else {
+            ops.extend(old.split('\n').map(Op::Delete));
+            ops.extend(new.split('\n').map(Op::Insert));
+        }
+    }
+
+    ops
+}
+
+/// format ops as unified diff, empty when nothing is changed
+pub fn unified(old_name: &str, new_name: &str, ops: &[Op], newline_at_eof: bool) -> String {
+    // (first change, last change) of hunks, changes separated by few equal lines share a hunk
+    let mut groups = Vec::<(usize, usize)>::new();
+    for (index, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(_)) {
+            continue;
+        }
+
+        match groups.last_mut() {
+            Some((_, last)) if index - *last <= 2 * CONTEXT + 1 => *last = index,
+            _ => groups.push((index, index)),
+        }
+    }
+
+    if groups.is_empty() {
+        return String::new();
+    }
+
+    // line numbers before every op, start from 0
+    let mut old_lines = Vec::with_capacity(ops.len() + 1);
+    let mut new_lines = Vec::with_capacity(ops.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for op in ops {
+        old_lines.push(old_line);
+        new_lines.push(new_line);
+        match op {
+            Op::Equal(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Op::Delete(_) => old_line += 1,
+            Op::Insert(_) => new_line += 1,
+        }
+    }
+    old_lines.push(old_line);
+    new_lines.push(new_line);
+    let (old_total, new_total) = (old_line, new_line);
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    for (first, last) in groups {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let old_len = old_lines[end] - old_lines[start];
+        let new_len = new_lines[end] - new_lines[start];
+        // empty range starts at the line before it
+        let old_start = old_lines[start] + usize::from(old_len > 0);
+        let new_start = new_lines[start] + usize::from(new_len > 0);
+        let _ = writeln!(diff, "@@ -{old_start},{old_len} +{new_start},{new_len} @@");
+
+        for index in start..end {
+            let (prefix, line, last_line) = match ops[index] {
+                Op::Equal(line) => (
+                    ' ',
+                    line,
+                    old_lines[index] + 1 == old_total || new_lines[index] + 1 == new_total,
+                ),
+                Op::Delete(line) => ('-', line, old_lines[index] + 1 == old_total),
+                Op::Insert(line) => ('+', line, new_lines[index] + 1 == new_total),
+            };
+
+            let _ = writeln!(diff, "{prefix}{line}");
+            if last_line && !newline_at_eof {
+                diff.push_str("\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_of_replaced_block() {
+        let ops = block_ops([
+            ("# title", "# title"),
+            ("", ""),
+            ("hello\nworld", "你好\n世界"),
+            ("", ""),
+            ("a", "a"),
+            ("b", "b"),
+            ("c", "c"),
+            ("d", "d"),
+        ]);
+
+        assert_eq!(
+            unified("a/README.md", "b/README.md", &ops, true),
+            "--- a/README.md\n+++ b/README.md\n@@ -1,7 +1,7 @@\n # title\n \n-hello\n-world\n+你好\n+世界\n \n a\n b\n"
+        );
+    }
+
+    #[test]
+    fn no_newline_at_end_of_file() {
+        let ops = block_ops([("a", "a"), ("hello", "你好")]);
+
+        assert_eq!(
+            unified("a", "b", &ops, false),
+            "--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n-hello\n\\ No newline at end of file\n+你好\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn unchanged_is_empty() {
+        let ops = block_ops([("a", "a"), ("b", "b")]);
+
+        assert_eq!(unified("a", "b", &ops, true), "");
+    }
+}
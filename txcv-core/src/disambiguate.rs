@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::atomic;
+use crate::lang::Language;
+
+const CHOICES_FILE: &str = "languages.json";
+/// short han text without kana may be chinese or japanese, like `大丈夫`
+const MAX_AMBIGUOUS_HAN_CHARS: usize = 4;
+
+/// words spelled the same in several languages
+const HOMOGRAPHS: &[(&str, &[Language])] = &[
+    ("also", &[Language::English, Language::German]),
+    ("arm", &[Language::English, Language::German]),
+    ("bad", &[Language::English, Language::German]),
+    ("burro", &[Language::Spanish, Language::Italian]),
+    ("chat", &[Language::English, Language::French]),
+    ("coin", &[Language::English, Language::French]),
+    ("die", &[Language::English, Language::German]),
+    ("fast", &[Language::English, Language::German]),
+    ("gift", &[Language::English, Language::German]),
+    ("hell", &[Language::English, Language::German]),
+    ("kind", &[Language::English, Language::German]),
+    ("main", &[Language::English, Language::French]),
+    ("pain", &[Language::English, Language::French]),
+    ("rat", &[Language::English, Language::German]),
+    ("salut", &[Language::French, Language::Malay]),
+    ("tag", &[Language::English, Language::German]),
+];
+
+/// the languages the text plausibly belongs to, guessed locally from the homographs and the
+/// script, empty if there is no guess
+pub fn candidates(text: &str) -> Vec<Language> {
+    let text = text.trim();
+    let word = text.to_lowercase();
+    if let Some((_, languages)) = HOMOGRAPHS.iter().find(|(homograph, _)| *homograph == word) {
+        return languages.to_vec();
+    }
+
+    let has = |f: fn(char) -> bool| text.chars().any(f);
+    if has(is_kana) {
+        vec![Language::Japanese]
+    } else if has(is_hangul) {
+        vec![Language::Korean]
+    } else if has(is_han) {
+        if text.chars().filter(|c| is_han(*c)).count() <= MAX_AMBIGUOUS_HAN_CHARS
+            && text.chars().all(|c| is_han(c) || !c.is_alphanumeric())
+        {
+            vec![Language::Chinese, Language::Japanese]
+        } else {
+            vec![Language::Chinese]
+        }
+    } else if has(|c| matches!(c, '\u{0400}'..='\u{04ff}')) {
+        vec![Language::Russian]
+    } else if has(|c| matches!(c, '\u{0600}'..='\u{06ff}')) {
+        vec![Language::Arabic]
+    } else if has(|c| matches!(c, '\u{0e00}'..='\u{0e7f}')) {
+        vec![Language::Thai]
+    } else if has(|c| matches!(c, '\u{0900}'..='\u{097f}')) {
+        vec![Language::Hindi]
+    } else {
+        vec![]
+    }
+}
+
+/// the languages to choose from when the local guess and the detected language disagree, or the
+/// text has several candidates, the detected language is the first, empty if it's unambiguous
+pub fn ambiguous(candidates: &[Language], detected: Option<Language>) -> Vec<Language> {
+    let agreed = candidates.len() == 1 && candidates.first() == detected.as_ref();
+    if candidates.is_empty() || agreed {
+        return vec![];
+    }
+
+    let mut choices = detected.into_iter().collect::<Vec<_>>();
+    choices.extend(
+        candidates
+            .iter()
+            .filter(|candidate| Some(**candidate) != detected),
+    );
+
+    choices
+}
+
+/// source languages chosen by user for the ambiguous texts
+#[derive(Debug)]
+pub struct Choices {
+    path: PathBuf,
+    /// lowercase text to language code
+    choices: HashMap<String, String>,
+}
+
+impl Choices {
+    pub fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(CHOICES_FILE);
+
+        let choices = match fs::read(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+            Ok(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("parse {} failed", path.display()))?,
+        };
+
+        Ok(Self { path, choices })
+    }
+
+    pub fn get(&self, text: &str) -> Option<Language> {
+        Language::from_code(self.choices.get(&text.trim().to_lowercase())?)
+    }
+
+    pub fn remember(&mut self, text: &str, lang: Language) -> anyhow::Result<()> {
+        self.choices
+            .insert(text.trim().to_lowercase(), lang.as_str().to_string());
+
+        atomic::write(&self.path, &serde_json::to_vec(&self.choices)?)
+            .with_context(|| format!("write {} failed", self.path.display()))
+    }
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}')
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{ac00}'..='\u{d7af}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}')
+}
+
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ask_when_ambiguous() {
+        let chat = candidates("Chat");
+        assert_eq!(
+            ambiguous(&chat, Some(Language::French)),
+            vec![Language::French, Language::English]
+        );
+        assert_eq!(
+            ambiguous(&candidates("大丈夫"), Some(Language::Chinese)),
+            vec![Language::Chinese, Language::Japanese]
+        );
+        assert_eq!(
+            ambiguous(&candidates("ありがとう"), Some(Language::Chinese)),
+            vec![Language::Chinese, Language::Japanese]
+        );
+        assert!(ambiguous(&candidates("ありがとう"), Some(Language::Japanese)).is_empty());
+        assert!(ambiguous(&candidates("hello world"), Some(Language::English)).is_empty());
+    }
+}
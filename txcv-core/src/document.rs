@@ -0,0 +1,339 @@
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::lang::Language;
+
+const DIRECTIVE_PREFIX: &str = "<!-- txcv:";
+const DIRECTIVE_SUFFIX: &str = "-->";
+
+/// per-file directives, e.g. `<!-- txcv: target=ja glossary=api.toml skip-lines=1-10 -->`
+#[derive(Debug, Default)]
+pub struct Directives {
+    pub source: Option<Language>,
+    pub target: Option<Language>,
+    /// glossary path, relative to the file
+    pub glossary: Option<PathBuf>,
+    /// line ranges which are kept untranslated, start from 1
+    pub skip_lines: Vec<RangeInclusive<usize>>,
+}
+
+impl Directives {
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut directives = Self::default();
+        for line in content.lines() {
+            let body = match directive_body(line) {
+                // ignore region markers are not directives
+                Some(body) if !matches!(body.trim(), "off" | "on") => body,
+                _ => continue,
+            };
+
+            for option in body.split_whitespace() {
+                let (key, value) = option
+                    .split_once('=')
+                    .with_context(|| format!("invalid txcv directive option {option}"))?;
+
+                match key {
+                    "source" => directives.source = Some(parse_language(value)?),
+                    "target" => directives.target = Some(parse_language(value)?),
+                    "glossary" => directives.glossary = Some(PathBuf::from(value)),
+                    "skip-lines" => {
+                        for range in value.split(',') {
+                            directives.skip_lines.push(parse_line_range(range)?);
+                        }
+                    }
+
+                    _ => return Err(anyhow::anyhow!("unknown txcv directive option {key}")),
+                }
+            }
+        }
+
+        Ok(directives)
+    }
+
+    fn is_skipped(&self, line_number: usize) -> bool {
+        self.skip_lines
+            .iter()
+            .any(|range| range.contains(&line_number))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Format {
+    Text,
+    Markdown,
+    Html,
+    Code,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md" | "markdown") => Format::Markdown,
+            Some("html" | "htm" | "xml") => Format::Html,
+            Some(
+                "rs" | "go" | "c" | "h" | "cpp" | "hpp" | "java" | "kt" | "js" | "ts" | "py" | "rb"
+                | "sh" | "lua" | "toml",
+            ) => Format::Code,
+            _ => Format::Text,
+        }
+    }
+
+    pub fn default_markers(self) -> Markers {
+        match self {
+            Format::Markdown | Format::Html => Markers {
+                off: "<!-- txcv:off -->".to_string(),
+                on: "<!-- txcv:on -->".to_string(),
+            },
+
+            // code comment syntax is various, just match the marker text
+            Format::Text | Format::Code => Markers {
+                off: "txcv:off".to_string(),
+                on: "txcv:on".to_string(),
+            },
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Text => f.write_str("text"),
+            Format::Markdown => f.write_str("markdown"),
+            Format::Html => f.write_str("html"),
+            Format::Code => f.write_str("code"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            "code" => Ok(Format::Code),
+            _ => Err(anyhow::anyhow!(
+                "invalid format {s}, should be text, markdown, html or code"
+            )),
+        }
+    }
+}
+
+/// lines between the off marker and the on marker are not translated
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Markers {
+    pub off: String,
+    pub on: String,
+}
+
+/// markers of a format, parsed from `<FORMAT>=<OFF>,<ON>`
+#[derive(Debug, Clone)]
+pub struct FormatMarkers {
+    pub format: Format,
+    pub markers: Markers,
+}
+
+impl FromStr for FormatMarkers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, markers) = s
+            .split_once('=')
+            .with_context(|| format!("invalid markers {s}, should be <FORMAT>=<OFF>,<ON>"))?;
+        let format = format.parse::<Format>()?;
+        let (off, on) = markers
+            .split_once(',')
+            .with_context(|| format!("invalid markers {s}, should be <FORMAT>=<OFF>,<ON>"))?;
+
+        Ok(Self {
+            format,
+            markers: Markers {
+                off: off.to_string(),
+                on: on.to_string(),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Block {
+    /// kept as is
+    Keep(String),
+    /// paragraph which needs translating
+    Translate(String),
+}
+
+impl Block {
+    pub fn text(&self) -> &str {
+        match self {
+            Block::Keep(text) | Block::Translate(text) => text,
+        }
+    }
+}
+
+/// split content into paragraphs, blank lines, directive lines, skipped lines and lines in
+/// ignored regions are kept as is
+pub fn split(content: &str, directives: &Directives, markers: &Markers) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut paragraph = Vec::<&str>::new();
+    let mut ignored = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let mut keep = ignored;
+        if line.contains(markers.off.as_str()) {
+            ignored = true;
+            keep = true;
+        } else if line.contains(markers.on.as_str()) {
+            ignored = false;
+            keep = true;
+        }
+
+        if keep
+            || line.trim().is_empty()
+            || directive_body(line).is_some()
+            || directives.is_skipped(index + 1)
+        {
+            if !paragraph.is_empty() {
+                blocks.push(Block::Translate(paragraph.join("\n")));
+                paragraph.clear();
+            }
+
+            blocks.push(Block::Keep(line.to_string()));
+        } else {
+            paragraph.push(line);
+        }
+    }
+
+    if !paragraph.is_empty() {
+        blocks.push(Block::Translate(paragraph.join("\n")));
+    }
+
+    blocks
+}
+
+/// join blocks back, keep the trailing newline of the original content
+pub fn join<S: AsRef<str>>(blocks: &[S], original: &str) -> String {
+    let mut content = blocks
+        .iter()
+        .map(|block| block.as_ref())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if original.ends_with('\n') {
+        content.push('\n');
+    }
+
+    content
+}
+
+/// position of a block in the original content
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct Span {
+    /// first line, start from 1
+    pub start_line: usize,
+    /// last line, inclusive
+    pub end_line: usize,
+    pub start_byte: usize,
+    /// exclusive, the line ending of the last line is not included
+    pub end_byte: usize,
+}
+
+/// locate the blocks split from content
+pub fn spans(blocks: &[Block], content: &str) -> Vec<Span> {
+    // start and end byte of every line without line ending, same as `str::lines`
+    let mut lines = vec![];
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        let text = match line.strip_suffix('\n') {
+            None => line,
+            Some(line) => line.strip_suffix('\r').unwrap_or(line),
+        };
+        let end = start + text.len();
+        lines.push((start, end));
+        start += line.len();
+    }
+
+    let mut line_index = 0;
+    blocks
+        .iter()
+        .map(|block| {
+            let count = block.text().split('\n').count();
+            let span = Span {
+                start_line: line_index + 1,
+                end_line: line_index + count,
+                start_byte: lines[line_index].0,
+                end_byte: lines[line_index + count - 1].1,
+            };
+            line_index += count;
+
+            span
+        })
+        .collect()
+}
+
+fn directive_body(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix(DIRECTIVE_PREFIX)?
+        .strip_suffix(DIRECTIVE_SUFFIX)
+}
+
+fn parse_language(value: &str) -> anyhow::Result<Language> {
+    Language::from_code(value).with_context(|| format!("unknown language {value}"))
+}
+
+fn parse_line_range(range: &str) -> anyhow::Result<RangeInclusive<usize>> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .with_context(|| format!("invalid line range {range}"))
+    };
+
+    match range.split_once('-') {
+        None => {
+            let line = parse(range)?;
+
+            Ok(line..=line)
+        }
+
+        Some((start, end)) => Ok(parse(start)?..=parse(end)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        // set PROPTEST_RNG_SEED to reproduce a failure with a deterministic seed
+        #[test]
+        fn split_join_round_trip(content in "[^\r]*") {
+            let blocks = split(&content, &Directives::default(), &Format::Markdown.default_markers());
+            let texts = blocks.iter().map(Block::text).collect::<Vec<_>>();
+
+            prop_assert_eq!(join(&texts, &content), content);
+        }
+
+        #[test]
+        fn spans_point_to_block_text(content in "[^\r]*") {
+            let blocks = split(&content, &Directives::default(), &Format::Markdown.default_markers());
+
+            for (block, span) in blocks.iter().zip(spans(&blocks, &content)) {
+                prop_assert_eq!(&content[span.start_byte..span.end_byte], block.text());
+            }
+        }
+
+        #[test]
+        fn directives_parse_never_panics(content in any::<String>()) {
+            let _ = Directives::parse(&content);
+        }
+    }
+}
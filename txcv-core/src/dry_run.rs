@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use futures_util::future::{self, BoxFuture, FutureExt};
+
+use crate::backend::{Capabilities, TranslateBackend};
+use crate::config::RateLimit;
+use crate::disambiguate;
+use crate::translate::{Provider, Translation};
+
+/// prints the requests instead of sending them, a text is translated to itself, so the language
+/// detection, chunking and batching of a run are planned like a real run without credentials
+#[derive(Debug)]
+pub struct DryRun {
+    capabilities: &'static Capabilities,
+    requests: AtomicUsize,
+    chars: AtomicUsize,
+}
+
+impl DryRun {
+    pub fn new(capabilities: &'static Capabilities) -> Self {
+        Self {
+            capabilities,
+            requests: AtomicUsize::new(0),
+            chars: AtomicUsize::new(0),
+        }
+    }
+
+    fn plan(&self, action: &str, languages: Option<(&str, &str)>, texts: usize, chars: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.chars.fetch_add(chars, Ordering::Relaxed);
+
+        let provider = self.capabilities.provider;
+        let texts = match texts {
+            1 => String::new(),
+            texts => format!("{texts} texts, "),
+        };
+        match languages {
+            None => println!("{provider} {action}, {texts}{chars} characters"),
+            Some((source, target)) => {
+                println!("{provider} {action} {source} -> {target}, {texts}{chars} characters")
+            }
+        }
+    }
+
+    /// the planned requests and characters, and the least time the rate limiter allows them
+    pub fn summary(&self, rate_limit: RateLimit) -> String {
+        let requests = self.requests.load(Ordering::Relaxed);
+
+        format!(
+            "{requests} requests, {} characters, at least {:.1}s by the rate limit",
+            self.chars.load(Ordering::Relaxed),
+            min_duration(requests, rate_limit).as_secs_f64()
+        )
+    }
+}
+
+/// the bucket is full at first, the later requests wait for the refills
+fn min_duration(requests: usize, rate_limit: RateLimit) -> Duration {
+    let waiting = requests.saturating_sub(rate_limit.max_concurrent as _);
+
+    rate_limit.refill_interval() * waiting as u32
+}
+
+impl TranslateBackend for DryRun {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        let action = match self.capabilities.provider {
+            Provider::Tmt => "TextTranslate",
+            _ => "translate",
+        };
+        self.plan(action, Some((source, target)), 1, text.chars().count());
+
+        future::ready(Ok(Translation {
+            source_lang: source.to_string(),
+            target_lang: target.to_string(),
+            text: text.to_string(),
+        }))
+        .boxed()
+    }
+
+    fn translate_batch<'a>(
+        &'a self,
+        texts: &'a [String],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<Translation>>> {
+        let chars = texts.iter().map(|text| text.chars().count()).sum();
+        self.plan(
+            "TextTranslateBatch",
+            Some((source, target)),
+            texts.len(),
+            chars,
+        );
+
+        let translations = texts
+            .iter()
+            .map(|text| Translation {
+                source_lang: source.to_string(),
+                target_lang: target.to_string(),
+                text: text.clone(),
+            })
+            .collect();
+
+        future::ready(Ok(translations)).boxed()
+    }
+
+    /// detected locally by the script, latin text is planned as english
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        let action = match self.capabilities.provider {
+            Provider::Tmt => "LanguageDetect",
+            _ => "detect",
+        };
+        self.plan(action, None, 1, text.chars().count());
+
+        let lang = disambiguate::candidates(text)
+            .first()
+            .map_or("en", |lang| lang.as_str());
+
+        future::ready(Ok(lang.to_string())).boxed()
+    }
+
+    fn max_batch(&self) -> usize {
+        self.capabilities.max_batch
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        self.capabilities.languages
+    }
+
+    fn provider(&self) -> Provider {
+        self.capabilities.provider
+    }
+
+    fn rate_limit(&self) -> RateLimit {
+        self.capabilities.rate_limit
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        self.capabilities.max_chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_duration() {
+        let rate_limit = RateLimit {
+            max_concurrent: 5,
+            refill_interval: 100,
+            chars_per_token: None,
+        };
+
+        assert_eq!(min_duration(3, rate_limit), Duration::ZERO);
+        assert_eq!(min_duration(25, rate_limit), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::process;
+
+use anyhow::Context;
+
+#[cfg(unix)]
+const DEFAULT_EDITOR: &str = "vi";
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+
+/// edit text in $VISUAL or $EDITOR, the editor command may contain arguments like `code --wait`
+pub fn edit(text: &str) -> anyhow::Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+
+    let path = std::env::temp_dir().join(format!("txcv-edit-{}.txt", process::id()));
+    fs::write(&path, text).with_context(|| format!("write {} failed", path.display()))?;
+
+    // pass the path as a shell argument, so it doesn't need quoting
+    #[cfg(unix)]
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(&path)
+        .status();
+    #[cfg(windows)]
+    let status = process::Command::new("cmd")
+        .arg("/C")
+        .arg(format!("{editor} \"{}\"", path.display()))
+        .status();
+
+    let result = status
+        .with_context(|| format!("run editor {editor} failed"))
+        .and_then(|status| {
+            if status.success() {
+                Ok(fs::read_to_string(&path)?)
+            } else {
+                Err(anyhow::anyhow!("editor {editor} exits with {status}"))
+            }
+        });
+    let _ = fs::remove_file(&path);
+
+    // editors usually add a newline at the end of file
+    let edited = result?;
+    let edited = edited
+        .strip_suffix('\n')
+        .map(|edited| edited.strip_suffix('\r').unwrap_or(edited))
+        .unwrap_or(&edited);
+
+    Ok(edited.to_string())
+}
@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+const BOM: char = '\u{feff}';
+/// the bytes read to tell a text file from a binary one
+const SNIFF_LEN: usize = 8 * 1024;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum NewlineStyle {
+    #[default]
+    Keep,
+    Lf,
+    Crlf,
+}
+
+impl Display for NewlineStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NewlineStyle::Keep => f.write_str("keep"),
+            NewlineStyle::Lf => f.write_str("lf"),
+            NewlineStyle::Crlf => f.write_str("crlf"),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum BomStyle {
+    #[default]
+    Keep,
+    Add,
+    Remove,
+}
+
+impl Display for BomStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BomStyle::Keep => f.write_str("keep"),
+            BomStyle::Add => f.write_str("add"),
+            BomStyle::Remove => f.write_str("remove"),
+        }
+    }
+}
+
+/// the encoding, bom and newline style of a file
+#[derive(Debug, Copy, Clone)]
+pub struct FileStyle {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+    pub crlf: bool,
+}
+
+impl FileStyle {
+    /// apply the forced styles
+    pub fn with(mut self, newline: NewlineStyle, bom: BomStyle) -> Self {
+        match newline {
+            NewlineStyle::Keep => {}
+            NewlineStyle::Lf => self.crlf = false,
+            NewlineStyle::Crlf => self.crlf = true,
+        }
+        match bom {
+            BomStyle::Keep => {}
+            BomStyle::Add => self.bom = true,
+            BomStyle::Remove => self.bom = false,
+        }
+
+        self
+    }
+}
+
+/// decode file content, non utf-8 content encoding is detected by chardet heuristics, the bom is
+/// removed from the content
+pub fn decode(data: &[u8]) -> (String, FileStyle) {
+    let (text, encoding, bom) = match Encoding::for_bom(data) {
+        Some((encoding, bom_len)) => {
+            let (text, _) = encoding.decode_without_bom_handling(&data[bom_len..]);
+
+            (text.into_owned(), encoding, true)
+        }
+
+        None => match std::str::from_utf8(data) {
+            Ok(text) => (text.to_string(), UTF_8, false),
+            Err(_) => {
+                let mut detector = EncodingDetector::new();
+                detector.feed(data, true);
+                let encoding = detector.guess(None, true);
+                let (text, _) = encoding.decode_without_bom_handling(data);
+
+                (text.into_owned(), encoding, false)
+            }
+        },
+    };
+
+    let crlf = text.contains("\r\n");
+
+    (
+        text,
+        FileStyle {
+            encoding,
+            bom,
+            crlf,
+        },
+    )
+}
+
+/// whether the file is text by its first bytes, binary data has nul bytes or can't be decoded
+/// without malformed sequences
+pub fn is_text_file(path: &Path) -> io::Result<bool> {
+    let mut prefix = Vec::with_capacity(SNIFF_LEN);
+    fs::File::open(path)?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut prefix)?;
+
+    Ok(is_text(&prefix))
+}
+
+/// the data may end in the middle of a character
+fn is_text(data: &[u8]) -> bool {
+    match Encoding::for_bom(data) {
+        Some((encoding, bom_len)) => decodes(encoding, &data[bom_len..]),
+
+        None if data.contains(&0) => false,
+        None => match std::str::from_utf8(data) {
+            Ok(_) => true,
+            Err(err) if err.error_len().is_none() => true,
+            Err(_) => {
+                let mut detector = EncodingDetector::new();
+                detector.feed(data, true);
+
+                decodes(detector.guess(None, true), data)
+            }
+        },
+    }
+}
+
+fn decodes(encoding: &'static Encoding, data: &[u8]) -> bool {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let Some(len) = decoder.max_utf8_buffer_length(data.len()) else {
+        return false;
+    };
+    let mut text = String::with_capacity(len);
+    let (_, _, had_errors) = decoder.decode_to_string(data, &mut text, false);
+
+    !had_errors
+}
+
+/// encode text with the file style, the text newline should be lf, characters which can't be
+/// encoded are replaced by html numeric character references
+pub fn encode(text: &str, style: FileStyle, keep_encoding: bool) -> Vec<u8> {
+    let mut text = if style.crlf {
+        Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+    } else {
+        Cow::Borrowed(text)
+    };
+
+    let encoding = if keep_encoding { style.encoding } else { UTF_8 };
+    // only utf-8 output can carry the bom
+    if style.bom && encoding.output_encoding() == UTF_8 {
+        text = Cow::Owned(format!("{BOM}{text}"));
+    }
+
+    let (data, _, _) = encoding.encode(&text);
+
+    data.into_owned()
+}
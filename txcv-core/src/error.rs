@@ -0,0 +1,183 @@
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use crate::backend::RateLimited;
+use crate::interrupt::Interrupted;
+
+/// the error of txcv, categorized so the callers and the json error output can match on it, the
+/// message keeps the context of the failed operation
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the credentials are missing or rejected
+    Auth(anyhow::Error),
+    /// the translation provider fails, like a network error or an api error
+    Provider(anyhow::Error),
+    /// the provider still rejects the request by its rate limit after the retries
+    RateLimited(anyhow::Error),
+    /// the backend doesn't support the language pair
+    UnsupportedPair(anyhow::Error),
+    /// reading or writing a file fails
+    Io(anyhow::Error),
+    /// the local translation cache is broken
+    Cache(anyhow::Error),
+    /// cancelled by ctrl-c
+    Interrupted,
+    Other(anyhow::Error),
+}
+
+impl Error {
+    /// the category name, used as the `kind` of the json errors
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Auth(_) => "auth",
+            Error::Provider(_) => "provider",
+            Error::RateLimited(_) => "rate_limited",
+            Error::UnsupportedPair(_) => "unsupported_pair",
+            Error::Io(_) => "io",
+            Error::Cache(_) => "cache",
+            Error::Interrupted => "interrupted",
+            Error::Other(_) => "other",
+        }
+    }
+
+    fn inner(&self) -> Option<&anyhow::Error> {
+        match self {
+            Error::Auth(err)
+            | Error::Provider(err)
+            | Error::RateLimited(err)
+            | Error::UnsupportedPair(err)
+            | Error::Io(err)
+            | Error::Cache(err)
+            | Error::Other(err) => Some(err),
+            Error::Interrupted => None,
+        }
+    }
+}
+
+/// the alternate format has the causes too, like anyhow
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.inner() {
+            None => Display::fmt(&Interrupted, f),
+            Some(err) if f.alternate() => write!(f, "{err:#}"),
+            Some(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.inner().and_then(|err| {
+            let top: &(dyn StdError + 'static) = err.as_ref();
+
+            top.source()
+        })
+    }
+}
+
+/// categorize by the first cause of the chain which has a category, the outer context is kept
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        // only unwrap the categorized error without context, downcasting drops the context
+        let top: &(dyn StdError + 'static) = err.as_ref();
+        let err = match top.is::<Error>() {
+            false => err,
+            true => match err.downcast::<Error>() {
+                Ok(err) => return err,
+                Err(err) => err,
+            },
+        };
+
+        match err.chain().find_map(category) {
+            None => Error::Other(err),
+            Some(category) => category(err),
+        }
+    }
+}
+
+/// the constructor of the category of the cause
+fn category(cause: &(dyn StdError + 'static)) -> Option<fn(anyhow::Error) -> Error> {
+    if let Some(err) = cause.downcast_ref::<Error>() {
+        let constructor: fn(anyhow::Error) -> Error = match err {
+            Error::Auth(_) => Error::Auth,
+            Error::Provider(_) => Error::Provider,
+            Error::RateLimited(_) => Error::RateLimited,
+            Error::UnsupportedPair(_) => Error::UnsupportedPair,
+            Error::Io(_) => Error::Io,
+            Error::Cache(_) => Error::Cache,
+            Error::Interrupted => |_| Error::Interrupted,
+            Error::Other(_) => Error::Other,
+        };
+
+        return Some(constructor);
+    }
+
+    if cause.is::<Interrupted>() {
+        return Some(|_| Error::Interrupted);
+    }
+    if cause.is::<RateLimited>() {
+        return Some(Error::RateLimited);
+    }
+    if let Some(err) = cause.downcast_ref::<tencentcloud::Error>() {
+        let constructor: fn(anyhow::Error) -> Error = match err {
+            tencentcloud::Error::Api { err, .. } if err.code.starts_with("AuthFailure") => {
+                Error::Auth
+            }
+            tencentcloud::Error::Api { err, .. }
+                if err.code.starts_with("RequestLimitExceeded") =>
+            {
+                Error::RateLimited
+            }
+            _ => Error::Provider,
+        };
+
+        return Some(constructor);
+    }
+    if let Some(err) = cause.downcast_ref::<ureq::Error>() {
+        let constructor: fn(anyhow::Error) -> Error = match err {
+            ureq::Error::Status(401 | 403, _) => Error::Auth,
+            _ => Error::Provider,
+        };
+
+        return Some(constructor);
+    }
+    if cause.is::<io::Error>() {
+        return Some(Error::Io);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn categorize_by_cause() {
+        let err = Error::from(
+            Err::<(), _>(io::Error::from(io::ErrorKind::NotFound))
+                .context("read glossary.csv failed")
+                .unwrap_err(),
+        );
+        assert_eq!(err.kind(), "io");
+        assert_eq!(err.to_string(), "read glossary.csv failed");
+        assert!(format!("{err:#}").starts_with("read glossary.csv failed: "));
+
+        let err = Error::from(anyhow::Error::new(Error::Cache(anyhow::anyhow!("broken"))));
+        assert_eq!(err.kind(), "cache");
+
+        let err = Error::from(
+            anyhow::Error::new(Error::Auth(anyhow::anyhow!("rejected"))).context("auth test"),
+        );
+        assert_eq!(err.kind(), "auth");
+        assert_eq!(
+            Error::from(anyhow::Error::new(RateLimited)).kind(),
+            "rate_limited"
+        );
+        assert_eq!(Error::from(anyhow::anyhow!("unknown")).kind(), "other");
+    }
+}
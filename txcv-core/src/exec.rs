@@ -0,0 +1,135 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+
+use async_std::channel::{self, Receiver, Sender};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// the wrapped command exits with a non-zero code, txcv exits with the same code, or 128 + the
+/// signal number like shells when the command is killed by a signal
+#[derive(Debug)]
+pub struct ChildFailed(pub i32);
+
+impl Display for ChildFailed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command exits with code {}", self.0)
+    }
+}
+
+impl Error for ChildFailed {}
+
+/// spawn the command with piped stdout and stderr, the lines are received in the order they are
+/// read, the receiver is closed when both streams end
+pub fn spawn(command: &[String]) -> anyhow::Result<(Child, Receiver<(Stream, String)>)> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("command is required"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| anyhow::anyhow!("run {program} failed: {err}"))?;
+
+    let (sender, receiver) = channel::unbounded();
+    if let Some(stdout) = child.stdout.take() {
+        read_lines(stdout, Stream::Stdout, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        read_lines(stderr, Stream::Stderr, sender);
+    }
+
+    Ok((child, receiver))
+}
+
+/// wait for the command to exit, ChildFailed if it fails
+pub fn wait(mut child: Child) -> anyhow::Result<()> {
+    let status = child.wait()?;
+    if status.success() {
+        return Ok(());
+    }
+
+    Err(ChildFailed(exit_code(status)).into())
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+
+    status.code().unwrap_or(1)
+}
+
+/// the pipes are blocking, read them in threads, lines which are not utf-8 are decoded lossily
+fn read_lines<R: Read + Send + 'static>(
+    reader: R,
+    stream: Stream,
+    sender: Sender<(Stream, String)>,
+) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut line = vec![];
+        while let Ok(n) = reader.read_until(b'\n', &mut line) {
+            if n == 0 {
+                break;
+            }
+
+            let text = String::from_utf8_lossy(&line)
+                .trim_end_matches(['\r', '\n'])
+                .to_string();
+            if sender.send_blocking((stream, text)).is_err() {
+                break;
+            }
+            line.clear();
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Vec<String> {
+        ["sh", "-c", script].map(String::from).to_vec()
+    }
+
+    fn code(result: anyhow::Result<()>) -> i32 {
+        result.unwrap_err().downcast::<ChildFailed>().unwrap().0
+    }
+
+    #[async_std::test]
+    async fn tag_lines_and_keep_exit_code() {
+        let (child, lines) = spawn(&sh("echo out; echo err >&2; exit 3")).unwrap();
+
+        let mut received = vec![];
+        while let Ok(line) = lines.recv().await {
+            received.push(line);
+        }
+        // the streams are read by their own threads
+        received.sort_by_key(|(stream, _)| *stream == Stream::Stderr);
+        assert_eq!(
+            received,
+            [
+                (Stream::Stdout, "out".to_string()),
+                (Stream::Stderr, "err".to_string())
+            ]
+        );
+        assert_eq!(code(wait(child)), 3);
+    }
+
+    #[test]
+    fn killed_by_signal() {
+        let (child, _) = spawn(&sh("kill -TERM $$")).unwrap();
+
+        assert_eq!(code(wait(child)), 128 + 15);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::{Debug, Formatter};
+
+use async_std::task;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Llm;
+
+const DEFAULT_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+/// local llm servers don't need it
+const API_KEY_ENV: &str = "TXCV_LLM_API_KEY";
+/// the llm answers it when the text has nothing to explain
+const NOTHING: &str = "none";
+const NOTE_INDENT: &str = "    ";
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    content: String,
+}
+
+/// explain grammar points and idioms of the source text by an openai compatible chat api
+#[derive(Clone)]
+pub struct Explainer {
+    agent: ureq::Agent,
+    api_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl Debug for Explainer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Explainer")
+            .field("api_url", &self.api_url)
+            .field("model", &self.model)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Explainer {
+    pub fn new(llm: &Llm) -> Self {
+        Self {
+            agent: ureq::Agent::new(),
+            api_url: llm
+                .api_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+            model: llm
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            api_key: std::env::var(API_KEY_ENV).ok(),
+        }
+    }
+
+    /// explain in the target language, which is the language the user reads, return none when
+    /// there is nothing worth explaining
+    pub async fn explain(&self, text: &str, target_lang: &str) -> anyhow::Result<Option<String>> {
+        let explainer = self.clone();
+        let prompt = format!(
+            "You help language learners. Briefly explain the grammar points and idioms in the text \
+             given by the user, in {}. Use at most 3 short lines and don't translate the whole \
+             text. Answer {NOTHING} if nothing is worth explaining.",
+            language_name(target_lang)
+        );
+        let body = json!({
+            "model": explainer.model,
+            "messages": [
+                { "role": "system", "content": prompt },
+                { "role": "user", "content": text },
+            ],
+        });
+
+        let note = task::spawn_blocking(move || {
+            let mut request = explainer.agent.post(&explainer.api_url);
+            if let Some(api_key) = &explainer.api_key {
+                request = request.set("Authorization", &format!("Bearer {api_key}"));
+            }
+
+            let resp = match request.send_json(body) {
+                Err(ureq::Error::Status(code, resp)) => {
+                    let message = resp.into_string().unwrap_or_default();
+
+                    return Err(anyhow::anyhow!("llm api error {code}: {message}"));
+                }
+                result => result?,
+            };
+
+            resp.into_json::<ChatResponse>()?
+                .choices
+                .pop()
+                .map(|choice| choice.message.content)
+                .ok_or_else(|| anyhow::anyhow!("llm returns no answer"))
+        })
+        .await?;
+
+        let note = note.trim();
+        if note.is_empty() || note.eq_ignore_ascii_case(NOTHING) {
+            return Ok(None);
+        }
+
+        Ok(Some(indent(note)))
+    }
+}
+
+/// indent the note so it's told apart from the translation
+fn indent(note: &str) -> String {
+    note.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| format!("{NOTE_INDENT}{}", line.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn language_name(code: &str) -> &str {
+    match code {
+        "zh" => "Simplified Chinese",
+        "zh-TW" => "Traditional Chinese",
+        "jp" => "Japanese",
+        "ko" => "Korean",
+        "fr" => "French",
+        "es" => "Spanish",
+        "it" => "Italian",
+        "de" => "German",
+        "tr" => "Turkish",
+        "ru" => "Russian",
+        "pt" => "Portuguese",
+        "vi" => "Vietnamese",
+        "id" => "Indonesian",
+        "th" => "Thai",
+        "ms" => "Malay",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        // the target language is unknown when the translation comes from memory
+        _ => "English",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indent_note() {
+        assert_eq!(
+            indent("- break a leg: good luck\n\n  - idiom\n"),
+            "    - break a leg: good luck\n    - idiom"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs;
+#[cfg(feature = "interactive")]
+use std::io::IsTerminal;
+use std::io::{ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use age::secrecy::{ExposeSecret, Secret, SecretString};
+use age::Decryptor;
+use anyhow::Context;
+#[cfg(feature = "interactive")]
+use requestty::Question;
+
+use crate::atomic;
+
+const CREDENTIALS_FILE: &str = "credentials.age";
+/// passphrase of the credentials file
+const PASSPHRASE_ENV: &str = "TXCV_PASSPHRASE";
+/// command which prints the passphrase, like `pass show txcv` or a secret agent client
+const PASSPHRASE_COMMAND_ENV: &str = "TXCV_PASSPHRASE_COMMAND";
+
+/// credentials stored in an age passphrase encrypted file, for systems without secret service
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl FileStore {
+    /// the credentials file opened once by the process, the scrypt decryption takes about a
+    /// second, so it isn't repeated for every entry
+    pub fn shared() -> anyhow::Result<MutexGuard<'static, Self>> {
+        static STORE: OnceLock<Mutex<FileStore>> = OnceLock::new();
+
+        if let Some(store) = STORE.get() {
+            return Ok(store.lock().unwrap());
+        }
+
+        let store = Self::open()?;
+
+        Ok(STORE.get_or_init(|| Mutex::new(store)).lock().unwrap())
+    }
+
+    /// open the credentials file under the data dir, the passphrase is only needed when the file
+    /// exists
+    fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(CREDENTIALS_FILE);
+
+        let data = match fs::read(&path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Self {
+                    path,
+                    entries: BTreeMap::new(),
+                })
+            }
+            Err(err) => return Err(err.into()),
+            Ok(data) => data,
+        };
+
+        let passphrase = passphrase()?;
+        let decryptor = match Decryptor::new(&data[..])
+            .with_context(|| format!("read {} failed", path.display()))?
+        {
+            Decryptor::Passphrase(decryptor) => decryptor,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "{} is not encrypted with passphrase",
+                    path.display()
+                ))
+            }
+        };
+
+        let mut plaintext = vec![];
+        decryptor
+            .decrypt(&passphrase, None)
+            .with_context(|| format!("decrypt {} failed, check the passphrase", path.display()))?
+            .read_to_end(&mut plaintext)?;
+        let entries = serde_json::from_slice(&plaintext)
+            .with_context(|| format!("parse {} failed", path.display()))?;
+
+        Ok(Self { path, entries })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.entries.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    /// encrypt and write the entries, the file is only readable by the owner
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut encrypted = vec![];
+        let mut writer =
+            age::Encryptor::with_user_passphrase(passphrase()?).wrap_output(&mut encrypted)?;
+        writer.write_all(&serde_json::to_vec(&self.entries)?)?;
+        writer.finish()?;
+
+        atomic::write_private(&self.path, &encrypted)
+            .with_context(|| format!("write {} failed", self.path.display()))
+    }
+}
+
+/// read passphrase from TXCV_PASSPHRASE, the output of TXCV_PASSPHRASE_COMMAND, or ask it on the
+/// terminal, the passphrase is cached so the command runs and the question is asked at most once
+fn passphrase() -> anyhow::Result<SecretString> {
+    static PASSPHRASE: OnceLock<SecretString> = OnceLock::new();
+
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = read_passphrase()?;
+
+    Ok(PASSPHRASE.get_or_init(|| passphrase).clone())
+}
+
+fn read_passphrase() -> anyhow::Result<SecretString> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(Secret::new(passphrase));
+    }
+
+    let command = match std::env::var(PASSPHRASE_COMMAND_ENV) {
+        Err(_) => return ask_passphrase(),
+        Ok(command) => command,
+    };
+
+    #[cfg(unix)]
+    let output = process::Command::new("sh").arg("-c").arg(&command).output();
+    #[cfg(windows)]
+    let output = process::Command::new("cmd")
+        .arg("/C")
+        .arg(&command)
+        .output();
+    let output = output.with_context(|| format!("run passphrase command {command} failed"))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "passphrase command {command} failed with {}",
+            output.status
+        ));
+    }
+
+    let passphrase = Secret::new(String::from_utf8(output.stdout)?);
+    let trimmed = passphrase.expose_secret().trim_end_matches(['\r', '\n']);
+
+    Ok(Secret::new(trimmed.to_string()))
+}
+
+/// ask the passphrase on the terminal, like over ssh without a secret service
+#[cfg(feature = "interactive")]
+fn ask_passphrase() -> anyhow::Result<SecretString> {
+    if !std::io::stdin().is_terminal() {
+        return Err(no_passphrase());
+    }
+
+    let question = Question::password("passphrase")
+        .message("passphrase of the credentials file")
+        .build();
+    let answer = requestty::prompt_one(question)?;
+    let passphrase = answer
+        .as_string()
+        .filter(|passphrase| !passphrase.is_empty())
+        .ok_or_else(no_passphrase)?;
+
+    Ok(Secret::new(passphrase.to_string()))
+}
+
+#[cfg(not(feature = "interactive"))]
+fn ask_passphrase() -> anyhow::Result<SecretString> {
+    Err(no_passphrase())
+}
+
+fn no_passphrase() -> anyhow::Error {
+    anyhow::anyhow!(
+        "file credential store needs passphrase, set {PASSPHRASE_ENV} or {PASSPHRASE_COMMAND_ENV}"
+    )
+}
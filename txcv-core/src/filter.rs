@@ -0,0 +1,271 @@
+//! filters of the translation pipeline, a filter rewrites the source text before translating, or
+//! finds the spans which are kept untranslated by placeholders
+
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// markdown code fences
+const FENCES: &[&str] = &["```", "~~~"];
+/// characters trimmed from the end of an url, they are usually the punctuation of the sentence
+const URL_TRAILING: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '}', '\'', '"'];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Filter {
+    /// keep markdown code blocks and inline code untranslated
+    Code,
+    /// keep format placeholders like `{name}`, `%s` and `{{count}}` untranslated
+    Placeholders,
+    /// join the hard wrapped lines of a paragraph, so the sentences aren't broken
+    Unwrap,
+    /// keep urls and emails untranslated
+    Urls,
+}
+
+/// rewrite the text by the filters in order
+pub fn rewrite(text: &str, filters: &[Filter]) -> String {
+    filters
+        .iter()
+        .fold(text.to_string(), |text, filter| match filter {
+            Filter::Unwrap => unwrap_lines(&text),
+            Filter::Code | Filter::Placeholders | Filter::Urls => text,
+        })
+}
+
+/// the spans kept untranslated by the filters, every span is restored to itself
+pub fn spans(text: &str, filters: &[Filter]) -> Vec<(Range<usize>, String)> {
+    let mut spans = vec![];
+    for filter in filters {
+        match filter {
+            Filter::Code => spans.extend(code_spans(text)),
+            Filter::Placeholders => spans.extend(placeholder_spans(text)),
+            Filter::Urls => spans.extend(url_spans(text)),
+            Filter::Unwrap => {}
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|range| (range.clone(), text[range].to_string()))
+        .collect()
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+
+    FENCES.iter().any(|fence| line.starts_with(fence))
+}
+
+/// the lines which start a markdown block, they are never joined to the previous line
+fn is_block_start(line: &str) -> bool {
+    let line = line.trim_start();
+    let ordered = line.split_once(['.', ')']).is_some_and(|(number, rest)| {
+        !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+            && (rest.is_empty() || rest.starts_with(' '))
+    });
+
+    ordered
+        || ["- ", "* ", "+ ", "#", ">", "|"]
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+}
+
+/// wide characters like cjk are joined without a space
+fn is_wide(c: char) -> bool {
+    c >= '\u{2e80}'
+}
+
+/// join the hard wrapped lines of every paragraph, blank lines, code blocks, headings, list items,
+/// quotes, tables and markdown hard breaks are kept
+fn unwrap_lines(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_fence = false;
+    // the paragraph is an indented code block
+    let mut in_indented = false;
+    let mut previous: Option<&str> = None;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let blank = content.trim().is_empty();
+        let fence = is_fence(content);
+        let paragraph_start = previous.is_none_or(|previous| previous.trim().is_empty());
+        let indented = content.starts_with("    ") || content.starts_with('\t');
+        in_indented = indented && (paragraph_start || in_indented);
+
+        let join = match previous {
+            Some(previous) => {
+                !in_fence
+                    && !fence
+                    && !blank
+                    && !in_indented
+                    && !previous.trim().is_empty()
+                    && !is_fence(previous)
+                    && !is_block_start(content)
+                    && !previous.trim_start().starts_with(['#', '|'])
+                    && !previous.ends_with("  ")
+            }
+            None => false,
+        };
+
+        if join {
+            // replace the line break of the previous line
+            let end = result.trim_end_matches(['\r', '\n']).len();
+            result.truncate(end);
+            let last = result.chars().next_back();
+            let next = content.trim_start();
+            if !last.is_some_and(is_wide) || !next.chars().next().is_some_and(is_wide) {
+                result.push(' ');
+            }
+            result.push_str(next);
+            result.push_str(&line[content.len()..]);
+        } else {
+            result.push_str(line);
+        }
+
+        if fence {
+            in_fence = !in_fence;
+        }
+        previous = Some(content);
+    }
+
+    result
+}
+
+/// fenced code blocks and inline code
+fn code_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut fence_start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if is_fence(line) {
+            match fence_start {
+                None => fence_start = Some(offset),
+                Some(start) => {
+                    spans.push(start..offset + line.trim_end_matches(['\r', '\n']).len());
+                    fence_start = None;
+                }
+            }
+        } else if fence_start.is_none() {
+            spans.extend(
+                inline_code(line)
+                    .into_iter()
+                    .map(|range| offset + range.start..offset + range.end),
+            );
+        }
+
+        offset += line.len();
+    }
+
+    // an unclosed fence runs to the end
+    if let Some(start) = fence_start {
+        spans.push(start..text.len());
+    }
+
+    spans
+}
+
+/// inline code like `` `cargo build` ``, the closing backticks must be as many as the opening ones
+fn inline_code(line: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = 0;
+    while let Some(found) = line[start..].find('`') {
+        let open = start + found;
+        let ticks = line[open..].chars().take_while(|c| *c == '`').count();
+        let delimiter = "`".repeat(ticks);
+        let content = open + ticks;
+
+        match line[content..].find(&delimiter) {
+            None => break,
+            Some(close) => {
+                let end = content + close + ticks;
+                spans.push(open..end);
+                start = end;
+            }
+        }
+    }
+
+    spans
+}
+
+/// the placeholders of the common format strings, like `{0}`, `{{count}}`, `${name}`, `%1$s` and
+/// `%(name)s`
+fn placeholder_spans(text: &str) -> Vec<Range<usize>> {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+
+    let placeholder = PLACEHOLDER.get_or_init(|| {
+        Regex::new(
+            r"\{\{[^{}]*\}\}|\$\{[^{}]*\}|\{[\w.:,-]*\}|%(?:\d+\$)?[-+#0]*\d*(?:\.\d+)?[sdifuxXoeEgGcp@]|%\([\w.-]+\)[sdifr]|%%",
+        )
+        .unwrap()
+    });
+
+    placeholder
+        .find_iter(text)
+        .map(|found| found.range())
+        .collect()
+}
+
+fn url_spans(text: &str) -> Vec<Range<usize>> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+
+    let url = URL.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:https?://|www\.)[^\s<>]+|\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b",
+        )
+        .unwrap()
+    });
+
+    url.find_iter(text)
+        .map(|found| {
+            let trimmed = found.as_str().trim_end_matches(URL_TRAILING);
+
+            found.start()..found.start() + trimmed.len()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwrap_hard_wrapped_lines() {
+        let text = "This is a\nhard wrapped  \nparagraph.\n\n- item one\n- item\n  two\n\n```\nlet a;\nlet b;\n```\n\n    indented\n    code\n# 标题\n中文\n换行\n";
+
+        assert_eq!(
+            unwrap_lines(text),
+            "This is a hard wrapped  \nparagraph.\n\n- item one\n- item two\n\n```\nlet a;\nlet b;\n```\n\n    indented\n    code\n# 标题\n中文换行\n"
+        );
+    }
+
+    #[test]
+    fn keep_code_and_urls() {
+        let text = "run `cargo build`, see https://example.com/a.\n```sh\nmake\n```\nmail a@b.io";
+        let spans = spans(text, &[Filter::Code, Filter::Urls])
+            .into_iter()
+            .map(|(_, span)| span)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            spans,
+            [
+                "`cargo build`",
+                "```sh\nmake\n```",
+                "https://example.com/a",
+                "a@b.io"
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_placeholders() {
+        let text = "Hi {name}, {{count}} of %d files in %(dir)s are 100% done, ${total} left";
+        let spans = spans(text, &[Filter::Placeholders])
+            .into_iter()
+            .map(|(_, span)| span)
+            .collect::<Vec<_>>();
+
+        assert_eq!(spans, ["{name}", "{{count}}", "%d", "%(dir)s", "${total}"]);
+    }
+}
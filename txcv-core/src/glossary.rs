@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+
+const GLOSSARY_FILE: &str = "glossary.csv";
+/// used as the default glossary instead of the csv one if exists
+const TOML_GLOSSARY_FILE: &str = "glossary.toml";
+
+/// the toml glossary, like
+///
+/// ```toml
+/// [terms]
+/// "pull request" = "合并请求"
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TomlGlossary {
+    #[serde(default)]
+    terms: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+pub struct Deviation<'a> {
+    /// segment index, start from 1
+    pub segment: usize,
+    pub term: &'a Term,
+}
+
+impl Glossary {
+    /// load glossary file, a `.toml` file has a `[terms]` table, otherwise each line is
+    /// `term,translation`, empty lines and lines start with `#` are ignored
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("read glossary {} failed", path.display()))?;
+
+        if is_toml(path) {
+            let glossary = toml::from_str::<TomlGlossary>(&content)
+                .with_context(|| format!("parse glossary {} failed", path.display()))?;
+            let terms = glossary
+                .terms
+                .into_iter()
+                .map(|(source, target)| Term { source, target })
+                .collect();
+
+            return Ok(Self { terms });
+        }
+
+        let terms = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                let (source, target) = line
+                    .split_once(',')
+                    .with_context(|| format!("invalid glossary line {}: {line}", index + 1))?;
+
+                Ok(Term {
+                    source: source.trim().to_string(),
+                    target: target.trim().to_string(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { terms })
+    }
+
+    /// load the glossary file, an empty glossary if not exists
+    pub fn load_or_default(path: &Path) -> anyhow::Result<Self> {
+        match fs::metadata(path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            _ => Self::load(path),
+        }
+    }
+
+    /// the glossary under the config dir, used when --glossary is not set, the toml one is
+    /// preferred if exists
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = dirs::config_dir()?.join("txcv");
+        let toml = dir.join(TOML_GLOSSARY_FILE);
+        if toml.exists() {
+            return Some(toml);
+        }
+
+        Some(dir.join(GLOSSARY_FILE))
+    }
+
+    /// write the glossary file, the comments of a csv file are not kept
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content = if is_toml(path) {
+            toml::to_string(&TomlGlossary {
+                terms: self
+                    .terms
+                    .iter()
+                    .map(|term| (term.source.clone(), term.target.clone()))
+                    .collect(),
+            })?
+        } else {
+            if let Some(term) = self
+                .terms
+                .iter()
+                .find(|term| term.source.contains([',', '\n']))
+            {
+                return Err(anyhow::anyhow!(
+                    "term {} can't be written to a csv glossary, use a toml glossary",
+                    term.source
+                ));
+            }
+
+            self.terms
+                .iter()
+                .map(|term| format!("{},{}\n", term.source, term.target))
+                .collect()
+        };
+
+        atomic::write(path, content.as_bytes())
+            .with_context(|| format!("write glossary {} failed", path.display()))
+    }
+
+    /// add the term, the term with the same source is replaced, case insensitive
+    pub fn add(&mut self, term: Term) {
+        match self
+            .terms
+            .iter_mut()
+            .find(|old| old.source.eq_ignore_ascii_case(&term.source))
+        {
+            None => self.terms.push(term),
+            Some(old) => *old = term,
+        }
+    }
+
+    /// remove the term of the source, case insensitive, false if not found
+    pub fn remove(&mut self, source: &str) -> bool {
+        let len = self.terms.len();
+        self.terms
+            .retain(|term| !term.source.eq_ignore_ascii_case(source));
+
+        self.terms.len() != len
+    }
+
+    /// append a term to the glossary file, the file is created if not exists
+    pub fn append(path: &Path, term: &Term) -> anyhow::Result<()> {
+        if is_toml(path) {
+            let mut glossary = Self::load_or_default(path)?;
+            glossary.add(term.clone());
+
+            return glossary.save(path);
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // don't join the term to the last line
+        let separator = match fs::read_to_string(path) {
+            Ok(content) if !content.is_empty() && !content.ends_with('\n') => "\n",
+            _ => "",
+        };
+
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{separator}{},{}", term.source, term.target))
+            .with_context(|| format!("write glossary {} failed", path.display()))
+    }
+
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// find the glossary terms in the text, ascii case insensitive, every span is restored to the
+    /// translation of its term, the longer term wins when they overlap, a term starts or ends
+    /// with an alphanumeric character must not be a part of a word
+    pub fn spans(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        let mut terms = self
+            .terms
+            .iter()
+            .filter(|term| !term.source.is_empty())
+            .collect::<Vec<_>>();
+        terms.sort_by_key(|term| std::cmp::Reverse(term.source.len()));
+
+        let mut spans: Vec<(Range<usize>, String)> = vec![];
+        for (start, _) in text.char_indices() {
+            if spans.last().is_some_and(|(span, _)| start < span.end) {
+                continue;
+            }
+
+            let before = text[..start].chars().next_back();
+            for term in &terms {
+                let end = start + term.source.len();
+                let matched = text
+                    .get(start..end)
+                    .is_some_and(|span| span.eq_ignore_ascii_case(&term.source));
+                if !matched {
+                    continue;
+                }
+
+                let after = text[end..].chars().next();
+                let inside_word = |edge: Option<char>, neighbor: Option<char>| {
+                    edge.is_some_and(char::is_alphanumeric)
+                        && neighbor.is_some_and(char::is_alphanumeric)
+                };
+                if inside_word(term.source.chars().next(), before)
+                    || inside_word(term.source.chars().next_back(), after)
+                {
+                    continue;
+                }
+
+                spans.push((start..end, term.target.clone()));
+                break;
+            }
+        }
+
+        spans
+    }
+
+    /// check every segment which contains glossary term in source is translated with the
+    /// glossary translation
+    pub fn check<S: AsRef<str>, T: AsRef<str>>(&self, segments: &[(S, T)]) -> Vec<Deviation<'_>> {
+        segments
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (source, target))| {
+                let source = source.as_ref().to_lowercase();
+                let target = target.as_ref().to_lowercase();
+
+                self.terms
+                    .iter()
+                    .filter(move |term| {
+                        source.contains(&term.source.to_lowercase())
+                            && !target.contains(&term.target.to_lowercase())
+                    })
+                    .map(move |term| Deviation {
+                        segment: index + 1,
+                        term,
+                    })
+            })
+            .collect()
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(source: &str, target: &str) -> Term {
+        Term {
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn find_term_spans() {
+        let mut glossary = Glossary::default();
+        glossary.add(term("pull request", "合并请求"));
+        glossary.add(term("pull", "拉取"));
+        glossary.add(term("PR", "合并请求"));
+
+        let text = "Pull request and pull, not PRs";
+        assert_eq!(
+            glossary.spans(text),
+            vec![
+                (0..12, "合并请求".to_string()),
+                (17..21, "拉取".to_string()),
+            ]
+        );
+
+        glossary.add(term("Pull", "拉"));
+        assert!(glossary.remove("pull request"));
+        assert!(!glossary.remove("pull request"));
+        assert_eq!(glossary.terms().len(), 2);
+        assert_eq!(glossary.spans("pull")[0].1, "拉");
+    }
+}
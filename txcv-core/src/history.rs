@@ -0,0 +1,268 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+
+const HISTORY_FILE: &str = "history.jsonl";
+/// the original history is kept here when corrupted lines are removed
+const BACKUP_FILE: &str = "history.jsonl.bak";
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum HistoryFormat {
+    #[default]
+    Plain,
+    Json,
+    Csv,
+}
+
+impl Display for HistoryFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryFormat::Plain => f.write_str("plain"),
+            HistoryFormat::Json => f.write_str("json"),
+            HistoryFormat::Csv => f.write_str("csv"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// unix timestamp
+    pub timestamp: u64,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub input: String,
+    pub output: String,
+}
+
+impl Entry {
+    pub fn new(source_lang: &str, target_lang: &str, input: &str, output: &str) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            source_lang: source_lang.to_string(),
+            target_lang: target_lang.to_string(),
+            input: input.to_string(),
+            output: output.to_string(),
+        }
+    }
+
+    fn time(&self) -> String {
+        humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(self.timestamp))
+            .to_string()
+    }
+
+    fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+
+        self.input.to_lowercase().contains(&keyword)
+            || self.output.to_lowercase().contains(&keyword)
+    }
+}
+
+/// translated queries, one json per line, appending a line is locked for concurrent txcv, a line
+/// torn by a crash only loses itself
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(HISTORY_FILE);
+
+        Ok(Self { path })
+    }
+
+    pub fn append(&self, entry: &Entry) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                // concurrent txcv append in turn
+                file.lock()?;
+
+                // terminate the line torn by a crash, or the new line is appended to it
+                if file.metadata()?.len() > 0 {
+                    let mut last = [0];
+                    file.seek(SeekFrom::End(-1))?;
+                    file.read_exact(&mut last)?;
+                    if last[0] != b'\n' {
+                        line.insert(0, b'\n');
+                    }
+                }
+
+                file.write_all(&line)?;
+                file.sync_data()
+            })
+            .with_context(|| format!("write history {} failed", self.path.display()))
+    }
+
+    /// entries from the oldest to the newest
+    pub fn entries(&self) -> anyhow::Result<Vec<Entry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            result => {
+                result.with_context(|| format!("read history {} failed", self.path.display()))?
+            }
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!(
+                        "parse history {} line {} failed, repair it by `txcv cache verify`",
+                        self.path.display(),
+                        index + 1
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// remove the lines which can't be parsed, the original history is backed up if any line is
+    /// removed, return the count of kept entries and removed lines
+    pub fn verify(&self) -> anyhow::Result<(usize, usize)> {
+        let content = match fs::read(&self.path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok((0, 0)),
+            result => {
+                result.with_context(|| format!("read history {} failed", self.path.display()))?
+            }
+        };
+
+        let (kept, removed) = valid_lines(&content);
+        if removed > 0 {
+            let backup = self.path.with_file_name(BACKUP_FILE);
+            atomic::write(&backup, &content)
+                .with_context(|| format!("back up history to {} failed", backup.display()))?;
+            atomic::write(&self.path, kept.concat().as_bytes())
+                .with_context(|| format!("write history {} failed", self.path.display()))?;
+        }
+
+        Ok((kept.len(), removed))
+    }
+}
+
+/// the lines which are valid entries, with the newlines, and the count of the others, blank lines
+/// are dropped silently
+fn valid_lines(content: &[u8]) -> (Vec<String>, usize) {
+    let mut kept = vec![];
+    let mut removed = 0;
+    for line in content.split(|b| *b == b'\n') {
+        let line = match std::str::from_utf8(line) {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) if serde_json::from_str::<Entry>(line).is_ok() => line,
+            _ => {
+                removed += 1;
+                continue;
+            }
+        };
+
+        kept.push(format!("{line}\n"));
+    }
+
+    (kept, removed)
+}
+
+/// print the entries which contain keyword, only the newest limit entries are printed if limit
+/// is set
+pub fn print<W: Write>(
+    entries: &[Entry],
+    keyword: Option<&str>,
+    limit: Option<usize>,
+    format: HistoryFormat,
+    mut writer: W,
+) -> anyhow::Result<()> {
+    let entries = entries
+        .iter()
+        .filter(|entry| keyword.is_none_or(|keyword| entry.matches(keyword)))
+        .collect::<Vec<_>>();
+    let entries = &entries[entries.len() - limit.unwrap_or(usize::MAX).min(entries.len())..];
+
+    match format {
+        HistoryFormat::Plain => {
+            for entry in entries {
+                writeln!(
+                    writer,
+                    "{} {}-{} {} -> {}",
+                    entry.time(),
+                    entry.source_lang,
+                    entry.target_lang,
+                    entry.input,
+                    entry.output
+                )?;
+            }
+        }
+
+        HistoryFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, entries)?;
+            writeln!(writer)?;
+        }
+
+        HistoryFormat::Csv => {
+            writeln!(writer, "time,source_lang,target_lang,input,output")?;
+            for entry in entries {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    entry.time(),
+                    escape_csv(&entry.source_lang),
+                    escape_csv(&entry.target_lang),
+                    escape_csv(&entry.input),
+                    escape_csv(&entry.output)
+                )?;
+            }
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_torn_lines() {
+        let entry = serde_json::to_string(&Entry::new("en", "zh", "hello", "你好")).unwrap();
+        let content = format!("{entry}\n{{\"timestamp\":1,\"sou\n\n{entry}\n{{\"ti");
+
+        let (kept, removed) = valid_lines(content.as_bytes());
+        assert_eq!(kept, vec![format!("{entry}\n"), format!("{entry}\n")]);
+        assert_eq!(removed, 2);
+    }
+}
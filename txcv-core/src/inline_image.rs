@@ -0,0 +1,270 @@
+use std::env;
+use std::fmt::{Display, Formatter, Write as _};
+use std::io::Write;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+
+/// the base64 payload of a kitty graphics command is split into chunks of this size
+const KITTY_CHUNK_SIZE: usize = 4096;
+/// sixel images are drawn pixel by pixel, wider images are scaled down
+const SIXEL_MAX_WIDTH: usize = 800;
+/// levels of every channel in the sixel palette, 6 * 6 * 6 colors
+const SIXEL_LEVELS: u32 = 6;
+
+/// how images are drawn in the terminal
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ImageProtocol {
+    /// detect the protocol from the terminal
+    #[default]
+    Auto,
+    /// kitty graphics protocol, also supported by ghostty and wezterm
+    Kitty,
+    /// iterm2 inline images, also supported by wezterm
+    Iterm2,
+    /// sixel graphics, supported by foot, mlterm, contour and xterm with sixel enabled
+    Sixel,
+}
+
+impl Display for ImageProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageProtocol::Auto => f.write_str("auto"),
+            ImageProtocol::Kitty => f.write_str("kitty"),
+            ImageProtocol::Iterm2 => f.write_str("iterm2"),
+            ImageProtocol::Sixel => f.write_str("sixel"),
+        }
+    }
+}
+
+impl ImageProtocol {
+    /// resolve auto by the environment variables of the terminal, none if the terminal can't
+    /// draw images
+    pub fn resolve(self) -> Option<Self> {
+        if self != ImageProtocol::Auto {
+            return Some(self);
+        }
+
+        let var = |name| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term_program == "ghostty"
+        {
+            Some(ImageProtocol::Kitty)
+        } else if matches!(term_program.as_str(), "iTerm.app" | "WezTerm")
+            || var("LC_TERMINAL") == "iTerm2"
+        {
+            Some(ImageProtocol::Iterm2)
+        } else if term.contains("sixel")
+            || ["foot", "mlterm", "contour"]
+                .iter()
+                .any(|prefix| term.starts_with(prefix))
+        {
+            Some(ImageProtocol::Sixel)
+        } else {
+            None
+        }
+    }
+}
+
+/// draw the png image at the cursor by the protocol, auto must be resolved before
+pub fn render<W: Write>(png: &[u8], protocol: ImageProtocol, mut writer: W) -> anyhow::Result<()> {
+    match protocol {
+        ImageProtocol::Auto => unreachable!("auto protocol must be resolved"),
+        ImageProtocol::Kitty => {
+            let data = BASE64_STANDARD.encode(png);
+            let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect::<Vec<_>>();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                // only the first chunk has the control keys
+                if i == 0 {
+                    write!(writer, "\x1b_Ga=T,f=100,m={more};")?;
+                } else {
+                    write!(writer, "\x1b_Gm={more};")?;
+                }
+                writer.write_all(chunk)?;
+                write!(writer, "\x1b\\")?;
+            }
+        }
+
+        ImageProtocol::Iterm2 => write!(
+            writer,
+            "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{}\x07",
+            png.len(),
+            BASE64_STANDARD.encode(png)
+        )?,
+
+        ImageProtocol::Sixel => {
+            let (pixels, width, height) = decode(png)?;
+            let (pixels, width, height) = shrink(&pixels, width, height, SIXEL_MAX_WIDTH);
+            writer.write_all(sixel(&pixels, width, height).as_bytes())?;
+        }
+    }
+
+    writeln!(writer)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// decode the png into rgba pixels
+fn decode(png: &[u8]) -> anyhow::Result<(Vec<[u8; 4]>, usize, usize)> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buf)?;
+    let data = &buf[..frame.buffer_size()];
+
+    let pixels = match frame.color_type {
+        png::ColorType::Rgba => data
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => data
+            .iter()
+            .map(|gray| [*gray, *gray, *gray, u8::MAX])
+            .collect(),
+        png::ColorType::Indexed => return Err(anyhow::anyhow!("indexed png is not expanded")),
+    };
+
+    Ok((pixels, frame.width as _, frame.height as _))
+}
+
+/// scale the image down to the max width by the nearest pixels
+fn shrink(
+    pixels: &[[u8; 4]],
+    width: usize,
+    height: usize,
+    max_width: usize,
+) -> (Vec<[u8; 4]>, usize, usize) {
+    if width <= max_width {
+        return (pixels.to_vec(), width, height);
+    }
+
+    let new_height = (height * max_width / width).max(1);
+    let pixels = (0..new_height)
+        .flat_map(|y| {
+            (0..max_width)
+                .map(move |x| pixels[y * height / new_height * width + x * width / max_width])
+        })
+        .collect();
+
+    (pixels, max_width, new_height)
+}
+
+/// encode the pixels as sixel, the colors are mapped to a 6 * 6 * 6 palette, transparent pixels
+/// are left undrawn
+fn sixel(pixels: &[[u8; 4]], width: usize, height: usize) -> String {
+    let colors = SIXEL_LEVELS.pow(3) as usize;
+    let level = |channel: u8| (u32::from(channel) * (SIXEL_LEVELS - 1) + 127) / 255;
+
+    let mut out = format!("\x1bPq\"1;1;{width};{height}");
+    for color in 0..colors as u32 {
+        let percent = |level: u32| level * 100 / (SIXEL_LEVELS - 1);
+        let (r, g, b) = (
+            color / (SIXEL_LEVELS * SIXEL_LEVELS),
+            color / SIXEL_LEVELS % SIXEL_LEVELS,
+            color % SIXEL_LEVELS,
+        );
+        let _ = write!(
+            out,
+            "#{color};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        // the sixel bits of every column of every color in the band
+        let mut bands = vec![vec![0u8; width]; colors];
+        for row in 0..6.min(height - band) {
+            for x in 0..width {
+                let [r, g, b, a] = pixels[(band + row) * width + x];
+                if a < 128 {
+                    continue;
+                }
+
+                let color = (level(r) * SIXEL_LEVELS * SIXEL_LEVELS
+                    + level(g) * SIXEL_LEVELS
+                    + level(b)) as usize;
+                bands[color][x] |= 1 << row;
+            }
+        }
+
+        let mut first = true;
+        for (color, bits) in bands.iter().enumerate() {
+            if bits.iter().all(|bits| *bits == 0) {
+                continue;
+            }
+            // go back to the start of the band for every color
+            if !first {
+                out.push('$');
+            }
+            first = false;
+
+            let _ = write!(out, "#{color}");
+            run_length_encode(bits, &mut out);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+
+    out
+}
+
+/// sixel characters are `?` plus the bits, repeats are written as `!<count><char>`
+fn run_length_encode(bits: &[u8], out: &mut String) {
+    let mut i = 0;
+    while i < bits.len() {
+        let count = bits[i..].iter().take_while(|bit| **bit == bits[i]).count();
+        let c = char::from(b'?' + bits[i]);
+        if count > 3 {
+            let _ = write!(out, "!{count}{c}");
+        } else {
+            out.extend(std::iter::repeat_n(c, count));
+        }
+
+        i += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_sixel() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let clear = [0, 0, 0, 0];
+        let pixels = [
+            red, red, red, red, blue, clear, red, red, red, red, blue, clear,
+        ];
+
+        let sixel = sixel(&pixels, 6, 2);
+        assert!(sixel.starts_with("\x1bPq\"1;1;6;2#0;2;0;0;0"));
+        // blue is color 5 and red is color 5 * 36, the transparent column is left undrawn
+        assert!(sixel.ends_with("#5!4?B?$#180!4B??-\x1b\\"), "{sixel:?}");
+    }
+
+    #[test]
+    fn shrink_wide_image() {
+        let pixels = (0..8u8).map(|i| [i, 0, 0, 255]).collect::<Vec<_>>();
+
+        let (pixels, width, height) = shrink(&pixels, 4, 2, 2);
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(pixels, vec![[0, 0, 0, 255], [2, 0, 0, 255]]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::cancel::CancelToken;
+
+/// exit code when interrupted by ctrl-c, same as shells
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// cancelled by the first ctrl-c, the parent of the tokens of all tasks
+static SHUTDOWN: OnceLock<CancelToken> = OnceLock::new();
+/// running cancellable tasks, ctrl-c exits at once if there is none
+static CANCELLABLE: AtomicUsize = AtomicUsize::new(0);
+/// the running abortable request, ctrl-c cancels it and nothing else is interrupted
+static ABORT: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+/// the task is cancelled by ctrl-c
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Interrupted;
+
+impl Display for Interrupted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("interrupted")
+    }
+}
+
+impl Error for Interrupted {}
+
+/// decrease the running cancellable tasks when dropped
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        CANCELLABLE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// forget the abortable request when it's done or dropped
+struct Abortable;
+
+impl Drop for Abortable {
+    fn drop(&mut self) {
+        *ABORT.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+}
+
+/// the token cancelled by ctrl-c, the tokens of tasks are its children, so a task can be
+/// cancelled alone or expire without stopping the others
+pub fn shutdown() -> &'static CancelToken {
+    SHUTDOWN.get_or_init(CancelToken::new)
+}
+
+/// called by the ctrl-c handler of the application, an abortable request is aborted alone,
+/// otherwise the first one cancels the running cancellable tasks so they can print the results
+/// already obtained. false for the second one, or one without such tasks, then the application
+/// should exit with INTERRUPTED_EXIT_CODE
+pub fn interrupt() -> bool {
+    abort() || (CANCELLABLE.load(Ordering::SeqCst) > 0 && shutdown().cancel())
+}
+
+/// abort the running abortable request, false if there is none
+fn abort() -> bool {
+    let token = ABORT.lock().unwrap_or_else(|err| err.into_inner()).take();
+
+    token.is_some_and(|token| token.cancel())
+}
+
+/// run a single request of the interactive mode until it's done or ctrl-c is pressed, unlike
+/// cancellable, later requests aren't affected, the future is dropped when aborted, which releases
+/// its rate limit permit
+pub async fn abortable<F: Future>(future: F) -> Result<F::Output, Interrupted> {
+    let token = shutdown().child();
+    *ABORT.lock().unwrap_or_else(|err| err.into_inner()) = Some(token.clone());
+    let _abortable = Abortable;
+
+    token.run(future).await.map_err(|_| Interrupted)
+}
+
+/// run the future until it's done or ctrl-c is pressed, the future is dropped when interrupted,
+/// which cancels its in-flight requests
+pub async fn cancellable<F: Future>(future: F) -> Result<F::Output, Interrupted> {
+    shutdown().check().map_err(|_| Interrupted)?;
+
+    CANCELLABLE.fetch_add(1, Ordering::SeqCst);
+    let _running = Running;
+
+    shutdown().run(future).await.map_err(|_| Interrupted)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future;
+
+    use super::*;
+
+    #[async_std::test]
+    async fn abort_request() {
+        let (aborted, _) = future::join(abortable(future::pending::<()>()), async {
+            assert!(abort());
+        })
+        .await;
+        assert_eq!(aborted, Err(Interrupted));
+        // the next request isn't affected
+        assert_eq!(abortable(future::ready(1)).await, Ok(1));
+        assert!(!abort());
+    }
+}
@@ -0,0 +1,223 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+
+/// source of the fallback auto target rule
+const ANY_SOURCE: &str = "*";
+
+/// languages supported by tmt, parsed from the language name or code
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Language {
+    Chinese,
+    TraditionalChinese,
+    English,
+    Japanese,
+    Korean,
+    French,
+    Spanish,
+    Italian,
+    German,
+    Turkish,
+    Russian,
+    Portuguese,
+    Vietnamese,
+    Indonesian,
+    Thai,
+    Malay,
+    Arabic,
+    Hindi,
+}
+
+impl Language {
+    pub const ALL: [Language; 18] = [
+        Language::Chinese,
+        Language::TraditionalChinese,
+        Language::English,
+        Language::Japanese,
+        Language::Korean,
+        Language::French,
+        Language::Spanish,
+        Language::Italian,
+        Language::German,
+        Language::Turkish,
+        Language::Russian,
+        Language::Portuguese,
+        Language::Vietnamese,
+        Language::Indonesian,
+        Language::Thai,
+        Language::Malay,
+        Language::Arabic,
+        Language::Hindi,
+    ];
+
+    /// parse from language code or language name
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| {
+            language.name().eq_ignore_ascii_case(code)
+                || language
+                    .aliases()
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(code))
+        })
+    }
+
+    /// the language name, like `traditional-chinese`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Chinese => "chinese",
+            Language::TraditionalChinese => "traditional-chinese",
+            Language::English => "english",
+            Language::Japanese => "japanese",
+            Language::Korean => "korean",
+            Language::French => "french",
+            Language::Spanish => "spanish",
+            Language::Italian => "italian",
+            Language::German => "german",
+            Language::Turkish => "turkish",
+            Language::Russian => "russian",
+            Language::Portuguese => "portuguese",
+            Language::Vietnamese => "vietnamese",
+            Language::Indonesian => "indonesian",
+            Language::Thai => "thai",
+            Language::Malay => "malay",
+            Language::Arabic => "arabic",
+            Language::Hindi => "hindi",
+        }
+    }
+
+    /// the language codes accepted besides the name
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Language::Chinese => &["zh"],
+            Language::TraditionalChinese => &["zh-TW", "zh-tw"],
+            Language::English => &["en"],
+            Language::Japanese => &["jp", "ja"],
+            Language::Korean => &["ko"],
+            Language::French => &["fr"],
+            Language::Spanish => &["es"],
+            Language::Italian => &["it"],
+            Language::German => &["de"],
+            Language::Turkish => &["tr"],
+            Language::Russian => &["ru"],
+            Language::Portuguese => &["pt"],
+            Language::Vietnamese => &["vi"],
+            Language::Indonesian => &["id"],
+            Language::Thai => &["th"],
+            Language::Malay => &["ms"],
+            Language::Arabic => &["ar"],
+            Language::Hindi => &["hi"],
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Chinese => "zh",
+            Language::TraditionalChinese => "zh-TW",
+            Language::English => "en",
+            Language::Japanese => "jp",
+            Language::Korean => "ko",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::Italian => "it",
+            Language::German => "de",
+            Language::Turkish => "tr",
+            Language::Russian => "ru",
+            Language::Portuguese => "pt",
+            Language::Vietnamese => "vi",
+            Language::Indonesian => "id",
+            Language::Thai => "th",
+            Language::Malay => "ms",
+            Language::Arabic => "ar",
+            Language::Hindi => "hi",
+        }
+    }
+}
+
+impl AsRef<str> for Language {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::ValueEnum for Language {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()).aliases(self.aliases().iter().copied()))
+    }
+}
+
+/// target language used when the source language is detected as source, like `ja=zh`, the
+/// source `*` matches the other languages
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AutoTarget {
+    /// none is any language
+    source: Option<Language>,
+    target: Language,
+}
+
+impl AutoTarget {
+    pub fn new(source: &str, target: &str) -> anyhow::Result<Self> {
+        let source = match source.trim() {
+            ANY_SOURCE => None,
+            source => Some(
+                Language::from_code(source)
+                    .with_context(|| format!("unknown language {source}"))?,
+            ),
+        };
+        let target = Language::from_code(target.trim())
+            .with_context(|| format!("unknown language {target}"))?;
+
+        Ok(Self { source, target })
+    }
+}
+
+impl FromStr for AutoTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, target) = s
+            .split_once('=')
+            .with_context(|| format!("auto target {s} should be like ja=zh"))?;
+
+        Self::new(source, target)
+    }
+}
+
+/// find the target language of the detected source language, the exact rule wins over `*`, and
+/// `*` never translates a language into itself
+pub fn auto_target(rules: &[AutoTarget], source: &str) -> Option<Language> {
+    let source = Language::from_code(source);
+
+    rules
+        .iter()
+        .find(|rule| rule.source.is_some() && rule.source == source)
+        .or_else(|| {
+            rules
+                .iter()
+                .find(|rule| rule.source.is_none() && Some(rule.target) != source)
+        })
+        .map(|rule| rule.target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_rule_before_fallback() {
+        let rules = ["*=ja", "ko=en"]
+            .into_iter()
+            .map(|rule| rule.parse::<AutoTarget>().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(auto_target(&rules, "ko"), Some(Language::English));
+        assert_eq!(auto_target(&rules, "fr"), Some(Language::Japanese));
+        assert_eq!(auto_target(&rules, "jp"), None);
+        assert!("ko".parse::<AutoTarget>().is_err());
+        assert!("ko=xx".parse::<AutoTarget>().is_err());
+    }
+}
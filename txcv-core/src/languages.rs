@@ -0,0 +1,159 @@
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::backend::{Capabilities, TMT_CAPABILITIES};
+use crate::lang::Language;
+use crate::translate::Provider;
+use crate::{deepl, dict};
+
+const PROVIDERS: &[&Capabilities] = &[&TMT_CAPABILITIES, &deepl::CAPABILITIES, &dict::CAPABILITIES];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum LanguagesFormat {
+    /// a line of the code, the name and the providers of every language
+    #[default]
+    Plain,
+    /// the languages, the capabilities of the providers and the supported pairs
+    Json,
+}
+
+impl Display for LanguagesFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LanguagesFormat::Plain => f.write_str("plain"),
+            LanguagesFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+/// the pair support of all providers, for the language pickers of editor plugins and the browser
+/// extension
+#[derive(Debug, Serialize)]
+pub struct Matrix {
+    pub languages: Vec<LanguageRecord>,
+    pub providers: &'static [&'static Capabilities],
+    pub pairs: Vec<PairRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageRecord {
+    pub code: &'static str,
+    /// the name accepted by `--source` and `--target`
+    pub name: String,
+    /// the providers which translate from and to the language
+    pub providers: Vec<Provider>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairRecord {
+    pub source: &'static str,
+    pub target: &'static str,
+    pub providers: Vec<Provider>,
+}
+
+/// the providers which support both languages
+fn providers(source: &str, target: &str) -> Vec<Provider> {
+    PROVIDERS
+        .iter()
+        .filter(|capabilities| {
+            capabilities.languages.contains(&source) && capabilities.languages.contains(&target)
+        })
+        .map(|capabilities| capabilities.provider)
+        .collect()
+}
+
+pub fn matrix() -> Matrix {
+    let codes = Language::ALL
+        .iter()
+        .map(Language::as_str)
+        .collect::<Vec<_>>();
+
+    let languages = Language::ALL
+        .iter()
+        .map(|language| LanguageRecord {
+            code: language.as_str(),
+            name: language.name().to_string(),
+            providers: providers(language.as_str(), language.as_str()),
+        })
+        .collect();
+
+    let pairs = codes
+        .iter()
+        .flat_map(|&source| {
+            codes
+                .iter()
+                .filter(move |&&target| target != source)
+                .map(move |&target| PairRecord {
+                    source,
+                    target,
+                    providers: providers(source, target),
+                })
+        })
+        .filter(|pair| !pair.providers.is_empty())
+        .collect();
+
+    Matrix {
+        languages,
+        providers: PROVIDERS,
+        pairs,
+    }
+}
+
+pub fn print<W: Write>(format: LanguagesFormat, mut writer: W) -> anyhow::Result<()> {
+    let matrix = matrix();
+
+    match format {
+        LanguagesFormat::Plain => {
+            for language in &matrix.languages {
+                let providers = language
+                    .providers
+                    .iter()
+                    .map(Provider::to_string)
+                    .collect::<Vec<_>>();
+
+                writeln!(
+                    writer,
+                    "{:<6} {:<20} {}",
+                    language.code,
+                    language.name,
+                    providers.join(" ")
+                )?;
+            }
+        }
+
+        LanguagesFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, &matrix)?;
+            writeln!(writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pair_matrix() {
+        let matrix = matrix();
+
+        let pair = |source, target| {
+            matrix
+                .pairs
+                .iter()
+                .find(|pair| pair.source == source && pair.target == target)
+                .map(|pair| pair.providers.clone())
+        };
+        assert_eq!(
+            pair("en", "zh"),
+            Some(vec![Provider::Tmt, Provider::Deepl, Provider::Dict])
+        );
+        assert_eq!(pair("vi", "th"), Some(vec![Provider::Tmt]));
+        assert_eq!(pair("zh", "zh"), None);
+        assert_eq!(matrix.languages[0].name, "chinese");
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+
+const CORRECTIONS_FILE: &str = "corrections.json";
+/// suggest a glossary entry when the same correction is made this many times
+pub const SUGGEST_AFTER: u32 = 3;
+/// longer changes are rewrites, not term corrections
+const MAX_TERM_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Correction {
+    from: String,
+    to: String,
+    count: u32,
+}
+
+/// how many times the machine translated terms are corrected by user
+#[derive(Debug)]
+pub struct Corrections {
+    path: PathBuf,
+    corrections: Vec<Correction>,
+}
+
+impl Corrections {
+    pub fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(CORRECTIONS_FILE);
+
+        let corrections = load(&path)?;
+
+        Ok(Self { path, corrections })
+    }
+
+    /// count the correction and save, return how many times it has been made, the corrections
+    /// saved by other txcv since opening are kept
+    pub fn record(&mut self, from: &str, to: &str) -> anyhow::Result<u32> {
+        let _lock = atomic::lock(&self.path)?;
+        self.corrections = load(&self.path)?;
+
+        let count = match self
+            .corrections
+            .iter_mut()
+            .find(|correction| correction.from == from && correction.to == to)
+        {
+            None => {
+                self.corrections.push(Correction {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    count: 1,
+                });
+
+                1
+            }
+
+            Some(correction) => {
+                correction.count += 1;
+
+                correction.count
+            }
+        };
+
+        atomic::write(&self.path, &serde_json::to_vec(&self.corrections)?)
+            .with_context(|| format!("write {} failed", self.path.display()))?;
+
+        Ok(count)
+    }
+}
+
+fn load(path: &Path) -> anyhow::Result<Vec<Correction>> {
+    match fs::read(path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+        Ok(data) => serde_json::from_slice(&data)
+            .with_context(|| format!("parse corrections {} failed", path.display())),
+    }
+}
+
+/// find the term changed from the machine translation to the edited one, the change is extended
+/// to whole words for languages separated by spaces, return None when nothing is changed or the
+/// change is too long to be a term
+pub fn changed_term<'a>(machine: &'a str, edited: &'a str) -> Option<(&'a str, &'a str)> {
+    let mut start = machine
+        .chars()
+        .zip(edited.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum::<usize>();
+    let max_suffix = machine.len().min(edited.len()) - start;
+    let mut suffix = machine
+        .chars()
+        .rev()
+        .zip(edited.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .scan(0, |len, char_len| {
+            *len += char_len;
+            (*len <= max_suffix).then_some(*len)
+        })
+        .last()
+        .unwrap_or(0);
+
+    // the prefix and suffix are shared, so extending them is the same for both texts
+    while let Some(c) = machine[..start].chars().next_back() {
+        if !c.is_ascii_alphanumeric() {
+            break;
+        }
+        start -= c.len_utf8();
+    }
+    while let Some(c) = machine[machine.len() - suffix..].chars().next() {
+        if !c.is_ascii_alphanumeric() {
+            break;
+        }
+        suffix -= c.len_utf8();
+    }
+
+    let from = machine[start..machine.len() - suffix].trim();
+    let to = edited[start..edited.len() - suffix].trim();
+    if from.is_empty()
+        || to.is_empty()
+        || from == to
+        || from.chars().count() > MAX_TERM_CHARS
+        || to.chars().count() > MAX_TERM_CHARS
+    {
+        return None;
+    }
+
+    Some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_word() {
+        assert_eq!(
+            changed_term("open the colour picker", "open the color picker"),
+            Some(("colour", "color"))
+        );
+        assert_eq!(changed_term("点击按纽", "点击按钮"), Some(("纽", "钮")));
+    }
+
+    #[test]
+    fn unchanged_or_rewritten() {
+        assert_eq!(changed_term("same", "same"), None);
+        assert_eq!(changed_term("a b", "a  b"), None);
+        assert_eq!(
+            changed_term(
+                "this sentence is translated by machine",
+                "a totally different sentence written by a human translator"
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! the library of txcv: the translation providers, the document formats, the cache and the rate limit, driven by the `txcv` command line in txcv-cli
+
+pub use self::error::Error;
+
+pub mod api;
+pub mod atomic;
+pub mod backend;
+pub mod bench;
+pub mod cache;
+pub mod cancel;
+pub mod card;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod color;
+pub mod config;
+pub mod corpus;
+pub mod credential;
+pub mod daemon;
+pub mod deepl;
+pub mod diagnostic;
+pub mod dict;
+pub mod diff;
+// the local guess is also used by the dry run
+#[cfg_attr(not(feature = "interactive"), allow(dead_code))]
+pub mod disambiguate;
+pub mod document;
+pub mod dry_run;
+#[cfg(feature = "interactive")]
+pub mod editor;
+pub mod encoding;
+pub mod error;
+pub mod exec;
+pub mod explain;
+#[cfg(feature = "file-store")]
+pub mod file_store;
+pub mod filter;
+pub mod glossary;
+pub mod history;
+pub mod inline_image;
+pub mod interrupt;
+pub mod lang;
+pub mod languages;
+#[cfg(feature = "interactive")]
+pub mod learn;
+pub mod memory;
+pub mod normalize;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod output;
+#[cfg(feature = "tui")]
+pub mod pager;
+#[cfg(feature = "wasm-plugin")]
+pub mod plugin;
+pub mod plural;
+pub mod protect;
+pub mod qa;
+pub mod rate_limit;
+#[cfg(feature = "interactive")]
+pub mod repl;
+pub mod resource;
+pub mod retry;
+#[cfg(feature = "interactive")]
+pub mod review;
+pub mod segment;
+pub mod slang;
+pub mod status;
+pub mod substitute;
+#[cfg(feature = "tui")]
+pub mod terminal;
+pub mod translate;
+pub mod usage;
+#[cfg(feature = "clipboard")]
+pub mod watch;
+pub mod wav;
+pub mod whitespace;
+
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing {
+    pub use crate::document::{join, split, Block, Directives, Format};
+    pub use crate::protect::{emoji_spans, protect, restore};
+    pub use crate::whitespace::{restore as restore_whitespace, strip as strip_whitespace};
+}
@@ -0,0 +1,186 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+
+const MEMORY_FILE: &str = "memory.json";
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum MemoryMode {
+    #[default]
+    Off,
+    Offer,
+    Force,
+}
+
+impl Display for MemoryMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryMode::Off => f.write_str("off"),
+            MemoryMode::Offer => f.write_str("offer"),
+            MemoryMode::Force => f.write_str("force"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub source_lang: String,
+    pub target_lang: String,
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Match<'a> {
+    pub segment: &'a Segment,
+    /// similarity in percent, 100 means exact match
+    pub similarity: u8,
+}
+
+#[derive(Debug)]
+pub struct TranslationMemory {
+    path: PathBuf,
+    segments: Vec<Segment>,
+}
+
+impl TranslationMemory {
+    pub fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(MEMORY_FILE);
+
+        let segments = load(&path)?;
+
+        Ok(Self { path, segments })
+    }
+
+    /// find the most similar segment whose similarity is not less than threshold percent, the
+    /// segments of other languages are skipped when the languages are known
+    pub fn lookup(
+        &self,
+        text: &str,
+        source_lang: Option<&str>,
+        target_lang: Option<&str>,
+        threshold: u8,
+    ) -> Option<Match<'_>> {
+        self.segments
+            .iter()
+            .filter(|segment| source_lang.is_none_or(|lang| segment.source_lang == lang))
+            .filter(|segment| target_lang.is_none_or(|lang| segment.target_lang == lang))
+            .map(|segment| Match {
+                segment,
+                similarity: similarity(text, &segment.source),
+            })
+            .filter(|m| m.similarity >= threshold)
+            .max_by_key(|m| m.similarity)
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// the segments saved by other txcv since opening are kept
+    pub fn insert(&mut self, segment: Segment) -> anyhow::Result<()> {
+        let _lock = atomic::lock(&self.path)?;
+        self.segments = load(&self.path)?;
+
+        match self.segments.iter_mut().find(|exist| {
+            exist.source == segment.source
+                && exist.source_lang == segment.source_lang
+                && exist.target_lang == segment.target_lang
+        }) {
+            None => self.segments.push(segment),
+            Some(exist) => exist.target = segment.target,
+        }
+
+        self.save()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        atomic::write(&self.path, &serde_json::to_vec(&self.segments)?)
+            .with_context(|| format!("write translation memory {} failed", self.path.display()))
+    }
+}
+
+fn load(path: &Path) -> anyhow::Result<Vec<Segment>> {
+    match fs::read(path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+        Ok(data) => serde_json::from_slice(&data)
+            .with_context(|| format!("parse translation memory {} failed", path.display())),
+    }
+}
+
+/// levenshtein based similarity in percent
+fn similarity(a: &str, b: &str) -> u8 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 100;
+    }
+
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    let distance = prev[b.len()];
+    // only the same texts are 100% similar
+    if distance == 0 {
+        return 100;
+    }
+
+    (100 - (distance * 100).div_ceil(max_len)) as _
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_match_is_not_exact() {
+        let text = "the quick brown fox jumps over the lazy dog ".repeat(5);
+        let near = text.replacen("fox", "box", 1);
+
+        assert_eq!(similarity(&text, &text), 100);
+        assert_eq!(similarity(&text, &near), 99);
+        assert_eq!(similarity("", ""), 100);
+        assert_eq!(similarity("abcd", "abce"), 75);
+    }
+
+    #[test]
+    fn lookup_by_languages() {
+        let segment = |source_lang: &str, target: &str| Segment {
+            source_lang: source_lang.to_string(),
+            target_lang: "en".to_string(),
+            source: "本".to_string(),
+            target: target.to_string(),
+        };
+        let memory = TranslationMemory {
+            path: PathBuf::new(),
+            segments: vec![segment("ja", "book"), segment("zh", "root")],
+        };
+
+        let matched = memory.lookup("本", Some("zh"), Some("en"), 100).unwrap();
+        assert_eq!(matched.segment.target, "root");
+        assert!(memory.lookup("本", Some("ko"), Some("en"), 100).is_none());
+        assert!(memory.lookup("本", None, Some("zh"), 100).is_none());
+        assert!(memory.lookup("本", None, None, 100).is_some());
+    }
+}
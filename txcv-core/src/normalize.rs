@@ -0,0 +1,112 @@
+use std::fmt::{Display, Formatter};
+
+use unicode_normalization::UnicodeNormalization;
+
+/// punctuation whose repetition is collapsed, like `!!!` to `!`
+const COLLAPSED_PUNCTUATION: &[char] = &['!', '?', ',', ';', '！', '？', '，', '；', '。'];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Normalization {
+    /// full-width punctuation to half-width
+    Fullwidth,
+    /// smart quotes to straight quotes
+    Quotes,
+    /// unicode nfc
+    Nfc,
+    /// collapse repeated punctuation
+    Punctuation,
+    /// collapse runs of spaces and tabs, and trim the spaces at the end of lines
+    Whitespace,
+}
+
+impl Display for Normalization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Normalization::Fullwidth => f.write_str("fullwidth"),
+            Normalization::Quotes => f.write_str("quotes"),
+            Normalization::Nfc => f.write_str("nfc"),
+            Normalization::Punctuation => f.write_str("punctuation"),
+            Normalization::Whitespace => f.write_str("whitespace"),
+        }
+    }
+}
+
+/// normalize source text, the normalizations are applied in order
+pub fn normalize(text: &str, normalizations: &[Normalization]) -> String {
+    normalizations.iter().fold(
+        text.to_string(),
+        |text, normalization| match normalization {
+            Normalization::Fullwidth => text.chars().map(fullwidth_to_halfwidth).collect(),
+            Normalization::Quotes => text.chars().map(straight_quote).collect(),
+            Normalization::Nfc => text.nfc().collect(),
+            Normalization::Punctuation => collapse_punctuation(&text),
+            Normalization::Whitespace => collapse_whitespace(&text),
+        },
+    )
+}
+
+fn fullwidth_to_halfwidth(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{ff01}'..='\u{ff5e}' => {
+            let half = char::from_u32(c as u32 - 0xfee0).unwrap_or(c);
+            if half.is_ascii_punctuation() {
+                half
+            } else {
+                c
+            }
+        }
+        _ => c,
+    }
+}
+
+fn straight_quote(c: char) -> char {
+    match c {
+        '‘' | '’' | '‚' | '‛' => '\'',
+        '“' | '”' | '„' | '‟' => '"',
+        _ => c,
+    }
+}
+
+fn collapse_punctuation(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = None;
+    for c in text.chars() {
+        if last == Some(c) && COLLAPSED_PUNCTUATION.contains(&c) {
+            continue;
+        }
+
+        result.push(c);
+        last = Some(c);
+    }
+
+    result
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        // the indentation is kept
+        let indent = content.len() - content.trim_start_matches([' ', '\t']).len();
+        result.push_str(&content[..indent]);
+
+        let mut blank = false;
+        for c in content[indent..].trim_end_matches([' ', '\t']).chars() {
+            if matches!(c, ' ' | '\t') {
+                blank = true;
+                continue;
+            }
+            if blank {
+                result.push(' ');
+                blank = false;
+            }
+
+            result.push(c);
+        }
+        result.push_str(&line[content.len()..]);
+    }
+
+    result
+}
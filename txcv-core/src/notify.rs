@@ -0,0 +1,51 @@
+use notify_rust::Notification;
+
+/// notifications of unregistered apps are shown under the app id of powershell on windows, or
+/// they are dropped silently
+#[cfg(windows)]
+const POWERSHELL_APP_ID: &str =
+    "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+/// txcv has no app bundle on macos, the notifications are sent as the terminal
+#[cfg(target_os = "macos")]
+const TERMINAL_BUNDLE_ID: &str = "com.apple.Terminal";
+
+/// show the translation as a desktop notification, a toast on windows, the notification center
+/// on macos, or by the notification server of freedesktop elsewhere
+pub fn show(source: &str, translated: &str) -> anyhow::Result<()> {
+    let mut notification = Notification::new();
+    notification.summary(source).body(translated);
+    platform(&mut notification);
+
+    notification
+        .show()
+        .map_err(|err| anyhow::anyhow!("show notification failed: {err}"))?;
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn platform(notification: &mut Notification) {
+    notification.app_id(POWERSHELL_APP_ID);
+}
+
+#[cfg(target_os = "macos")]
+fn platform(_notification: &mut Notification) {
+    // the application can only be set once, later calls fail
+    let _ = notify_rust::set_application(TERMINAL_BUNDLE_ID);
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform(notification: &mut Notification) {
+    notification.appname("txcv");
+}
+
+/// needs a desktop session, run by `cargo test --features notify,desktop-tests`
+#[cfg(all(test, feature = "desktop-tests"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn show_notification() {
+        show("hello", "你好").unwrap();
+    }
+}
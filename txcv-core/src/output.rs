@@ -0,0 +1,184 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::translate::Provider;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Output {
+    /// print translations, write translated files
+    #[default]
+    Plain,
+    /// print json lines of source text, source language, target language and translated text
+    Json,
+    /// print tab separated source text, source language, target language and translated text
+    Tsv,
+    /// print unified diffs which transform source files into translated files, files are not
+    /// written
+    Diff,
+}
+
+impl Display for Output {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Output::Plain => f.write_str("plain"),
+            Output::Json => f.write_str("json"),
+            Output::Tsv => f.write_str("tsv"),
+            Output::Diff => f.write_str("diff"),
+        }
+    }
+}
+
+/// a translated text, the source language is detected one if not specified
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub source: &'a str,
+    pub source_lang: &'a str,
+    pub target_lang: &'a str,
+    pub target: &'a str,
+}
+
+/// where a text line is recognized in an image, in pixels from the top left corner
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct BoundingBox {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+/// a text line translated from an image, the json output has its number and bounding box, so
+/// overlay tools can draw the translation on top of the image
+#[derive(Debug, Serialize)]
+pub struct RegionRecord<'a> {
+    /// counted from 1, same as the numbers printed by `--show-image`
+    pub region: usize,
+    #[serde(flatten)]
+    pub record: Record<'a>,
+    pub bbox: BoundingBox,
+}
+
+/// counts of a batch run, printed after the results by `--summary`
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// translated by the backend
+    pub translated: usize,
+    /// reused from the translation memory or the cache
+    pub cached: usize,
+    pub failed: usize,
+    /// characters sent to the backend
+    pub chars: usize,
+}
+
+impl Summary {
+    /// count a translated text by where it comes from
+    pub fn count(&mut self, source: &str, provider: Provider) {
+        match provider {
+            Provider::Memory | Provider::Cache => self.cached += 1,
+            Provider::Untranslated => {}
+            _ => {
+                self.translated += 1;
+                self.chars += source.chars().count();
+            }
+        }
+    }
+
+    pub fn line(&self, elapsed: Duration) -> String {
+        format!(
+            "{} translated, {} cached, {} failed, {} characters, {:.2}s",
+            self.translated,
+            self.cached,
+            self.failed,
+            self.chars,
+            elapsed.as_secs_f64()
+        )
+    }
+}
+
+impl Output {
+    /// format the record as a line, none for the human readable outputs
+    pub fn format(self, record: &Record) -> Option<String> {
+        match self {
+            Output::Plain | Output::Diff => None,
+            Output::Json => Some(serde_json::to_string(record).expect("record is serializable")),
+            Output::Tsv => Some(
+                [
+                    record.source,
+                    record.source_lang,
+                    record.target_lang,
+                    record.target,
+                ]
+                .map(escape_tsv)
+                .join("\t"),
+            ),
+        }
+    }
+}
+
+/// escape the tab and newlines, so a record is kept in one line
+pub fn escape_tsv(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// the record separator of `--record-sep`, `\0`, `\n`, `\t`, `\r`, `\\` and `\xHH` are unescaped
+pub fn record_separator(sep: &str) -> anyhow::Result<String> {
+    let mut unescaped = String::new();
+    let mut chars = sep.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+
+            continue;
+        }
+
+        match chars.next() {
+            Some('0') => unescaped.push('\0'),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some('x') => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|byte| byte.is_ascii() && hex.len() == 2)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("invalid escape \\x{hex} of the record separator")
+                    })?;
+
+                unescaped.push(byte as char);
+            }
+            Some(c) => {
+                return Err(anyhow::anyhow!(
+                    "invalid escape \\{c} of the record separator"
+                ))
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "the record separator ends with a backslash"
+                ))
+            }
+        }
+    }
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_record_separator() {
+        assert_eq!(record_separator("\\0").unwrap(), "\0");
+        assert_eq!(record_separator("\\x1e").unwrap(), "\x1e");
+        assert_eq!(record_separator("---\\n").unwrap(), "---\n");
+        assert!(record_separator("\\q").is_err());
+        assert!(record_separator("\\xff").is_err());
+    }
+}
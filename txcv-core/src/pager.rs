@@ -0,0 +1,122 @@
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use crate::terminal::TerminalGuard;
+
+/// lines shown before paging
+const SUMMARY_LINES: usize = 3;
+/// the status line at the bottom of the page
+const STATUS_ROWS: usize = 1;
+
+/// whether the lines can't fit in the terminal
+pub fn needs_paging(lines: usize) -> bool {
+    terminal::size().is_ok_and(|(_, rows)| lines + STATUS_ROWS > rows as usize)
+}
+
+/// print the title and a compact summary of the lines, then page all lines in the alternate
+/// screen if user presses space
+pub fn page(title: &str, lines: &[&str]) -> anyhow::Result<()> {
+    println!("{}", title.blue());
+    for line in lines.iter().take(SUMMARY_LINES) {
+        println!("{}", line.green());
+    }
+    println!(
+        "{}",
+        format!(
+            "... {} more lines, press space to browse, any other key to skip",
+            lines.len().saturating_sub(SUMMARY_LINES)
+        )
+        .dim()
+    );
+
+    let _guard = TerminalGuard;
+    terminal::enable_raw_mode()?;
+    if read_key()?.code != KeyCode::Char(' ') {
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let mut top = 0;
+    loop {
+        let (columns, rows) = terminal::size()?;
+        let height = (rows as usize).saturating_sub(STATUS_ROWS).max(1);
+        top = top.min(lines.len().saturating_sub(height));
+
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+        for (row, line) in lines.iter().skip(top).take(height).enumerate() {
+            // long lines are cut, wrapping would push the status line out
+            let line = line.chars().take(columns as usize).collect::<String>();
+            queue!(stdout, cursor::MoveTo(0, row as u16), style::Print(line))?;
+        }
+        let status = format!(
+            "{}-{}/{}  j/k scroll, space/b page, q quit",
+            top + 1,
+            (top + height).min(lines.len()),
+            lines.len()
+        );
+        queue!(
+            stdout,
+            cursor::MoveTo(0, height as u16),
+            style::PrintStyledContent(status.reverse())
+        )?;
+        stdout.flush()?;
+
+        match scroll(top, read_key()?, height, lines.len()) {
+            None => return Ok(()),
+            Some(new_top) => top = new_top,
+        }
+    }
+}
+
+fn read_key() -> anyhow::Result<KeyEvent> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Release {
+                return Ok(key);
+            }
+        }
+    }
+}
+
+/// the new top line after the key is pressed, none when quit
+fn scroll(top: usize, key: KeyEvent, height: usize, total: usize) -> Option<usize> {
+    let last_top = total.saturating_sub(height);
+
+    let top = match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return None,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return None,
+        KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => top + 1,
+        KeyCode::Char('k') | KeyCode::Up => top.saturating_sub(1),
+        KeyCode::Char(' ') | KeyCode::PageDown => top + height,
+        KeyCode::Char('b') | KeyCode::PageUp => top.saturating_sub(height),
+        KeyCode::Char('g') | KeyCode::Home => 0,
+        KeyCode::Char('G') | KeyCode::End => last_top,
+        _ => top,
+    };
+
+    Some(top.min(last_top))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn scroll_within_lines() {
+        assert_eq!(scroll(0, key(KeyCode::Char('j')), 10, 25), Some(1));
+        assert_eq!(scroll(0, key(KeyCode::Char('k')), 10, 25), Some(0));
+        assert_eq!(scroll(10, key(KeyCode::Char(' ')), 10, 25), Some(15));
+        assert_eq!(scroll(15, key(KeyCode::Char('b')), 10, 25), Some(5));
+        assert_eq!(scroll(3, key(KeyCode::Char('G')), 10, 25), Some(15));
+        assert_eq!(scroll(3, key(KeyCode::Char('q')), 10, 25), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! sandboxed wasm plugins
+//!
+//! the plugin can't import any host function, so it can't access the file system, network or
+//! credentials. it must export `memory`, `txcv_alloc(len: i32) -> i32`, and optional
+//! `txcv_pre(ptr: i32, len: i32) -> i64` which filters the source text before translating and
+//! `txcv_post(ptr: i32, len: i32) -> i64` which filters the translated text, the returned i64 is
+//! `ptr << 32 | len` of the utf-8 result in the plugin memory
+//!
+//! a call is limited by fuel and the memory is limited to 64 MiB. the plugins only filter the
+//! text, the file formats are parsed by txcv itself and can't be added by a plugin
+
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use wasmtime::{
+    Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+/// limit the instructions a plugin call can execute
+const FUEL_PER_CALL: u64 = 100_000_000;
+/// limit the memory of a plugin, `memory.grow` beyond it fails
+const MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+pub struct WasmPlugin {
+    path: PathBuf,
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    pre: Option<TypedFunc<(i32, i32), i64>>,
+    post: Option<TypedFunc<(i32, i32), i64>>,
+}
+
+impl Debug for WasmPlugin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmPlugin {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, path)
+            .with_context(|| format!("load wasm plugin {} failed", path.display()))?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&engine, limits);
+        store.limiter(|limits| limits);
+        // no imports are provided, a plugin which needs host functions is rejected
+        let instance = Instance::new(&mut store, &module, &[]).with_context(|| {
+            format!(
+                "instantiate wasm plugin {} failed, plugin can't import host functions",
+                path.display()
+            )
+        })?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .with_context(|| format!("wasm plugin {} doesn't export memory", path.display()))?;
+        let alloc = instance.get_typed_func(&mut store, "txcv_alloc")?;
+        let pre = instance.get_typed_func(&mut store, "txcv_pre").ok();
+        let post = instance.get_typed_func(&mut store, "txcv_post").ok();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            store,
+            memory,
+            alloc,
+            pre,
+            post,
+        })
+    }
+
+    pub fn pre(&mut self, text: &str) -> anyhow::Result<String> {
+        match self.pre.clone() {
+            None => Ok(text.to_string()),
+            Some(pre) => self.call(pre, text),
+        }
+    }
+
+    pub fn post(&mut self, text: &str) -> anyhow::Result<String> {
+        match self.post.clone() {
+            None => Ok(text.to_string()),
+            Some(post) => self.call(post, text),
+        }
+    }
+
+    fn call(&mut self, func: TypedFunc<(i32, i32), i64>, text: &str) -> anyhow::Result<String> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let len = i32::try_from(text.len())?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as _, text.as_bytes())?;
+
+        let result = func.call(&mut self.store, (ptr, len))?;
+        let (result_ptr, result_len) = ((result >> 32) as u32, result as u32);
+        let mut buf = vec![0; result_len as _];
+        self.memory.read(&self.store, result_ptr as _, &mut buf)?;
+
+        String::from_utf8(buf)
+            .with_context(|| format!("wasm plugin {} returns invalid utf-8", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `txcv_pre` returns the text unchanged, `txcv_post` returns the text without its first byte
+    const PLUGIN: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "txcv_alloc") (param i32) (result i32) i32.const 16)
+            (func (export "txcv_pre") (param i32 i32) (result i64)
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32))
+                    (i64.extend_i32_u (local.get 1))))
+            (func (export "txcv_post") (param i32 i32) (result i64)
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (i32.add (local.get 0) (i32.const 1))) (i64.const 32))
+                    (i64.extend_i32_u (i32.sub (local.get 1) (i32.const 1))))))
+    "#;
+
+    fn load(name: &str, wat: &str) -> anyhow::Result<WasmPlugin> {
+        let path =
+            std::env::temp_dir().join(format!("txcv-plugin-{}-{name}.wasm", std::process::id()));
+        std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        let plugin = WasmPlugin::load(&path);
+        std::fs::remove_file(path).unwrap();
+
+        plugin
+    }
+
+    #[test]
+    fn filter_text() {
+        let mut plugin = load("filter", PLUGIN).unwrap();
+
+        assert_eq!(plugin.pre("hello").unwrap(), "hello");
+        assert_eq!(plugin.post("hello").unwrap(), "ello");
+    }
+
+    #[test]
+    fn reject_imports() {
+        let wat = r#"
+            (module
+                (import "env" "read_file" (func (param i32)))
+                (memory (export "memory") 1)
+                (func (export "txcv_alloc") (param i32) (result i32) i32.const 0))
+        "#;
+
+        assert!(load("imports", wat).is_err());
+    }
+
+    #[test]
+    fn limit_memory() {
+        // 2048 pages are 128 MiB
+        let wat = r#"
+            (module
+                (memory (export "memory") 2048)
+                (func (export "txcv_alloc") (param i32) (result i32) i32.const 0))
+        "#;
+
+        assert!(load("memory", wat).is_err());
+    }
+}
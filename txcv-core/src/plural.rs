@@ -0,0 +1,116 @@
+//! plural rules of the target languages, the gettext plural forms of po and the cldr plural
+//! categories of icu messages
+
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Category {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl Category {
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "zero" => Some(Category::Zero),
+            "one" => Some(Category::One),
+            "two" => Some(Category::Two),
+            "few" => Some(Category::Few),
+            "many" => Some(Category::Many),
+            "other" => Some(Category::Other),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Zero => f.write_str("zero"),
+            Category::One => f.write_str("one"),
+            Category::Two => f.write_str("two"),
+            Category::Few => f.write_str("few"),
+            Category::Many => f.write_str("many"),
+            Category::Other => f.write_str("other"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Rule {
+    /// the value of the `Plural-Forms` header of po
+    pub plural_forms: &'static str,
+    /// the category of every gettext plural form, in the order of `msgstr[n]`
+    pub forms: &'static [Category],
+    /// the categories of icu plural messages
+    pub categories: &'static [Category],
+}
+
+const ONE_FORM: Rule = Rule {
+    plural_forms: "nplurals=1; plural=0;",
+    forms: &[Category::Other],
+    categories: &[Category::Other],
+};
+
+const TWO_FORMS: Rule = Rule {
+    plural_forms: "nplurals=2; plural=(n != 1);",
+    forms: &[Category::One, Category::Other],
+    categories: &[Category::One, Category::Other],
+};
+
+/// french, spanish, italian and portuguese use `many` for the millions
+const TWO_FORMS_MANY: Rule = Rule {
+    categories: &[Category::One, Category::Many, Category::Other],
+    ..TWO_FORMS
+};
+
+/// the plural rule of the txcv language code, none for the unknown languages
+pub fn rule(lang: &str) -> Option<Rule> {
+    let rule = match lang {
+        "zh" | "zh-TW" | "jp" | "ko" | "vi" | "id" | "th" | "ms" => ONE_FORM,
+        "en" | "de" | "tr" | "hi" => TWO_FORMS,
+        "es" | "it" | "pt" => TWO_FORMS_MANY,
+        "fr" => Rule {
+            plural_forms: "nplurals=2; plural=(n > 1);",
+            ..TWO_FORMS_MANY
+        },
+        "ru" => Rule {
+            plural_forms: "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && \
+                           (n%100<10 || n%100>=20) ? 1 : 2);",
+            forms: &[Category::One, Category::Few, Category::Many],
+            categories: &[
+                Category::One,
+                Category::Few,
+                Category::Many,
+                Category::Other,
+            ],
+        },
+        "ar" => Rule {
+            plural_forms: "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && \
+                           n%100<=10 ? 3 : n%100>=11 ? 4 : 5);",
+            forms: &[
+                Category::Zero,
+                Category::One,
+                Category::Two,
+                Category::Few,
+                Category::Many,
+                Category::Other,
+            ],
+            categories: &[
+                Category::Zero,
+                Category::One,
+                Category::Two,
+                Category::Few,
+                Category::Many,
+                Category::Other,
+            ],
+        },
+        _ => return None,
+    };
+
+    Some(rule)
+}
@@ -0,0 +1,347 @@
+//! placeholder protection, the protected spans are replaced by placeholders before translating
+//! and restored after translating, so the translation engine can't drop or alter them
+
+use std::ops::Range;
+
+/// western emoticons which are kept as is
+const EMOTICONS: &[&str] = &[
+    "¯\\_(ツ)_/¯",
+    ":-)",
+    ":-(",
+    ":-D",
+    ":-P",
+    ";-)",
+    ":')",
+    ":'(",
+    ":)",
+    ":(",
+    ":D",
+    ":P",
+    ":p",
+    ";)",
+    "XD",
+    "<3",
+    "^_^",
+    "^^",
+    "T_T",
+    "-_-",
+    "o_O",
+    "O_o",
+];
+
+/// characters which are commonly used in kaomoji
+const KAOMOJI_CHARS: &[char] = &[
+    '^', '_', '°', '□', '◕', '‿', '｡', '・', 'ω', '∀', '≧', '≦', '▽', '´', '`', '･', 'Д', '╯',
+    '︵', 'ᴗ', '▿', '∇', '￣', 'ε', '♡', '❤', '⊙', '◉', 'ಠ', '益', '╬', 'ﾟ', '•', '≖', 'ヮ',
+];
+
+const MAX_KAOMOJI_LEN: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct Protected {
+    pub text: String,
+    /// the texts which placeholders are restored to, index is the placeholder number
+    restores: Vec<String>,
+}
+
+impl Protected {
+    pub fn is_empty(&self) -> bool {
+        self.restores.is_empty()
+    }
+}
+
+/// replace spans with placeholders, every span is restored to the given text, the spans must be
+/// sorted and not overlapped
+pub fn protect(text: &str, spans: Vec<(Range<usize>, String)>) -> Protected {
+    let mut protected = String::with_capacity(text.len());
+    let mut restores = Vec::with_capacity(spans.len());
+    let mut last = 0;
+    for (range, restore) in spans {
+        protected.push_str(&text[last..range.start]);
+        protected.push_str(&placeholder(restores.len()));
+        restores.push(restore);
+        last = range.end;
+    }
+    protected.push_str(&text[last..]);
+
+    Protected {
+        text: protected,
+        restores,
+    }
+}
+
+/// restore placeholders, the protected texts whose placeholders are dropped by the translation
+/// engine are appended, so they are never lost
+pub fn restore(translated: &str, protected: &Protected) -> String {
+    let mut result = translated.to_string();
+    let mut lost = vec![];
+    for (index, restore) in protected.restores.iter().enumerate() {
+        let placeholder = placeholder(index);
+        if result.contains(&placeholder) {
+            result = result.replace(&placeholder, restore);
+        } else {
+            lost.push(restore.as_str());
+        }
+    }
+
+    if !lost.is_empty() {
+        result.push(' ');
+        result.push_str(&lost.join(" "));
+    }
+
+    result
+}
+
+fn placeholder(index: usize) -> String {
+    format!("{{{{{index}}}}}")
+}
+
+/// merge spans of different finders, spans overlapped with a previous span are dropped
+pub fn merge_spans(mut spans: Vec<(Range<usize>, String)>) -> Vec<(Range<usize>, String)> {
+    spans.sort_by_key(|(range, _)| range.start);
+
+    let mut end = 0;
+    spans.retain(|(range, _)| {
+        if range.start < end {
+            return false;
+        }
+
+        end = range.end;
+        true
+    });
+
+    spans
+}
+
+/// find emoji sequences, kaomoji, emoticons and `:shortcodes:`, every span is restored to itself
+pub fn emoji_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = vec![];
+    spans.extend(emoji_sequences(text));
+    spans.extend(shortcodes(text));
+    spans.extend(kaomoji(text));
+    for emoticon in EMOTICONS {
+        spans.extend(
+            text.match_indices(emoticon)
+                .map(|(start, _)| start..start + emoticon.len())
+                .filter(|range| {
+                    // emoticon like `XD` must not be a part of word
+                    let before = text[..range.start].chars().next_back();
+                    let after = text[range.end..].chars().next();
+
+                    !before.is_some_and(char::is_alphanumeric)
+                        && !after.is_some_and(char::is_alphanumeric)
+                }),
+        );
+    }
+
+    merge_spans(
+        spans
+            .into_iter()
+            .map(|range| (range.clone(), text[range].to_string()))
+            .collect(),
+    )
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1f000..=0x1faff | 0x2600..=0x27bf | 0x2b00..=0x2bff
+    )
+}
+
+/// zero width joiner, variation selectors and keycap combine emoji into one sequence
+fn is_emoji_component(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x200d | 0xfe0e | 0xfe0f | 0x20e3 | 0xe0020..=0xe007f
+    )
+}
+
+fn emoji_sequences(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    let mut start = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        // keycap like 1️⃣ starts with an ascii character
+        let keycap = matches!(c, '0'..='9' | '#' | '*')
+            && chars
+                .peek()
+                .is_some_and(|(_, next)| is_emoji_component(*next));
+
+        match start {
+            None if is_emoji(c) || keycap => start = Some(index),
+            Some(_) if is_emoji(c) || is_emoji_component(c) => {}
+            Some(span_start) => {
+                spans.push(span_start..index);
+                start = keycap.then_some(index);
+            }
+            None => {}
+        }
+    }
+
+    if let Some(span_start) = start {
+        spans.push(span_start..text.len());
+    }
+
+    spans
+}
+
+/// shortcodes like `:smile:`, the colons must not be surrounded by alphanumeric characters, so
+/// time like `10:30:00` is not a shortcode
+fn shortcodes(text: &str) -> Vec<Range<usize>> {
+    let is_code_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-');
+
+    let mut spans = vec![];
+    for (start, _) in text.match_indices(':') {
+        if text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+
+        let rest = &text[start + 1..];
+        let code_len = rest
+            .chars()
+            .take_while(|c| is_code_char(*c))
+            .map(char::len_utf8)
+            .sum::<usize>();
+        let code = &rest[..code_len];
+        if code.is_empty()
+            || !code.chars().any(|c| c.is_ascii_alphabetic())
+            || !rest[code_len..].starts_with(':')
+        {
+            continue;
+        }
+
+        let end = start + 1 + code_len + 1;
+        if text[end..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+
+        spans.push(start..end);
+    }
+
+    spans
+}
+
+/// kaomoji like `(^_^)` or `(╯°□°）╯︵ ┻━┻`, a short bracket group without whitespace which
+/// contains kaomoji characters
+fn kaomoji(text: &str) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    for (start, c) in text.char_indices() {
+        if !matches!(c, '(' | '（') {
+            continue;
+        }
+
+        let rest = &text[start..];
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .take(MAX_KAOMOJI_LEN)
+            .take_while(|(_, c)| !c.is_whitespace())
+            .find(|(_, c)| matches!(c, ')' | '）'))
+            .map(|(index, c)| index + c.len_utf8());
+
+        if let Some(end) = end {
+            let group = &rest[..end];
+            if group.chars().any(|c| KAOMOJI_CHARS.contains(&c)) {
+                // kaomoji arms like `╯` after the bracket belong to it
+                let arms = rest[end..]
+                    .chars()
+                    .take_while(|c| !c.is_whitespace() && !c.is_alphanumeric())
+                    .map(char::len_utf8)
+                    .sum::<usize>();
+
+                spans.push(start..start + end + arms);
+            }
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const CORPUS: &[&str] = &[
+        "I love it 😀",
+        "family 👨‍👩‍👧‍👦 trip",
+        "flags 🇨🇳🇯🇵 and keycap 1️⃣",
+        "thumbs up 👍🏽!",
+        "nice :thumbsup: and :smile_cat:",
+        "so happy (^_^)",
+        "table flip (╯°□°）╯︵ ┻━┻",
+        "shrug ¯\\_(ツ)_/¯",
+        "see you :) <3",
+        "cute (｡◕‿◕｡) cat",
+        "time is 10:30:00",
+    ];
+
+    #[test]
+    fn corpus_round_trip() {
+        for text in CORPUS {
+            let protected = protect(text, emoji_spans(text));
+
+            assert_eq!(restore(&protected.text, &protected), *text);
+        }
+    }
+
+    #[test]
+    fn protected_text_has_no_emoji() {
+        for text in CORPUS {
+            let protected = protect(text, emoji_spans(text));
+
+            assert!(
+                !protected.text.chars().any(is_emoji),
+                "{text} -> {}",
+                protected.text
+            );
+        }
+    }
+
+    #[test]
+    fn shortcodes_kaomoji_and_keycaps_are_protected() {
+        for (text, protected_text) in [
+            ("nice :thumbsup: and :smile_cat:", "nice {{0}} and {{1}}"),
+            ("flags 🇨🇳🇯🇵 and keycap 1️⃣", "flags {{0}} and keycap {{1}}"),
+            ("table flip (╯°□°）╯︵ ┻━┻", "table flip {{0}} ┻━┻"),
+            ("so happy (^_^)", "so happy {{0}}"),
+        ] {
+            assert_eq!(protect(text, emoji_spans(text)).text, protected_text);
+        }
+    }
+
+    #[test]
+    fn time_is_not_shortcode() {
+        assert!(shortcodes("time is 10:30:00").is_empty());
+    }
+
+    #[test]
+    fn lost_placeholder_is_appended() {
+        let text = "I love it 😀";
+        let protected = protect(text, emoji_spans(text));
+
+        assert_eq!(restore("我喜欢它", &protected), "我喜欢它 😀");
+    }
+
+    proptest! {
+        // set PROPTEST_RNG_SEED to reproduce a failure with a deterministic seed
+        #[test]
+        fn protect_restore_round_trip(text in any::<String>()) {
+            // text which contains placeholder syntax is ambiguous
+            prop_assume!(!text.contains("{{"));
+
+            let protected = protect(&text, emoji_spans(&text));
+
+            prop_assert_eq!(restore(&protected.text, &protected), text);
+        }
+    }
+}
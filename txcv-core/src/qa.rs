@@ -0,0 +1,211 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+/// segments shorter than this are not checked by length ratio
+const MIN_RATIO_CHECK_LEN: usize = 10;
+const MIN_LENGTH_RATIO: f64 = 0.15;
+const MAX_LENGTH_RATIO: f64 = 6.0;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum QaFormat {
+    #[default]
+    Plain,
+    Json,
+}
+
+impl Display for QaFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QaFormat::Plain => f.write_str("plain"),
+            QaFormat::Json => f.write_str("json"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MissingNumber,
+    PlaceholderMismatch,
+    BracketMismatch,
+    Untranslated,
+    DoubledSpace,
+    LengthRatio,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    /// segment index, start from 1
+    pub segment: usize,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// returned when qa finds issues, the value is the number of issues
+#[derive(Debug, Copy, Clone)]
+pub struct QaFailed(pub usize);
+
+impl Display for QaFailed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "qa found {} issues", self.0)
+    }
+}
+
+impl Error for QaFailed {}
+
+pub fn check<S: AsRef<str>, T: AsRef<str>>(segments: &[(S, T)]) -> Vec<Issue> {
+    segments
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (source, target))| {
+            check_segment(index + 1, source.as_ref(), target.as_ref())
+        })
+        .collect()
+}
+
+pub fn print(issues: &[Issue], format: QaFormat) -> anyhow::Result<()> {
+    match format {
+        QaFormat::Json => eprintln!("{}", serde_json::to_string(issues)?),
+        QaFormat::Plain => {
+            if issues.is_empty() {
+                return Ok(());
+            }
+
+            eprintln!("qa issues:");
+            for issue in issues {
+                eprintln!("  segment {}: {}", issue.segment, issue.message);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_segment(segment: usize, source: &str, target: &str) -> Vec<Issue> {
+    let mut issues = vec![];
+    let mut issue = |kind, message| {
+        issues.push(Issue {
+            segment,
+            kind,
+            message,
+        })
+    };
+
+    let target_numbers = numbers(target);
+    for number in numbers(source) {
+        if !target_numbers.contains(&number) {
+            issue(
+                IssueKind::MissingNumber,
+                format!("number {number} is missing"),
+            );
+        }
+    }
+
+    let mut source_placeholders = placeholders(source);
+    let mut target_placeholders = placeholders(target);
+    source_placeholders.sort_unstable();
+    target_placeholders.sort_unstable();
+    if source_placeholders != target_placeholders {
+        issue(
+            IssueKind::PlaceholderMismatch,
+            format!("placeholders {source_placeholders:?} become {target_placeholders:?}"),
+        );
+    }
+
+    for (open, close) in [('(', ')'), ('[', ']'), ('{', '}')] {
+        if bracket_balance(source, open, close) != bracket_balance(target, open, close) {
+            issue(
+                IssueKind::BracketMismatch,
+                format!("brackets {open}{close} are mismatched"),
+            );
+        }
+    }
+
+    if source.trim() == target.trim() && source.chars().any(char::is_alphabetic) {
+        issue(
+            IssueKind::Untranslated,
+            "source is copied verbatim".to_string(),
+        );
+    }
+
+    if target.contains("  ") && !source.contains("  ") {
+        issue(IssueKind::DoubledSpace, "doubled spaces".to_string());
+    }
+
+    let source_len = source.chars().count();
+    if source_len >= MIN_RATIO_CHECK_LEN {
+        let ratio = target.chars().count() as f64 / source_len as f64;
+        if !(MIN_LENGTH_RATIO..=MAX_LENGTH_RATIO).contains(&ratio) {
+            issue(
+                IssueKind::LengthRatio,
+                format!("length ratio {ratio:.2} is abnormal"),
+            );
+        }
+    }
+
+    issues
+}
+
+fn numbers(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|number| !number.is_empty())
+        .collect()
+}
+
+/// collect `{name}` and printf style `%s` placeholders
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut placeholders = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' => {
+                if let Some(end) = text[start..].find('}') {
+                    let placeholder = &text[start..start + end + 1];
+                    if !placeholder[1..].contains('{') {
+                        placeholders.push(placeholder);
+                    }
+                }
+            }
+
+            '%' => {
+                if let Some(&(end, next)) = chars.peek() {
+                    if next.is_ascii_alphabetic() {
+                        placeholders.push(&text[start..end + 1]);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    placeholders
+}
+
+/// count open and close brackets, full-width brackets are treated as ascii
+fn bracket_balance(text: &str, open: char, close: char) -> (usize, usize) {
+    let normalize = |c: char| match c {
+        '（' => '(',
+        '）' => ')',
+        '【' | '［' => '[',
+        '】' | '］' => ']',
+        '｛' => '{',
+        '｝' => '}',
+        c => c,
+    };
+
+    text.chars()
+        .map(normalize)
+        .fold((0, 0), |(opens, closes), c| {
+            if c == open {
+                (opens + 1, closes)
+            } else if c == close {
+                (opens, closes + 1)
+            } else {
+                (opens, closes)
+            }
+        })
+}
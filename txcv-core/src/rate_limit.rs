@@ -0,0 +1,876 @@
+// copy from leaky-bucket-lite, but the timer is injected by `Clock` to work with any runtime
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::{Mutex as SyncMutex, RwLock};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use async_std::channel::{self, Receiver, Sender};
+use futures_timer::Delay;
+use futures_util::future::{self, BoxFuture, Either, FutureExt};
+
+use crate::diagnostic;
+
+/// The shortest refill interval, a shorter one can't be measured.
+const MIN_REFILL_INTERVAL: Duration = Duration::from_nanos(1);
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Source of time of the bucket, implement it to use the timer of an async runtime, or a mock
+/// time in tests.
+pub trait Clock: Debug + Send + Sync + 'static {
+    fn now(&self) -> Instant;
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The default clock, uses a runtime independent timer thread.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Delay::new(duration).boxed()
+    }
+}
+
+/// The system clock which shows a transient notice on stderr while sleeping long.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoticeClock;
+
+impl Clock for NoticeClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        diagnostic::waiting(duration, Delay::new(duration)).boxed()
+    }
+}
+
+/// The error returned by `acquire` when the bucket is closed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Closed;
+
+impl Display for Closed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("rate limiter is closed")
+    }
+}
+
+impl Error for Closed {}
+
+/// Held while the acquired tokens are in use, `drain` waits until all permits are dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct Permit {
+    _in_flight: Sender<()>,
+}
+
+/// The waits of the acquiring tasks, counted since the bucket is built.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Metrics {
+    /// Successful acquisitions.
+    pub acquired: u64,
+    /// Acquisitions which slept for the tokens.
+    pub waits: u64,
+    /// Total time of the waits, the time queued behind the other tasks is included.
+    pub waited: Duration,
+    pub longest_wait: Duration,
+}
+
+impl Display for Metrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} requests waited for the rate limiter, {}ms in total, {}ms at most",
+            self.waits,
+            self.acquired,
+            self.waited.as_millis(),
+            self.longest_wait.as_millis()
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct TurnsState {
+    next: u64,
+    serving: u64,
+    wakers: HashMap<u64, Waker>,
+    /// Tickets dropped before their turns, skipped when the turn comes.
+    abandoned: HashSet<u64>,
+}
+
+impl TurnsState {
+    fn advance(&mut self) {
+        self.serving += 1;
+        while self.abandoned.remove(&self.serving) {
+            self.serving += 1;
+        }
+
+        if let Some(waker) = self.wakers.remove(&self.serving) {
+            waker.wake();
+        }
+    }
+}
+
+/// Serves the acquiring tasks one by one in their arrival order, so a heavy acquisition isn't
+/// starved by the lighter ones which come later.
+#[derive(Debug, Default)]
+struct Turns {
+    state: SyncMutex<TurnsState>,
+}
+
+impl Turns {
+    /// Take a ticket, the returned future is ready when it's the turn of the ticket.
+    fn take(&self) -> Ticket<'_> {
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next;
+        state.next += 1;
+
+        Ticket {
+            turns: self,
+            ticket,
+            served: false,
+        }
+    }
+}
+
+struct Ticket<'a> {
+    turns: &'a Turns,
+    ticket: u64,
+    served: bool,
+}
+
+impl<'a> Future for Ticket<'a> {
+    type Output = Turn<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.turns.state.lock().unwrap();
+        if state.serving != self.ticket {
+            state.wakers.insert(self.ticket, cx.waker().clone());
+
+            return Poll::Pending;
+        }
+        drop(state);
+
+        self.served = true;
+
+        Poll::Ready(Turn { turns: self.turns })
+    }
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if self.served {
+            return;
+        }
+
+        let mut state = self.turns.state.lock().unwrap();
+        state.wakers.remove(&self.ticket);
+        if state.serving == self.ticket {
+            state.advance();
+        } else {
+            state.abandoned.insert(self.ticket);
+        }
+    }
+}
+
+/// Held while it's the turn of the task, the next ticket is served when it's dropped.
+struct Turn<'a> {
+    turns: &'a Turns,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        self.turns.state.lock().unwrap().advance();
+    }
+}
+
+#[derive(Debug)]
+struct LeakyBucketInner {
+    /// How many tokens this bucket can hold.
+    max: u32,
+    /// Interval at which the bucket gains tokens.
+    refill_interval: Duration,
+    /// Amount of tokens gained per interval.
+    refill_amount: u32,
+    /// Characters counted as one token by `acquire_chars`, none counts one token per request.
+    chars_per_token: Option<u32>,
+    clock: Arc<dyn Clock>,
+
+    /// Current tokens in the bucket.
+    tokens: RwLock<u32>,
+    /// Last refill of the tokens.
+    last_refill: RwLock<Instant>,
+
+    /// To prevent more than one task from acquiring at the same time, the tasks take turns in
+    /// their arrival order.
+    turns: Turns,
+    metrics: SyncMutex<Metrics>,
+
+    /// Closed when the bucket is closed, nothing is sent, sleeping acquires wait on it to wake
+    /// up early.
+    closed: (Sender<()>, Receiver<()>),
+    /// Cloned into every permit, the sender is taken when draining, so the receiver returns
+    /// once all permits are dropped.
+    in_flight: (SyncMutex<Option<Sender<()>>>, Receiver<()>),
+}
+
+impl LeakyBucketInner {
+    fn new(
+        max: u32,
+        tokens: u32,
+        refill_interval: Duration,
+        refill_amount: u32,
+        chars_per_token: Option<u32>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let (in_flight_sender, in_flight_receiver) = channel::bounded(1);
+
+        Self {
+            tokens: RwLock::new(tokens),
+            max,
+            refill_interval,
+            refill_amount,
+            chars_per_token,
+            last_refill: RwLock::new(clock.now()),
+            clock,
+            turns: Default::default(),
+            metrics: Default::default(),
+            closed: channel::bounded(1),
+            in_flight: (SyncMutex::new(Some(in_flight_sender)), in_flight_receiver),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.0.is_closed()
+    }
+
+    /// Updates the tokens in the leaky bucket and returns the current amount
+    /// of tokens in the bucket.
+    #[inline]
+    fn update_tokens(&self) -> u32 {
+        let mut last_refill = self.last_refill.write().unwrap();
+        let mut tokens = self.tokens.write().unwrap();
+        let time_passed = self.clock.now() - *last_refill;
+
+        // Integer division, the float one floors an exact multiple of the interval like 0.3 / 0.1
+        // to one refill less.
+        let refills_since = time_passed.as_nanos() / self.refill_interval.as_nanos();
+        let refilled = u64::try_from(refills_since)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(u64::from(self.refill_amount));
+
+        // Not longer than the time passed, so it fits.
+        let advanced = refills_since * self.refill_interval.as_nanos();
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            *last_refill += Duration::new(
+                (advanced / NANOS_PER_SEC) as u64,
+                (advanced % NANOS_PER_SEC) as u32,
+            );
+            *tokens = u64::from(*tokens)
+                .saturating_add(refilled)
+                .min(u64::from(self.max)) as u32;
+        }
+
+        *tokens
+    }
+
+    async fn acquire(&self, amount: u32) -> Result<Permit, Closed> {
+        let started = self.clock.now();
+        // Make sure this is the only task accessing the tokens in a real
+        // "write" rather than "update" way.
+        let _turn = self.turns.take().await;
+
+        if self.is_closed() {
+            return Err(Closed);
+        }
+
+        let current_tokens = self.update_tokens();
+
+        let waited = current_tokens < amount;
+        if waited {
+            let tokens_needed = amount - current_tokens;
+            let mut refills_needed = tokens_needed / self.refill_amount;
+            let refills_needed_remainder = tokens_needed % self.refill_amount;
+
+            if refills_needed_remainder > 0 {
+                refills_needed += 1;
+            }
+
+            let target_time = {
+                let last_refill = self.last_refill.read().unwrap();
+                *last_refill + self.refill_interval * refills_needed
+            };
+            let sleep_duration = target_time.saturating_duration_since(self.clock.now());
+
+            // Wake up when the bucket is closed while sleeping.
+            let sleep = self.clock.sleep(sleep_duration);
+            if let Either::Right(_) = future::select(sleep, self.closed.1.recv().boxed()).await {
+                return Err(Closed);
+            }
+
+            self.update_tokens();
+        }
+
+        let in_flight = self.in_flight.0.lock().unwrap().clone().ok_or(Closed)?;
+        *self.tokens.write().unwrap() -= amount;
+
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.acquired += 1;
+        if waited {
+            let wait = self.clock.now().saturating_duration_since(started);
+            metrics.waits += 1;
+            metrics.waited += wait;
+            metrics.longest_wait = metrics.longest_wait.max(wait);
+        }
+
+        Ok(Permit {
+            _in_flight: in_flight,
+        })
+    }
+}
+
+/// The leaky bucket.
+#[derive(Clone, Debug)]
+pub struct LeakyBucket {
+    inner: Arc<LeakyBucketInner>,
+}
+
+impl LeakyBucket {
+    fn new(
+        max: u32,
+        tokens: u32,
+        refill_interval: Duration,
+        refill_amount: u32,
+        chars_per_token: Option<u32>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let inner = Arc::new(LeakyBucketInner::new(
+            max,
+            tokens,
+            refill_interval,
+            refill_amount,
+            chars_per_token,
+            clock,
+        ));
+
+        Self { inner }
+    }
+
+    /// Construct a new leaky bucket through a builder.
+    #[must_use]
+    pub const fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Get the max number of tokens this rate limiter is configured for.
+    #[must_use]
+    pub fn max(&self) -> u32 {
+        self.inner.max
+    }
+
+    #[inline]
+    pub async fn acquire_one(&self) -> Result<Permit, Closed> {
+        self.acquire(1).await
+    }
+
+    /// Acquire tokens, return `Closed` error if the bucket is closed before or while waiting.
+    pub async fn acquire(&self, amount: u32) -> Result<Permit, Closed> {
+        assert!(
+            amount <= self.max(),
+            "Acquiring more tokens than the configured maximum is not possible"
+        );
+
+        self.inner.acquire(amount).await
+    }
+
+    /// Acquire the tokens of a request of the characters, a token per started `chars_per_token`
+    /// characters, at least one and at most `max`, or one token without `chars_per_token`.
+    pub async fn acquire_chars(&self, chars: usize) -> Result<Permit, Closed> {
+        self.inner.acquire(self.weight(chars)).await
+    }
+
+    fn weight(&self, chars: usize) -> u32 {
+        let Some(chars_per_token) = self.inner.chars_per_token else {
+            return 1;
+        };
+
+        let tokens = chars.div_ceil(chars_per_token as usize).max(1);
+
+        u32::try_from(tokens).map_or(self.max(), |tokens| tokens.min(self.max()))
+    }
+
+    /// The waits of the acquisitions so far.
+    pub fn metrics(&self) -> Metrics {
+        *self.inner.metrics.lock().unwrap()
+    }
+
+    /// Close the bucket, the pending and later `acquire` calls return `Closed` error.
+    pub fn close(&self) {
+        self.inner.closed.0.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Close the bucket and wait until all permits are dropped.
+    pub async fn drain(&self) {
+        self.close();
+        self.inner.in_flight.0.lock().unwrap().take();
+
+        // Nothing is sent, it returns when all senders in permits are dropped.
+        let _ = self.inner.in_flight.1.recv().await;
+    }
+}
+
+/// Builder for a leaky bucket.
+#[derive(Debug)]
+pub struct Builder {
+    max: Option<u32>,
+    tokens: Option<u32>,
+    refill_interval: Option<Duration>,
+    refill_amount: Option<u32>,
+    chars_per_token: Option<u32>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Builder {
+    /// Create a new builder with all defaults.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max: None,
+            tokens: None,
+            refill_interval: None,
+            refill_amount: None,
+            chars_per_token: None,
+            clock: None,
+        }
+    }
+
+    /// Set the max value for the builder.
+    #[must_use]
+    pub const fn max(mut self, max: u32) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// The number of tokens that the bucket should start with.
+    ///
+    /// If set to larger than `max` at build time, will only saturate to max.
+    #[must_use]
+    pub const fn tokens(mut self, tokens: u32) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// Set the max value for the builder.
+    #[must_use]
+    pub const fn refill_interval(mut self, refill_interval: Duration) -> Self {
+        self.refill_interval = Some(refill_interval);
+        self
+    }
+
+    /// Set the amount of tokens gained per refill interval.
+    #[must_use]
+    pub const fn refill_amount(mut self, refill_amount: u32) -> Self {
+        self.refill_amount = Some(refill_amount);
+        self
+    }
+
+    /// Set the refill rate in tokens per second, one token is gained per interval, so fractional
+    /// rates like 0.5 tokens per second are possible by a longer interval.
+    ///
+    /// Overrides `refill_interval` and `refill_amount`.
+    #[must_use]
+    pub fn rate(mut self, tokens_per_sec: f64) -> Self {
+        assert!(
+            tokens_per_sec.is_finite() && tokens_per_sec > 0.0,
+            "The refill rate must be positive"
+        );
+
+        // A huge rate rounds to a zero interval.
+        self.refill_interval =
+            Some(Duration::from_secs_f64(1.0 / tokens_per_sec).max(MIN_REFILL_INTERVAL));
+        self.refill_amount = Some(1);
+        self
+    }
+
+    /// Set the characters counted as one token by `acquire_chars`, by default every request
+    /// takes one token.
+    #[must_use]
+    pub const fn chars_per_token(mut self, chars_per_token: Option<u32>) -> Self {
+        self.chars_per_token = chars_per_token;
+        self
+    }
+
+    /// Set the clock, default is `SystemClock`.
+    #[must_use]
+    pub fn clock<C: Clock>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Construct a new leaky bucket.
+    #[must_use]
+    pub fn build(self) -> LeakyBucket {
+        const DEFAULT_MAX: u32 = 120;
+        const DEFAULT_TOKENS: u32 = 0;
+        const DEFAULT_REFILL_INTERVAL: Duration = Duration::from_secs(1);
+        const DEFAULT_REFILL_AMOUNT: u32 = 1;
+
+        let max = self.max.unwrap_or(DEFAULT_MAX);
+        let tokens = self.tokens.unwrap_or(DEFAULT_TOKENS);
+        let refill_interval = self.refill_interval.unwrap_or(DEFAULT_REFILL_INTERVAL);
+        let refill_amount = self.refill_amount.unwrap_or(DEFAULT_REFILL_AMOUNT);
+        assert!(refill_amount > 0, "The refill amount must be positive");
+        assert!(
+            !refill_interval.is_zero(),
+            "The refill interval must be positive"
+        );
+        assert!(
+            self.chars_per_token != Some(0),
+            "The characters per token must be positive"
+        );
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+
+        LeakyBucket::new(
+            max,
+            tokens,
+            refill_interval,
+            refill_amount,
+            self.chars_per_token,
+            clock,
+        )
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_std::task;
+    use futures_util::future::join_all;
+
+    use super::*;
+
+    /// virtual time, sleeping advances the time immediately
+    #[derive(Debug, Clone)]
+    struct MockClock {
+        start: Instant,
+        elapsed: Arc<SyncMutex<Duration>>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                elapsed: Default::default(),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.elapsed.lock().unwrap() += duration;
+        }
+
+        fn elapsed(&self) -> Duration {
+            *self.elapsed.lock().unwrap()
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed()
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            self.advance(duration);
+
+            future::ready(()).boxed()
+        }
+    }
+
+    fn tokens(bucket: &LeakyBucket) -> u32 {
+        *bucket.inner.tokens.read().unwrap()
+    }
+
+    #[async_std::test]
+    async fn refill_math() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(10)
+            .refill_interval(Duration::from_millis(100))
+            .refill_amount(2)
+            .clock(clock.clone())
+            .build();
+
+        // 2 refills, the partial interval is kept for the next refill
+        clock.advance(Duration::from_millis(250));
+        let _permit = bucket.acquire(4).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(250));
+        assert_eq!(tokens(&bucket), 0);
+
+        // 3 tokens need 2 refills, counted from the last refill at 200ms
+        let _permit = bucket.acquire(3).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(400));
+        assert_eq!(tokens(&bucket), 1);
+    }
+
+    #[async_std::test]
+    async fn saturate_at_max() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(3)
+            .refill_interval(Duration::from_millis(100))
+            .clock(clock.clone())
+            .build();
+
+        clock.advance(Duration::from_secs(1));
+        let _permit = bucket.acquire(3).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+
+        let _permit = bucket.acquire_one().await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(1100));
+    }
+
+    #[async_std::test]
+    async fn weighted_acquire() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(5)
+            .tokens(5)
+            .refill_interval(Duration::from_millis(100))
+            .clock(clock.clone())
+            .build();
+
+        let _permit = bucket.acquire(5).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+
+        let _permit = bucket.acquire(2).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(200));
+    }
+
+    #[async_std::test]
+    async fn concurrent_waiters() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(1)
+            .refill_interval(Duration::from_millis(100))
+            .clock(clock.clone())
+            .build();
+
+        let acquired_at = join_all((0..3).map(|_| async {
+            let _permit = bucket.acquire_one().await.unwrap();
+
+            clock.elapsed()
+        }))
+        .await;
+
+        // waiters are served one by one, every one waits for its own refill
+        assert_eq!(
+            acquired_at,
+            [100, 200, 300].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[async_std::test]
+    async fn acquire_by_chars() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(3)
+            .tokens(3)
+            .refill_interval(Duration::from_millis(100))
+            .chars_per_token(Some(100))
+            .clock(clock.clone())
+            .build();
+
+        // 150 characters take 2 tokens
+        let _permit = bucket.acquire_chars(150).await.unwrap();
+        assert_eq!(tokens(&bucket), 1);
+        let _permit = bucket.acquire_chars(0).await.unwrap();
+        assert_eq!(tokens(&bucket), 0);
+
+        // a long request takes the whole bucket instead of never being served
+        let _permit = bucket.acquire_chars(10_000).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(300));
+
+        let unweighted = LeakyBucket::builder().max(3).tokens(3).build();
+        let _permit = unweighted.acquire_chars(10_000).await.unwrap();
+        assert_eq!(tokens(&unweighted), 2);
+    }
+
+    #[async_std::test]
+    async fn served_in_arrival_order() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(3)
+            .refill_interval(Duration::from_millis(100))
+            .clock(clock.clone())
+            .build();
+
+        let (bucket, clock) = (&bucket, &clock);
+        let acquired_at = join_all([3, 1, 1].map(|amount| async move {
+            let _permit = bucket.acquire(amount).await.unwrap();
+
+            clock.elapsed()
+        }))
+        .await;
+
+        // the heavy acquisition isn't overtaken by the lighter ones
+        assert_eq!(
+            acquired_at,
+            [300, 400, 500].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[async_std::test]
+    async fn dropped_ticket_is_skipped() {
+        let turns = Turns::default();
+        let first = turns.take().await;
+        let mut second = turns.take();
+        assert!((&mut second).now_or_never().is_none());
+        let mut third = turns.take();
+        assert!((&mut third).now_or_never().is_none());
+
+        drop(second);
+        drop(first);
+
+        assert!(third.now_or_never().is_some());
+        // the next ticket after the served one
+        assert!(turns.take().now_or_never().is_some());
+    }
+
+    #[async_std::test]
+    async fn count_waits() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(1)
+            .tokens(1)
+            .refill_interval(Duration::from_millis(100))
+            .clock(clock.clone())
+            .build();
+
+        for _ in 0..3 {
+            let _permit = bucket.acquire_one().await.unwrap();
+        }
+
+        assert_eq!(
+            bucket.metrics(),
+            Metrics {
+                acquired: 3,
+                waits: 2,
+                waited: Duration::from_millis(200),
+                longest_wait: Duration::from_millis(100),
+            }
+        );
+        assert_eq!(
+            bucket.metrics().to_string(),
+            "2 of 3 requests waited for the rate limiter, 200ms in total, 100ms at most"
+        );
+    }
+
+    #[async_std::test]
+    async fn refill_amount_per_interval() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(4)
+            .refill_interval(Duration::from_millis(50))
+            .refill_amount(4)
+            .clock(clock.clone())
+            .build();
+
+        let _permit = bucket.acquire(4).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn fractional_rate_scales_interval() {
+        let bucket = LeakyBucket::builder().refill_amount(3).rate(0.5).build();
+
+        assert_eq!(bucket.inner.refill_interval, Duration::from_secs(2));
+        assert_eq!(bucket.inner.refill_amount, 1);
+    }
+
+    #[test]
+    fn huge_rate_has_shortest_interval() {
+        let bucket = LeakyBucket::builder().rate(1e10).build();
+
+        assert_eq!(bucket.inner.refill_interval, MIN_REFILL_INTERVAL);
+    }
+
+    #[async_std::test]
+    async fn long_idle_saturates_at_max() {
+        let clock = MockClock::new();
+        let bucket = LeakyBucket::builder()
+            .max(u32::MAX)
+            .refill_interval(MIN_REFILL_INTERVAL)
+            .refill_amount(u32::MAX)
+            .clock(clock.clone())
+            .build();
+
+        clock.advance(Duration::from_secs(10));
+        let _permit = bucket.acquire(u32::MAX).await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+        assert_eq!(tokens(&bucket), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The refill interval must be positive")]
+    fn zero_interval_is_rejected() {
+        let _ = LeakyBucket::builder()
+            .refill_interval(Duration::ZERO)
+            .build();
+    }
+
+    #[async_std::test]
+    async fn close_wakes_pending_acquire() {
+        let bucket = LeakyBucket::builder()
+            .max(1)
+            .refill_interval(Duration::from_secs(3600))
+            .build();
+
+        let pending = task::spawn({
+            let bucket = bucket.clone();
+            async move { bucket.acquire_one().await.map(drop) }
+        });
+        task::sleep(Duration::from_millis(10)).await;
+        bucket.close();
+
+        assert_eq!(pending.await, Err(Closed));
+        assert_eq!(bucket.acquire_one().await.map(drop), Err(Closed));
+    }
+
+    #[async_std::test]
+    async fn drain_waits_for_permits() {
+        const HOLD: Duration = Duration::from_millis(50);
+
+        let bucket = LeakyBucket::builder().max(1).tokens(1).build();
+        let permit = bucket.acquire_one().await.unwrap();
+        task::spawn(async move {
+            task::sleep(HOLD).await;
+            drop(permit);
+        });
+
+        let start = Instant::now();
+        bucket.drain().await;
+
+        assert!(start.elapsed() + Duration::from_millis(5) >= HOLD);
+        assert!(bucket.is_closed());
+    }
+}
@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+
+use anyhow::Context;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hint, Hinter};
+use rustyline::validate::Validator;
+use rustyline::{Context as HintContext, Helper};
+
+use crate::color::Colorize;
+use crate::lang::Language;
+
+/// prefixes shorter than it are not previewed, too many texts start with them
+const MIN_PREVIEW_PREFIX_CHARS: usize = 2;
+
+pub const HELP: &str = "\
+:edit               open the last translation in $EDITOR to correct it
+:source <LANG|auto> change the source language
+:target <LANG|auto> change the target language
+:concise <on|off>   only print the translation
+:term <SRC>=<DST>   translate the term as DST in this session, without it list the session terms
+:help               print this help";
+
+/// in-session commands of the interactive mode, start with `:`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Command {
+    Edit,
+    /// none is auto detect
+    Source(Option<Language>),
+    /// none is auto detect
+    Target(Option<Language>),
+    Concise(bool),
+    /// a term and its translation of the session glossary, none lists the session terms
+    Term(Option<(String, String)>),
+    Help,
+}
+
+/// parse the line as a command, return none if it is text to translate
+pub fn parse(line: &str) -> Option<anyhow::Result<Command>> {
+    let line = line.trim().strip_prefix(':')?;
+    let (name, arg) = match line.split_once(char::is_whitespace) {
+        None => (line, ""),
+        Some((name, arg)) => (name, arg.trim()),
+    };
+
+    let command = match name {
+        "edit" => Ok(Command::Edit),
+        "source" => parse_language(arg).map(Command::Source),
+        "target" => parse_language(arg).map(Command::Target),
+        "concise" => match arg {
+            "on" => Ok(Command::Concise(true)),
+            "off" => Ok(Command::Concise(false)),
+            _ => Err(anyhow::anyhow!(":concise needs on or off")),
+        },
+        "term" if arg.is_empty() => Ok(Command::Term(None)),
+        "term" => match arg.split_once('=') {
+            Some((source, target)) if !source.trim().is_empty() && !target.trim().is_empty() => Ok(
+                Command::Term(Some((source.trim().to_string(), target.trim().to_string()))),
+            ),
+            _ => Err(anyhow::anyhow!(":term needs <term>=<translation>")),
+        },
+        "help" => Ok(Command::Help),
+        _ => Err(anyhow::anyhow!(
+            "unknown command :{name}, run :help to list commands"
+        )),
+    };
+
+    Some(command)
+}
+
+fn parse_language(arg: &str) -> anyhow::Result<Option<Language>> {
+    match arg {
+        "" => Err(anyhow::anyhow!("language is required, like ja or auto")),
+        "auto" => Ok(None),
+        _ => Language::from_code(arg)
+            .map(Some)
+            .with_context(|| format!("unknown language {arg}")),
+    }
+}
+
+/// show the translation of a previous query as a greyed hint while typing, when the input is
+/// the same as the query or a prefix of it
+#[derive(Debug, Default)]
+pub struct Preview {
+    /// input and output of previous queries, the newest is the last
+    queries: Vec<(String, String)>,
+}
+
+impl Preview {
+    pub fn new(queries: Vec<(String, String)>) -> Self {
+        Self { queries }
+    }
+
+    pub fn push(&mut self, input: String, output: String) {
+        self.queries.push((input, output));
+    }
+}
+
+#[derive(Debug)]
+pub struct PreviewHint {
+    display: String,
+    /// rest of the previous query, the translation is never completed into the input
+    completion: Option<String>,
+}
+
+impl Hint for PreviewHint {
+    fn display(&self) -> &str {
+        &self.display
+    }
+
+    fn completion(&self) -> Option<&str> {
+        self.completion.as_deref()
+    }
+}
+
+impl Hinter for Preview {
+    type Hint = PreviewHint;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &HintContext<'_>) -> Option<PreviewHint> {
+        if pos < line.len() || line.starts_with(':') {
+            return None;
+        }
+
+        if let Some((_, output)) = self.queries.iter().rev().find(|(input, _)| input == line) {
+            return Some(PreviewHint {
+                display: format!(" -> {output}"),
+                completion: None,
+            });
+        }
+
+        if line.trim().chars().count() < MIN_PREVIEW_PREFIX_CHARS {
+            return None;
+        }
+
+        let (input, output) = self
+            .queries
+            .iter()
+            .rev()
+            .find(|(input, _)| input.starts_with(line))?;
+        let rest = &input[line.len()..];
+
+        Some(PreviewHint {
+            display: format!("{rest} -> {output}"),
+            completion: Some(rest.to_string()),
+        })
+    }
+}
+
+impl Highlighter for Preview {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+}
+
+impl Completer for Preview {
+    type Candidate = String;
+}
+
+impl Validator for Preview {}
+
+impl Helper for Preview {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert!(parse("hello").is_none());
+        assert_eq!(
+            parse(":target ja").unwrap().unwrap(),
+            Command::Target(Some(Language::Japanese))
+        );
+        assert_eq!(
+            parse(":source auto").unwrap().unwrap(),
+            Command::Source(None)
+        );
+        assert_eq!(
+            parse(" :concise  on ").unwrap().unwrap(),
+            Command::Concise(true)
+        );
+        assert_eq!(parse(":edit").unwrap().unwrap(), Command::Edit);
+        assert_eq!(
+            parse(":term pull request = 合并请求").unwrap().unwrap(),
+            Command::Term(Some(("pull request".to_string(), "合并请求".to_string())))
+        );
+        assert!(parse(":term pull request").unwrap().is_err());
+        assert!(parse(":concise maybe").unwrap().is_err());
+        assert!(parse(":unknown").unwrap().is_err());
+    }
+
+    #[test]
+    fn preview_exact_and_prefix() {
+        let preview = Preview::new(vec![
+            ("hello".to_string(), "你好".to_string()),
+            ("hello world".to_string(), "你好世界".to_string()),
+        ]);
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = HintContext::new(&history);
+
+        let hint = preview.hint("hello", 5, &ctx).unwrap();
+        assert_eq!(hint.display(), " -> 你好");
+        assert_eq!(hint.completion(), None);
+
+        let hint = preview.hint("hello w", 7, &ctx).unwrap();
+        assert_eq!(hint.display(), "orld -> 你好世界");
+        assert_eq!(hint.completion(), Some("orld"));
+
+        assert!(preview.hint("h", 1, &ctx).is_none());
+        assert!(preview.hint("bye", 3, &ctx).is_none());
+    }
+}
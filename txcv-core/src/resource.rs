@@ -0,0 +1,877 @@
+//! localization resource files, only the translatable strings are translated, the string values
+//! of json and yaml, and the msgids of po into their empty msgstrs. the strings are replaced in
+//! place, so the keys, the order, the comments and the formatting are kept
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use regex::Regex;
+use serde::de::IgnoredAny;
+
+use crate::plural::{self, Category, Rule};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ResourceFormat {
+    Json,
+    Yaml,
+    /// gettext po and pot
+    Po,
+}
+
+impl ResourceFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Some(ResourceFormat::Json),
+            Some("yaml" | "yml") => Some(ResourceFormat::Yaml),
+            Some("po" | "pot") => Some(ResourceFormat::Po),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ResourceFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceFormat::Json => f.write_str("json"),
+            ResourceFormat::Yaml => f.write_str("yaml"),
+            ResourceFormat::Po => f.write_str("po"),
+        }
+    }
+}
+
+/// a translatable string of a resource file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Entry {
+    /// replaced by the translation, the quoted string, or the msgstr lines of po
+    pub range: Range<usize>,
+    /// the unescaped texts, translated separately, a plural message has a text for every variant
+    pub texts: Vec<String>,
+    /// the msgctxt of po
+    pub context: Option<String>,
+    style: Style,
+    /// the translated variants are composed into the icu message again
+    icu: Option<IcuPlural>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Style {
+    Json,
+    /// yaml double quoted, the escapes of json are valid in it
+    DoubleQuoted,
+    SingleQuoted,
+    /// yaml plain scalar, the translation is quoted if it can't be plain
+    Plain,
+    /// yaml literal or folded block scalar
+    Block {
+        indent: usize,
+        folded: bool,
+    },
+    /// the msgstr keyword, like `msgstr` or `msgstr[1]`
+    Po {
+        keyword: String,
+    },
+    /// the msgstr lines of a plural entry of po, the index of the text of every plural form
+    PoPlural {
+        forms: Vec<usize>,
+    },
+    /// the plural forms header of po, which isn't translated
+    Fixed(String),
+}
+
+impl Entry {
+    /// the translations of the texts written in the style of the original string
+    fn encode(&self, translations: &[String]) -> String {
+        let translation = || match &self.icu {
+            None => translations[0].clone(),
+            Some(icu) => icu.compose(translations),
+        };
+
+        match &self.style {
+            Style::Json | Style::DoubleQuoted => json_string(&translation()),
+            Style::SingleQuoted => match translation() {
+                translation if !translation.contains('\n') => {
+                    format!("'{}'", translation.replace('\'', "''"))
+                }
+                translation => json_string(&translation),
+            },
+            Style::Plain => match translation() {
+                translation if is_plain(&translation) => translation,
+                translation => json_string(&translation),
+            },
+            Style::Block { indent, folded } => {
+                let separator = if *folded { "\n\n" } else { "\n" };
+
+                translation()
+                    .lines()
+                    .map(|line| match line.is_empty() {
+                        true => String::new(),
+                        false => format!("{}{line}", " ".repeat(*indent)),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(separator)
+            }
+            Style::Po { keyword } => format!("{keyword} \"{}\"", po_escape(&translation())),
+            Style::PoPlural { forms } => forms
+                .iter()
+                .enumerate()
+                .map(|(index, text)| {
+                    format!("msgstr[{index}] \"{}\"", po_escape(&translations[*text]))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Style::Fixed(text) => text.clone(),
+        }
+    }
+}
+
+/// an icu plural message, like `{count, plural, one {# file} other {# files}}`, the text around
+/// the message is moved into every variant, so the variants are whole sentences
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct IcuPlural {
+    /// like `count, plural, offset:1`
+    head: String,
+    /// the selector of every case and the index of its text
+    cases: Vec<(String, usize)>,
+}
+
+impl IcuPlural {
+    /// the message and its variants, the cases of the categories of the target rule are generated
+    /// from the variants of the same categories, or `other`, the explicit cases like `=0` are kept
+    fn parse(text: &str, rule: Option<Rule>) -> Option<(Self, Vec<String>)> {
+        static HEAD: OnceLock<Regex> = OnceLock::new();
+
+        let head = HEAD
+            .get_or_init(|| Regex::new(r"\{\s*(\w+)\s*,\s*plural\s*,\s*(offset:\s*\d+)?").unwrap());
+        let captures = head.captures(text)?;
+        let start = captures.get(0)?.start();
+        let argument = &captures[1];
+
+        // the cases until the closing brace of the message
+        let mut source_cases = vec![];
+        let mut rest = &text[captures.get(0)?.end()..];
+        let end = loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix('}') {
+                break text.len() - after.len();
+            }
+
+            let selector_end = rest.find(|c: char| c.is_whitespace() || c == '{')?;
+            let selector = &rest[..selector_end];
+            let case = rest[selector_end..].trim_start().strip_prefix('{')?;
+            let mut depth = 0;
+            let case_end = case.char_indices().find_map(|(index, c)| match c {
+                '{' => {
+                    depth += 1;
+                    None
+                }
+                '}' if depth == 0 => Some(index),
+                '}' => {
+                    depth -= 1;
+                    None
+                }
+                _ => None,
+            })?;
+
+            source_cases.push((selector, &case[..case_end]));
+            rest = &case[case_end + 1..];
+        };
+        if source_cases.is_empty() {
+            return None;
+        }
+
+        let variant = |case: &str| format!("{}{case}{}", &text[..start], &text[end..]);
+        let source = |selector: &str| {
+            source_cases
+                .iter()
+                .find(|(source, _)| *source == selector)
+                .or_else(|| source_cases.iter().find(|(source, _)| *source == "other"))
+                .or(source_cases.last())
+                .map_or("", |(_, case)| *case)
+        };
+        let mut selectors = source_cases
+            .iter()
+            .map(|(selector, _)| selector.to_string())
+            .filter(|selector| {
+                rule.is_none()
+                    || selector.starts_with('=')
+                    || Category::from_keyword(selector).is_none()
+            })
+            .collect::<Vec<_>>();
+        if let Some(rule) = rule {
+            selectors.extend(rule.categories.iter().map(Category::to_string));
+        }
+
+        let mut texts = vec![];
+        let mut cases = vec![];
+        for selector in selectors {
+            let text = variant(source(&selector));
+            let index = match texts.iter().position(|variant| *variant == text) {
+                Some(index) => index,
+                None => {
+                    texts.push(text);
+                    texts.len() - 1
+                }
+            };
+            cases.push((selector, index));
+        }
+
+        let head = match captures.get(2) {
+            None => format!("{argument}, plural,"),
+            Some(offset) => format!("{argument}, plural, {}", offset.as_str()),
+        };
+
+        Some((Self { head, cases }, texts))
+    }
+
+    fn compose(&self, translations: &[String]) -> String {
+        let cases = self
+            .cases
+            .iter()
+            .map(|(selector, index)| format!("{selector} {{{}}}", translations[*index]))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("{{{} {cases}}}", self.head)
+    }
+}
+
+/// find the translatable strings in the order of the content, the plural forms are generated
+/// for the target language, or kept as the source when the target is unknown
+pub fn parse(
+    content: &str,
+    format: ResourceFormat,
+    target: Option<&str>,
+) -> anyhow::Result<Vec<Entry>> {
+    let rule = target.and_then(plural::rule);
+    match format {
+        ResourceFormat::Json => parse_json(content, rule),
+        ResourceFormat::Yaml => Ok(parse_yaml(content, rule)),
+        ResourceFormat::Po => Ok(parse_po(content, rule)),
+    }
+}
+
+/// replace the entries by their translations, return the (original, translated) blocks of whole
+/// lines, which are joined like the blocks of documents
+pub fn replace(content: &str, translated: &[(&Entry, Vec<String>)]) -> Vec<(String, String)> {
+    let body = content.strip_suffix('\n').unwrap_or(content);
+    let line_start = |pos: usize| body[..pos].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = |pos: usize| {
+        body[pos..]
+            .find('\n')
+            .map_or(body.len(), |index| pos + index)
+    };
+
+    let mut blocks = vec![];
+    // the start of the lines which are not in the blocks
+    let mut rest = 0;
+    let mut translated = translated.iter().peekable();
+    while let Some(first) = translated.next() {
+        // the entries sharing lines are in a block
+        let mut group = vec![first];
+        let start = line_start(first.0.range.start);
+        let mut end = line_end(first.0.range.end);
+        while let Some(next) = translated.next_if(|(entry, _)| entry.range.start <= end) {
+            end = end.max(line_end(next.0.range.end));
+            group.push(next);
+        }
+
+        if start > rest {
+            let kept = &body[rest..start - 1];
+            blocks.push((kept.to_string(), kept.to_string()));
+        }
+
+        let mut block = String::new();
+        let mut last = start;
+        for (entry, translation) in group {
+            block.push_str(&body[last..entry.range.start]);
+            block.push_str(&entry.encode(translation));
+            last = entry.range.end;
+        }
+        block.push_str(&body[last..end]);
+        blocks.push((body[start..end].to_string(), block));
+
+        rest = end + 1;
+    }
+
+    if rest <= body.len() {
+        let kept = &body[rest..];
+        blocks.push((kept.to_string(), kept.to_string()));
+    }
+
+    blocks
+}
+
+/// the strings without letters, like numbers and symbols, are kept
+fn is_translatable(text: &str) -> bool {
+    text.chars().any(char::is_alphabetic)
+}
+
+/// the entry of a string, an icu plural message is split into its variants
+fn string_entry(
+    range: Range<usize>,
+    text: String,
+    style: Style,
+    rule: Option<Rule>,
+) -> Option<Entry> {
+    if !is_translatable(&text) {
+        return None;
+    }
+
+    let (icu, texts) = match IcuPlural::parse(&text, rule) {
+        None => (None, vec![text]),
+        Some((icu, texts)) => (Some(icu), texts),
+    };
+
+    Some(Entry {
+        range,
+        texts,
+        context: None,
+        style,
+        icu,
+    })
+}
+
+fn json_string(text: &str) -> String {
+    serde_json::Value::from(text).to_string()
+}
+
+/// the string values of json, a string followed by a colon is a key
+fn parse_json(content: &str, rule: Option<Rule>) -> anyhow::Result<Vec<Entry>> {
+    serde_json::from_str::<IgnoredAny>(content).context("invalid json")?;
+
+    let bytes = content.as_bytes();
+    let mut entries = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'"' {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        index += 1;
+        while bytes[index] != b'"' {
+            if bytes[index] == b'\\' {
+                index += 1;
+            }
+            index += 1;
+        }
+        index += 1;
+
+        if content[index..].trim_start().starts_with(':') {
+            continue;
+        }
+
+        let text = serde_json::from_str::<String>(&content[start..index])?;
+        entries.extend(string_entry(start..index, text, Style::Json, rule));
+    }
+
+    Ok(entries)
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// the scalar values of the mappings and sequences of yaml, a line at a time, the multi line
+/// quoted and plain scalars, anchors, aliases, tags and flow collections are kept
+fn parse_yaml(content: &str, rule: Option<Rule>) -> Vec<Entry> {
+    let mut offset = 0;
+    let mut lines = content
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+
+            (start, line.trim_end_matches(['\r', '\n']))
+        })
+        .peekable();
+
+    let mut entries = vec![];
+    while let Some((start, line)) = lines.next() {
+        let Some(value_start) = yaml_value(line) else {
+            continue;
+        };
+        let value = &line[value_start..];
+
+        if value.starts_with(['|', '>']) {
+            let indent = indentation(line);
+            let mut block = vec![];
+            while let Some((_, next)) = lines.peek() {
+                if !next.trim().is_empty() && indentation(next) <= indent {
+                    break;
+                }
+
+                block.extend(lines.next());
+            }
+            // the trailing blank lines are kept by the chomping indicator
+            while block.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+                block.pop();
+            }
+
+            let (Some(&(first_start, first)), Some(&(last_start, last))) =
+                (block.first(), block.last())
+            else {
+                continue;
+            };
+            let block_indent = indentation(first);
+            let block_lines = block
+                .iter()
+                .map(|(_, line)| line.get(block_indent..).unwrap_or_default())
+                .collect::<Vec<_>>();
+            let folded = value.starts_with('>');
+            let text = match folded {
+                true => fold(&block_lines),
+                false => block_lines.join("\n"),
+            };
+
+            let style = Style::Block {
+                indent: block_indent,
+                folded,
+            };
+            entries.extend(string_entry(
+                first_start..last_start + last.len(),
+                text,
+                style,
+                rule,
+            ));
+
+            continue;
+        }
+
+        if let Some((range, text, style)) = yaml_scalar(value) {
+            let value_start = start + value_start;
+            entries.extend(string_entry(
+                value_start + range.start..value_start + range.end,
+                text,
+                style,
+                rule,
+            ));
+        }
+    }
+
+    entries
+}
+
+/// the start of the value of a `key: value` or `- value` line
+fn yaml_value(line: &str) -> Option<usize> {
+    let mut rest = line.trim_start_matches(' ');
+    if rest.is_empty() || rest.starts_with(['#', '%']) || rest.starts_with("---") {
+        return None;
+    }
+
+    let mut item = false;
+    while let Some(stripped) = rest.strip_prefix("- ") {
+        rest = stripped.trim_start_matches(' ');
+        item = true;
+    }
+
+    let value = match yaml_key_end(rest) {
+        Some(end) => rest[end..].trim_start_matches(' '),
+        None if item => rest,
+        None => return None,
+    };
+
+    (!value.is_empty()).then(|| line.len() - value.len())
+}
+
+/// the end of `key:` at the start of the text
+fn yaml_key_end(text: &str) -> Option<usize> {
+    let is_separator = |end: usize| text[end..].is_empty() || text[end..].starts_with(' ');
+
+    if text.starts_with(['"', '\'']) {
+        let end = quoted_end(text)?;
+
+        return (text[end..].starts_with(':') && is_separator(end + 1)).then_some(end + 1);
+    }
+
+    text.match_indices(':')
+        .map(|(index, _)| index + 1)
+        .find(|&end| is_separator(end))
+}
+
+/// the end of the quoted string at the start of the text, after the closing quote
+fn quoted_end(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' && quote == '"' {
+            chars.next();
+        } else if c == quote {
+            // a quote is escaped by doubling it in single quotes
+            if quote == '\'' && text[index + 1..].starts_with('\'') {
+                chars.next();
+                continue;
+            }
+
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+/// the range, the text and the style of a single line scalar value
+fn yaml_scalar(value: &str) -> Option<(Range<usize>, String, Style)> {
+    if value.starts_with(['"', '\'']) {
+        let end = quoted_end(value)?;
+        let after = value[end..].trim_start();
+        if !after.is_empty() && !after.starts_with('#') {
+            return None;
+        }
+
+        let quoted = &value[..end];
+        return if quoted.starts_with('"') {
+            // the yaml only escapes are rare, the strings with them are kept
+            let text = serde_json::from_str::<String>(quoted).ok()?;
+
+            Some((0..end, text, Style::DoubleQuoted))
+        } else {
+            let text = quoted[1..end - 1].replace("''", "'");
+
+            Some((0..end, text, Style::SingleQuoted))
+        };
+    }
+
+    if value.starts_with(['&', '*', '!', '{', '[', '@', '`', '%', '|', '>']) {
+        return None;
+    }
+
+    let end = value.find(" #").unwrap_or(value.len());
+    let text = value[..end].trim_end();
+    if is_plain_literal(text) {
+        return None;
+    }
+
+    Some((0..text.len(), text.to_string(), Style::Plain))
+}
+
+/// null, booleans and numbers
+fn is_plain_literal(text: &str) -> bool {
+    matches!(
+        text.to_ascii_lowercase().as_str(),
+        "~" | "null" | "true" | "false" | "yes" | "no" | "on" | "off"
+    ) || text.parse::<f64>().is_ok()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E' | '_'))
+}
+
+/// the text can be written as a plain scalar without changing its meaning
+fn is_plain(text: &str) -> bool {
+    !text.is_empty()
+        && text.trim() == text
+        && !text.contains(['\n', '\r'])
+        && !text.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.ends_with(':')
+        && !is_plain_literal(text)
+}
+
+/// the text of a folded block, the line breaks are folded into spaces and a blank line is a line
+/// break
+fn fold(lines: &[&str]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        if line.is_empty() {
+            text.push('\n');
+        } else {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push(' ');
+            }
+            text.push_str(line);
+        }
+    }
+
+    text
+}
+
+/// a keyword of a po entry and its string, with the continuation lines
+#[derive(Debug)]
+struct PoField<'a> {
+    keyword: &'a str,
+    value: String,
+    range: Range<usize>,
+    /// the range and the text of every line
+    lines: Vec<(Range<usize>, &'a str)>,
+}
+
+/// the msgids of the entries with empty msgstrs, the translated entries are kept, the plural
+/// forms header is set to the one of the target
+fn parse_po(content: &str, rule: Option<Rule>) -> Vec<Entry> {
+    let mut entries = vec![];
+    let mut fields = Vec::<PoField>::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim();
+        let range = start + line.len() - line.trim_start().len()..start + line.len();
+
+        if trimmed.starts_with('"') {
+            if let Some(field) = fields.last_mut() {
+                field.value.push_str(&po_unescape(trimmed));
+                field.range.end = range.end;
+                field.lines.push((range, trimmed));
+            }
+
+            continue;
+        }
+
+        match trimmed.split_once(' ') {
+            Some((keyword, string)) if keyword.starts_with("msg") => fields.push(PoField {
+                keyword,
+                value: po_unescape(string.trim()),
+                range: range.clone(),
+                lines: vec![(range, trimmed)],
+            }),
+
+            // blank lines and comments end the entry
+            _ => po_entries(&std::mem::take(&mut fields), rule, &mut entries),
+        }
+    }
+    po_entries(&fields, rule, &mut entries);
+
+    entries
+}
+
+/// the msgstr is translated from msgid, the plural forms of the target are translated from msgid
+/// for `one` and msgid_plural for the others
+fn po_entries(fields: &[PoField], rule: Option<Rule>, entries: &mut Vec<Entry>) {
+    let find = |keyword| fields.iter().find(|field| field.keyword == keyword);
+    let Some(msgid) = find("msgid") else {
+        return;
+    };
+    if msgid.value.is_empty() {
+        entries.extend(rule.and_then(|rule| plural_forms_header(fields, rule)));
+
+        return;
+    }
+    if !is_translatable(&msgid.value) {
+        return;
+    }
+
+    let context = find("msgctxt").map(|field| field.value.clone());
+    let msgstrs = fields
+        .iter()
+        .filter(|field| field.keyword.starts_with("msgstr"))
+        .collect::<Vec<_>>();
+    let (Some(first), Some(last)) = (msgstrs.first(), msgstrs.last()) else {
+        return;
+    };
+    if msgstrs.iter().any(|msgstr| !msgstr.value.is_empty()) {
+        return;
+    }
+
+    let Some(plural) = find("msgid_plural") else {
+        entries.push(Entry {
+            range: first.range.clone(),
+            texts: vec![msgid.value.clone()],
+            context,
+            style: Style::Po {
+                keyword: first.keyword.to_string(),
+            },
+            icu: None,
+        });
+
+        return;
+    };
+
+    // the categories of the plural forms, the source forms are kept if the target is unknown
+    let categories = match rule {
+        Some(rule) => rule.forms.to_vec(),
+        None => (0..msgstrs.len())
+            .map(|index| match index {
+                0 => Category::One,
+                _ => Category::Other,
+            })
+            .collect(),
+    };
+    let mut texts = vec![];
+    let mut forms = vec![];
+    for category in categories {
+        let text = match category {
+            Category::One => &msgid.value,
+            _ => &plural.value,
+        };
+        let index = match texts.iter().position(|variant| variant == text) {
+            Some(index) => index,
+            None => {
+                texts.push(text.clone());
+                texts.len() - 1
+            }
+        };
+        forms.push(index);
+    }
+
+    entries.push(Entry {
+        range: first.range.start..last.range.end,
+        texts,
+        context,
+        style: Style::PoPlural { forms },
+        icu: None,
+    });
+}
+
+/// replace the `Plural-Forms` line of the header, or append it to the header
+fn plural_forms_header(fields: &[PoField], rule: Rule) -> Option<Entry> {
+    let msgstr = fields.iter().find(|field| field.keyword == "msgstr")?;
+    let header = format!("\"Plural-Forms: {}\\n\"", rule.plural_forms);
+    let (range, header) = match msgstr
+        .lines
+        .iter()
+        .find(|(_, line)| line.trim_start_matches('"').starts_with("Plural-Forms:"))
+    {
+        Some((range, _)) => (range.clone(), header),
+        None => (msgstr.range.end..msgstr.range.end, format!("\n{header}")),
+    };
+
+    Some(Entry {
+        range,
+        texts: vec![],
+        context: None,
+        style: Style::Fixed(header),
+        icu: None,
+    })
+}
+
+/// the content of a quoted po string
+fn po_unescape(quoted: &str) -> String {
+    let content = quoted
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(quoted);
+
+    let mut text = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+
+    text
+}
+
+fn po_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate(content: &str, format: ResourceFormat, target: Option<&str>) -> String {
+        let entries = parse(content, format, target).unwrap();
+        let translated = entries
+            .iter()
+            .map(|entry| {
+                let texts = entry.texts.iter().map(|text| text.to_uppercase());
+
+                (entry, texts.collect())
+            })
+            .collect::<Vec<_>>();
+        let blocks = replace(content, &translated);
+        let mut result = blocks
+            .iter()
+            .map(|(_, translated)| translated.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if content.ends_with('\n') {
+            result.push('\n');
+        }
+
+        result
+    }
+
+    #[test]
+    fn translate_json_values() {
+        let content = "{\n  \"title\": \"Hello \\\"you\\\"\",\n  \"version\": \"1.0\",\n  \"items\": [\"a\", \"b\"]\n}\n";
+
+        assert_eq!(
+            translate(content, ResourceFormat::Json, None),
+            "{\n  \"title\": \"HELLO \\\"YOU\\\"\",\n  \"version\": \"1.0\",\n  \"items\": [\"A\", \"B\"]\n}\n"
+        );
+    }
+
+    #[test]
+    fn translate_yaml_values() {
+        let content = "en:\n  # greeting\n  hello: Hello world # comment\n  quoted: 'it''s'\n  count: 3\n  enabled: true\n  list:\n    - first\n    - \"second\"\n  text: |\n    line one\n    line two\n\n  other: &anchor value\n";
+
+        assert_eq!(
+            translate(content, ResourceFormat::Yaml, None),
+            "en:\n  # greeting\n  hello: HELLO WORLD # comment\n  quoted: 'IT''S'\n  count: 3\n  enabled: true\n  list:\n    - FIRST\n    - \"SECOND\"\n  text: |\n    LINE ONE\n    LINE TWO\n\n  other: &anchor value\n"
+        );
+    }
+
+    #[test]
+    fn translate_po_msgids() {
+        let content = "msgid \"\"\nmsgstr \"\"\n\"Language: fr\\n\"\n\n#: main.c:1\nmsgctxt \"button\"\nmsgid \"Book\"\nmsgstr \"\"\n\nmsgid \"Done\"\nmsgstr \"Fait\"\n\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+        let entries = parse(content, ResourceFormat::Po, None).unwrap();
+        assert_eq!(entries[0].context.as_deref(), Some("button"));
+
+        assert_eq!(
+            translate(content, ResourceFormat::Po, None),
+            "msgid \"\"\nmsgstr \"\"\n\"Language: fr\\n\"\n\n#: main.c:1\nmsgctxt \"button\"\nmsgid \"Book\"\nmsgstr \"BOOK\"\n\nmsgid \"Done\"\nmsgstr \"Fait\"\n\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%D FILE\"\nmsgstr[1] \"%D FILES\"\n"
+        );
+    }
+
+    #[test]
+    fn translate_icu_plurals() {
+        let content = "{\n  \"files\": \"Found {count, plural, =0 {no files} one {# file} other {# files}}.\"\n}\n";
+
+        assert_eq!(
+            translate(content, ResourceFormat::Json, Some("ru")),
+            "{\n  \"files\": \"{count, plural, =0 {FOUND NO FILES.} one {FOUND # FILE.} few {FOUND # FILES.} many {FOUND # FILES.} other {FOUND # FILES.}}\"\n}\n"
+        );
+        assert_eq!(
+            translate(content, ResourceFormat::Json, Some("zh")),
+            "{\n  \"files\": \"{count, plural, =0 {FOUND NO FILES.} other {FOUND # FILES.}}\"\n}\n"
+        );
+
+        let entries = parse(content, ResourceFormat::Json, Some("ru")).unwrap();
+        assert_eq!(
+            entries[0].texts,
+            ["Found no files.", "Found # file.", "Found # files."]
+        );
+    }
+
+    #[test]
+    fn translate_po_plural_forms() {
+        let content = "msgid \"\"\nmsgstr \"\"\n\"Language: en\\n\"\n\"Plural-Forms: nplurals=2; plural=(n != 1);\\n\"\n\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+
+        assert_eq!(
+            translate(content, ResourceFormat::Po, Some("zh")),
+            "msgid \"\"\nmsgstr \"\"\n\"Language: en\\n\"\n\"Plural-Forms: nplurals=1; plural=0;\\n\"\n\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%D FILES\"\n"
+        );
+
+        let content = "msgid \"\"\nmsgstr \"\"\n\"Language: en\\n\"\n\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+        let translated = translate(content, ResourceFormat::Po, Some("ru"));
+        assert!(translated.starts_with(
+            "msgid \"\"\nmsgstr \"\"\n\"Language: en\\n\"\n\"Plural-Forms: nplurals=3; plural="
+        ));
+        assert!(translated
+            .ends_with("msgstr[0] \"%D FILE\"\nmsgstr[1] \"%D FILES\"\nmsgstr[2] \"%D FILES\"\n"));
+    }
+}
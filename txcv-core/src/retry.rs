@@ -0,0 +1,190 @@
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use futures_timer::Delay;
+use serde::Deserialize;
+
+use crate::backend::RateLimited;
+use crate::diagnostic;
+
+/// the tencent cloud error code of the rate limit
+const RATE_LIMIT_CODE: &str = "RequestLimitExceeded";
+
+/// how the requests rejected by the rate limit or other transient errors are retried, the delay
+/// grows exponentially with random jitter, so concurrent requests don't retry at the same time
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RetryPolicy {
+    /// milliseconds before the first retry
+    pub initial_delay: u64,
+    /// the delay is multiplied by it after every retry
+    pub multiplier: f64,
+    /// the delay is randomly changed by up to this fraction of it, between 0 and 1
+    pub jitter: f64,
+    /// milliseconds, the delay never grows beyond it
+    pub max_delay: u64,
+    /// give up and return the error after retrying this many times
+    pub max_retries: u32,
+    /// tencent cloud error codes which are retried
+    pub codes: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: 200,
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_delay: 10_000,
+            max_retries: 5,
+            codes: vec![RATE_LIMIT_CODE.to_string()],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// the delay before the retry, counted from 0, random is between 0 and 1
+    pub fn delay(&self, retry: u32, random: f64) -> Duration {
+        let delay = (self.initial_delay as f64 * self.multiplier.powi(retry as _))
+            .min(self.max_delay as f64);
+        let jitter = delay * self.jitter * (random * 2.0 - 1.0);
+
+        Duration::from_millis((delay + jitter).max(0.0) as _)
+    }
+
+    /// the tencent cloud api returns a retryable error code
+    pub fn retryable_api_error(&self, err: &tencentcloud::Error) -> bool {
+        matches!(err, tencentcloud::Error::Api { err, .. } if self.codes.contains(&err.code))
+    }
+
+    /// the request is rejected by the rate limit of the backend, or fails by a retryable error
+    pub fn retryable(&self, err: &anyhow::Error) -> bool {
+        err.is::<RateLimited>()
+            || err
+                .downcast_ref::<tencentcloud::Error>()
+                .is_some_and(|err| self.retryable_api_error(err))
+    }
+
+    /// call f until it succeeds, returns a not retryable error, or the retries are used up,
+    /// waiting for the backoff delay between the calls
+    pub async fn run<Fut, T, E, F, R>(&self, mut f: F, retryable: R) -> Result<T, E>
+    where
+        Fut: Future<Output = Result<T, E>>,
+        F: FnMut() -> Fut,
+        E: Display,
+        R: Fn(&E) -> bool,
+    {
+        let mut retries = 0;
+        loop {
+            match f().await {
+                Err(err) if retries < self.max_retries && retryable(&err) => {
+                    let delay = self.delay(retries, random());
+                    retries += 1;
+                    diagnostic::debug(format_args!(
+                        "retry {retries} of {} in {}ms: {err}",
+                        self.max_retries,
+                        delay.as_millis()
+                    ));
+                    diagnostic::waiting(delay, Delay::new(delay)).await;
+                }
+
+                result => return result,
+            }
+        }
+    }
+}
+
+/// a random number between 0 and 1, the std hasher is randomly seeded for every RandomState
+fn random() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn no_delay() -> RetryPolicy {
+        RetryPolicy {
+            initial_delay: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn exponential_delay() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0, 0.5), Duration::from_millis(200));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(800));
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(640));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_millis(960));
+        assert_eq!(policy.delay(10, 0.5), Duration::from_millis(10_000));
+        assert!((0.0..1.0).contains(&random()));
+    }
+
+    #[async_std::test]
+    async fn retry_until_success() {
+        let attempts = Cell::new(0);
+
+        let result = no_delay()
+            .run(
+                || async {
+                    attempts.set(attempts.get() + 1);
+                    if attempts.get() < 3 {
+                        Err("rate limited")
+                    } else {
+                        Ok("done")
+                    }
+                },
+                |err| *err == "rate limited",
+            )
+            .await;
+
+        assert_eq!(result, Ok("done"));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[async_std::test]
+    async fn not_retryable_error_returns_immediately() {
+        let attempts = Cell::new(0);
+
+        let result = no_delay()
+            .run(
+                || async {
+                    attempts.set(attempts.get() + 1);
+
+                    Err::<(), _>("auth failed")
+                },
+                |err| *err == "rate limited",
+            )
+            .await;
+
+        assert_eq!(result, Err("auth failed"));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[async_std::test]
+    async fn give_up_after_max_retries() {
+        let attempts = Cell::new(0);
+
+        let result = no_delay()
+            .run(
+                || async {
+                    attempts.set(attempts.get() + 1);
+
+                    Err::<(), _>("rate limited")
+                },
+                |err| *err == "rate limited",
+            )
+            .await;
+
+        assert_eq!(result, Err("rate limited"));
+        assert_eq!(attempts.get(), 6);
+    }
+}
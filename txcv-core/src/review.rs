@@ -0,0 +1,55 @@
+use async_std::task;
+use requestty::Question;
+
+use crate::color::Colorize;
+
+#[derive(Debug)]
+pub enum Action {
+    Accept,
+    /// the edited translation
+    Edit(String),
+    /// edit in $EDITOR
+    Editor,
+    Retranslate,
+}
+
+/// show a translated segment and ask what to do with it
+pub async fn ask(
+    index: usize,
+    total: usize,
+    source: String,
+    target: String,
+) -> anyhow::Result<Action> {
+    task::spawn_blocking(move || {
+        println!("{}", format!("[{index}/{total}]").yellow());
+        println!("{}\n{}\n{}", source.blue(), "↓".white(), target.green());
+
+        let question = Question::select("action")
+            .message("review")
+            .choices(["accept", "edit", ":edit in $EDITOR", "retranslate"])
+            .build();
+        let answer = requestty::prompt_one(question)?;
+
+        match answer.as_list_item().map(|item| item.index) {
+            Some(1) => {
+                let question = Question::input("translation")
+                    .message("translation")
+                    .default(target)
+                    .build();
+                let answer = requestty::prompt_one(question)?;
+                let edited = answer
+                    .as_string()
+                    .ok_or_else(|| anyhow::anyhow!("translation is not string"))?;
+
+                Ok(Action::Edit(edited.to_string()))
+            }
+
+            Some(2) => Ok(Action::Editor),
+
+            Some(3) => Ok(Action::Retranslate),
+
+            _ => Ok(Action::Accept),
+        }
+    })
+    .await
+}
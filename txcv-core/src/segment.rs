@@ -0,0 +1,297 @@
+use std::fmt::Debug;
+
+use crate::lang::Language;
+
+/// latin words ending with a dot which don't end a sentence
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "no", "fig",
+];
+
+/// split text into sentences, the sentences keep the surrounding whitespace so joining them
+/// restores the text
+pub trait Segmenter: Debug + Send + Sync {
+    fn split<'a>(&self, text: &'a str) -> Vec<&'a str>;
+}
+
+/// sentences end with punctuation, optionally followed by closing quotes or brackets
+#[derive(Debug)]
+pub struct Punctuation {
+    pub terminators: &'static [char],
+    pub closers: &'static [char],
+    /// quotes which contain whole sentences, like japanese 「」, the text in them is never split
+    pub quotes: &'static [(char, char)],
+    /// latin sentences end only before whitespace, so `3.14` and `example.com` are kept
+    pub needs_space: bool,
+}
+
+/// scripts without punctuation, like thai, use spaces between sentences
+#[derive(Debug)]
+pub struct Whitespace;
+
+pub const LATIN: Punctuation = Punctuation {
+    terminators: &['.', '!', '?', ';', '…'],
+    closers: &['"', '\'', ')', ']', '”', '’'],
+    quotes: &[],
+    needs_space: true,
+};
+
+pub const CHINESE: Punctuation = Punctuation {
+    terminators: &['。', '！', '？', '；', '…', '!', '?'],
+    closers: &['”', '’', '」', '』', '）', '"', ')'],
+    quotes: &[],
+    needs_space: false,
+};
+
+pub const JAPANESE: Punctuation = Punctuation {
+    terminators: &['。', '！', '？', '…', '!', '?'],
+    closers: &['）', ')'],
+    quotes: &[('「', '」'), ('『', '』')],
+    needs_space: false,
+};
+
+/// the segmenter of the language, the language is guessed from the script if it's unknown
+pub fn segmenter(lang: Option<Language>, text: &str) -> &'static dyn Segmenter {
+    match lang {
+        Some(Language::Chinese | Language::TraditionalChinese) => &CHINESE,
+        Some(Language::Japanese) => &JAPANESE,
+        Some(Language::Thai) => &Whitespace,
+        Some(_) => &LATIN,
+        None if text.chars().any(is_kana) => &JAPANESE,
+        None if text.chars().any(is_cjk) => &CHINESE,
+        None => &LATIN,
+    }
+}
+
+/// split the text by the sentence rules of the language
+pub fn sentences(text: &str, lang: Option<Language>) -> Vec<&str> {
+    segmenter(lang, text).split(text)
+}
+
+/// pack the sentences into chunks of at most max_chars characters, a longer sentence is cut at
+/// the last whitespace before the limit, or at the limit if there is none
+pub fn chunks(text: &str, lang: Option<Language>, max_chars: usize) -> Vec<&str> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut chars = 0;
+
+    for sentence in sentences(text, lang) {
+        let sentence_chars = sentence.chars().count();
+        if chars > 0 && chars + sentence_chars > max_chars {
+            let end = sentence.as_ptr() as usize - text.as_ptr() as usize;
+            chunks.push(&text[start..end]);
+            start = end;
+            chars = 0;
+        }
+        chars += sentence_chars;
+
+        while chars > max_chars {
+            let rest = &text[start..];
+            let limit = rest
+                .char_indices()
+                .nth(max_chars)
+                .map_or(rest.len(), |(index, _)| index);
+            let cut = match rest[..limit].rfind(char::is_whitespace) {
+                Some(index) if index > 0 => {
+                    index + rest[index..].chars().next().map_or(0, char::len_utf8)
+                }
+                _ => limit,
+            };
+
+            chunks.push(&rest[..cut]);
+            chars -= rest[..cut].chars().count();
+            start += cut;
+        }
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+
+    chunks
+}
+
+/// split the text into paragraphs separated by blank lines, the blank lines belong to the
+/// previous paragraph so joining them restores the text
+pub fn paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+    let mut after_blank = false;
+
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if after_blank && !blank && offset > start {
+            paragraphs.push(&text[start..offset]);
+            start = offset;
+        }
+        after_blank = blank;
+        offset += line.len();
+    }
+    if start < text.len() {
+        paragraphs.push(&text[start..]);
+    }
+
+    paragraphs
+}
+
+impl Segmenter for Punctuation {
+    fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut sentences = vec![];
+        let mut start = 0;
+        let mut quote_depth = 0usize;
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((index, c)) = chars.next() {
+            if self.quotes.iter().any(|(open, _)| *open == c) {
+                quote_depth += 1;
+            } else if self.quotes.iter().any(|(_, close)| *close == c) {
+                quote_depth = quote_depth.saturating_sub(1);
+            }
+            if quote_depth > 0 || !self.terminators.contains(&c) {
+                continue;
+            }
+
+            // repeated terminators like `?!` and the closers belong to the sentence
+            let mut end = index + c.len_utf8();
+            while let Some(&(index, c)) = chars.peek() {
+                if !self.terminators.contains(&c) && !self.closers.contains(&c) {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+
+            let at_space = text[end..].chars().next().is_none_or(char::is_whitespace);
+            if self.needs_space && (!at_space || is_abbreviation(&text[start..end])) {
+                continue;
+            }
+
+            // the trailing whitespace belongs to the sentence
+            while let Some(&(index, c)) = chars.peek() {
+                if !c.is_whitespace() {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+
+        if start < text.len() {
+            sentences.push(&text[start..]);
+        }
+
+        sentences
+    }
+}
+
+impl Segmenter for Whitespace {
+    fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut sentences = vec![];
+        let mut start = 0;
+        let mut in_space = false;
+
+        for (index, c) in text.char_indices() {
+            if c.is_whitespace() {
+                in_space = true;
+            } else if in_space {
+                sentences.push(&text[start..index]);
+                start = index;
+                in_space = false;
+            }
+        }
+        if start < text.len() {
+            sentences.push(&text[start..]);
+        }
+
+        sentences
+    }
+}
+
+/// whether the sentence ends with an abbreviation like `Mr.` or a single letter initial
+fn is_abbreviation(sentence: &str) -> bool {
+    let word = sentence
+        .trim_end_matches('.')
+        .rsplit(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    word.chars().count() == 1 && word.chars().all(char::is_alphabetic)
+        || ABBREVIATIONS.contains(&word.as_str())
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}')
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latin_sentences() {
+        let text = "Mr. Smith paid $3.14 at example.com. Really?! \"Yes.\" Done";
+
+        assert_eq!(
+            sentences(text, Some(Language::English)),
+            vec![
+                "Mr. Smith paid $3.14 at example.com. ",
+                "Really?! ",
+                "\"Yes.\" ",
+                "Done"
+            ]
+        );
+    }
+
+    #[test]
+    fn cjk_sentences() {
+        assert_eq!(
+            sentences("你好。“真的吗？”是的！", None),
+            vec!["你好。", "“真的吗？”", "是的！"]
+        );
+        assert_eq!(
+            sentences("彼は「行く。待って。」と言った。はい。", None),
+            vec!["彼は「行く。待って。」と言った。", "はい。"]
+        );
+    }
+
+    #[test]
+    fn chunks_within_limit() {
+        let text = "One two. Three four five six seven. Eight.";
+        let packed = chunks(text, Some(Language::English), 12);
+
+        assert_eq!(
+            packed,
+            vec!["One two. ", "Three four ", "five six ", "seven. ", "Eight."]
+        );
+        assert!(packed.iter().all(|chunk| chunk.chars().count() <= 12));
+        assert_eq!(chunks("你好。再见。", None, 100), vec!["你好。再见。"]);
+        assert_eq!(chunks("你好世界", None, 3), vec!["你好世", "界"]);
+    }
+
+    #[test]
+    fn split_paragraphs() {
+        assert_eq!(
+            paragraphs("\nfirst\nline\n\n \nsecond\n\nthird"),
+            vec!["\n", "first\nline\n\n \n", "second\n\n", "third"]
+        );
+        assert!(paragraphs("").is_empty());
+    }
+
+    #[test]
+    fn join_restores_text() {
+        for text in ["a. b.  c", "สวัสดี ครับ  ลาก่อน", "。。", ""]
+        {
+            for lang in [None, Some(Language::Thai), Some(Language::English)] {
+                assert_eq!(sentences(text, lang).concat(), text);
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// common abbreviations and slang which are translated poorly, the expansions are in the same
+/// language so the translator can handle them
+const BUILTIN: &[(&str, &str)] = &[
+    ("afaik", "as far as I know"),
+    ("asap", "as soon as possible"),
+    ("brb", "be right back"),
+    ("btw", "by the way"),
+    ("fyi", "for your information"),
+    ("idk", "I don't know"),
+    ("imho", "in my humble opinion"),
+    ("imo", "in my opinion"),
+    ("irl", "in real life"),
+    ("lmk", "let me know"),
+    ("nvm", "never mind"),
+    ("smh", "shaking my head"),
+    ("tbh", "to be honest"),
+    ("ttyl", "talk to you later"),
+    ("awsl", "啊我死了"),
+    ("bhys", "不好意思"),
+    ("dbq", "对不起"),
+    ("nsdd", "你说得对"),
+    ("xswl", "笑死我了"),
+    ("yyds", "永远的神"),
+    ("zqsg", "真情实感"),
+];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum SlangMode {
+    /// replace the slang with its expansion
+    Expand,
+    /// keep the slang and append its expansion in parentheses
+    Annotate,
+}
+
+impl Display for SlangMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlangMode::Expand => f.write_str("expand"),
+            SlangMode::Annotate => f.write_str("annotate"),
+        }
+    }
+}
+
+/// slang dictionary, keys are matched case-insensitively as whole words
+#[derive(Debug, Clone)]
+pub struct Slang {
+    mode: SlangMode,
+    dictionary: HashMap<String, String>,
+}
+
+impl Slang {
+    /// the custom entries override the builtin ones
+    pub fn new(mode: SlangMode, custom: impl IntoIterator<Item = (String, String)>) -> Self {
+        let dictionary = BUILTIN
+            .iter()
+            .map(|(slang, expansion)| (slang.to_string(), expansion.to_string()))
+            .chain(
+                custom
+                    .into_iter()
+                    .map(|(slang, expansion)| (slang.to_lowercase(), expansion)),
+            )
+            .collect();
+
+        Self { mode, dictionary }
+    }
+
+    /// expand or annotate the slang in text, words are runs of ascii letters and digits, so
+    /// slang mixed in cjk text like `真的yyds` is found too
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric()) {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match self.dictionary.get(&word.to_ascii_lowercase()) {
+                None => result.push_str(word),
+                Some(expansion) => match self.mode {
+                    SlangMode::Expand => result.push_str(expansion),
+                    SlangMode::Annotate => {
+                        result.push_str(word);
+                        result.push_str(" (");
+                        result.push_str(expansion);
+                        result.push(')');
+                    }
+                },
+            }
+
+            rest = &rest[end..];
+        }
+        result.push_str(rest);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_whole_words() {
+        let slang = Slang::new(SlangMode::Expand, []);
+
+        assert_eq!(slang.apply("BRB, grab lunch"), "be right back, grab lunch");
+        assert_eq!(slang.apply("这个真的yyds"), "这个真的永远的神");
+        assert_eq!(slang.apply("tbhx stays"), "tbhx stays");
+    }
+
+    #[test]
+    fn annotate_with_custom_entries() {
+        let slang = Slang::new(
+            SlangMode::Annotate,
+            [("GG".to_string(), "good game".to_string())],
+        );
+
+        assert_eq!(slang.apply("gg, idk"), "gg (good game), idk (I don't know)");
+    }
+}
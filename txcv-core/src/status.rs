@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use tencentcloud::{Auth, Client};
+
+use crate::backend::{Tmt, TranslateBackend};
+use crate::config::RateLimit;
+use crate::credential::Credentials;
+use crate::deepl::Deepl;
+use crate::error::Error;
+use crate::translate::Provider;
+use crate::usage::{self, Usage};
+
+/// the text of the probe request, the language detection of tmt is free, deepl detects by
+/// translating it
+const PROBE_TEXT: &str = "hello";
+/// the response of the probe is small
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+/// the health of a provider, reported by `txcv status`
+#[derive(Debug)]
+pub struct ProviderStatus {
+    pub provider: Provider,
+    pub credentials: String,
+    /// none when the credentials aren't set
+    pub endpoint: Option<String>,
+    pub usage: String,
+    pub rate_limit: String,
+}
+
+pub fn tmt((secret_id, secret_key, region): Credentials) -> Box<dyn TranslateBackend> {
+    let client = Client::new(region, Auth::new(secret_key, secret_id), MAX_RESPONSE_SIZE);
+
+    Box::new(Tmt::new(client, vec![]))
+}
+
+pub fn deepl(api_key: String) -> Box<dyn TranslateBackend> {
+    Box::new(Deepl::new(api_key))
+}
+
+/// probe the provider by a language detection, the backend is an auth error when its
+/// credentials aren't set
+pub async fn check(
+    provider: Provider,
+    backend: anyhow::Result<Box<dyn TranslateBackend>>,
+    rate_limit: RateLimit,
+    usage: &Usage,
+) -> ProviderStatus {
+    let (credentials, endpoint) = match backend {
+        Err(err) => match Error::from(err) {
+            Error::Auth(_) => ("not set".to_string(), None),
+            err => (format!("{err:#}"), None),
+        },
+        Ok(backend) => {
+            let started = Instant::now();
+            let result = backend.detect_language(PROBE_TEXT).await;
+
+            probed(result.map(drop), started.elapsed())
+        }
+    };
+
+    ProviderStatus {
+        provider,
+        credentials,
+        endpoint,
+        usage: month_usage(provider, usage.month_chars(provider)),
+        rate_limit: rate_limit_state(rate_limit),
+    }
+}
+
+/// the credentials and endpoint states of the probe result, an api error means the endpoint is
+/// reachable
+fn probed(result: anyhow::Result<()>, latency: Duration) -> (String, Option<String>) {
+    let reachable = format!("reachable, {}ms", latency.as_millis());
+
+    match result.map_err(Error::from) {
+        Ok(()) => ("valid".to_string(), Some(reachable)),
+        Err(err @ Error::Auth(_)) => (format!("rejected, {err:#}"), Some(reachable)),
+        Err(Error::RateLimited(_)) => (
+            "valid".to_string(),
+            Some(format!("{reachable}, rate limited now")),
+        ),
+        Err(err) => ("unknown".to_string(), Some(format!("unreachable, {err:#}"))),
+    }
+}
+
+fn month_usage(provider: Provider, chars: u64) -> String {
+    match usage::free_tier(provider) {
+        None => format!("{chars} characters this month"),
+        Some(free) => format!(
+            "{chars} of the free {free} characters this month, {:.1}%",
+            usage::percent(chars, free)
+        ),
+    }
+}
+
+fn rate_limit_state(rate_limit: RateLimit) -> String {
+    format!(
+        "{} concurrent, {:.1} requests per second",
+        rate_limit.max_concurrent,
+        1000.0 / rate_limit.refill_interval as f64
+    )
+}
+
+pub fn print<W: Write>(statuses: &[ProviderStatus], mut writer: W) -> anyhow::Result<()> {
+    for status in statuses {
+        writeln!(writer, "{}", status.provider)?;
+        writeln!(writer, "  credentials: {}", status.credentials)?;
+        if let Some(endpoint) = &status.endpoint {
+            writeln!(writer, "  endpoint: {endpoint}")?;
+        }
+        writeln!(writer, "  usage: {}", status.usage)?;
+        writeln!(writer, "  rate limit: {}", status.rate_limit)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_lines() {
+        let rate_limit = RateLimit {
+            max_concurrent: 5,
+            refill_interval: 100,
+            chars_per_token: None,
+        };
+
+        assert_eq!(
+            rate_limit_state(rate_limit),
+            "5 concurrent, 10.0 requests per second"
+        );
+        assert_eq!(
+            month_usage(Provider::Tmt, 1_000_000),
+            "1000000 of the free 5000000 characters this month, 20.0%"
+        );
+        assert_eq!(month_usage(Provider::Dict, 10), "10 characters this month");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::config::Substitution;
+use crate::lang::Language;
+
+/// apply the substitutions matching the language pair of the translation in order
+pub fn apply(
+    substitutions: &[Substitution],
+    text: &str,
+    source_lang: &str,
+    target_lang: &str,
+) -> String {
+    let source_lang = Language::from_code(source_lang);
+    let target_lang = Language::from_code(target_lang);
+
+    substitutions
+        .iter()
+        .filter(|substitution| {
+            substitution
+                .source
+                .is_none_or(|source| Some(source) == source_lang)
+                && substitution
+                    .target
+                    .is_none_or(|target| Some(target) == target_lang)
+        })
+        .fold(text.to_string(), |text, substitution| {
+            substitution
+                .pattern
+                .replace_all(&text, substitution.replacement.as_str())
+                .into_owned()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::*;
+
+    #[test]
+    fn apply_matching_pairs() {
+        let substitutions = [
+            Substitution {
+                source: Some(Language::English),
+                target: Some(Language::Chinese),
+                pattern: Regex::new("使用者").unwrap(),
+                replacement: "用户".to_string(),
+            },
+            Substitution {
+                source: None,
+                target: None,
+                pattern: Regex::new(r"(\d+)\s*%").unwrap(),
+                replacement: "$1%".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            apply(&substitutions, "使用者占 30 %", "en", "zh"),
+            "用户占 30%"
+        );
+        assert_eq!(
+            apply(&substitutions, "使用者占 30 %", "jp", "zh"),
+            "使用者占 30%"
+        );
+    }
+}
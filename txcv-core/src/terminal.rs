@@ -0,0 +1,50 @@
+use std::io::{self, IsTerminal};
+use std::panic;
+#[cfg(unix)]
+use std::process;
+
+use crossterm::{cursor, execute, terminal};
+
+/// restore the terminal state when dropped
+#[derive(Debug)]
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// restore terminal on panic, so an abrupt exit won't leave the terminal in raw mode, the ctrl-c
+/// handler of the application should restore it too
+pub fn install_guard() {
+    let prev_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        prev_hook(info);
+    }));
+}
+
+/// leave raw mode and show the cursor, errors are ignored because the terminal may be broken
+pub fn restore() {
+    let _ = terminal::disable_raw_mode();
+
+    if io::stdout().is_terminal() {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+    }
+}
+
+/// reset a terminal which is left in a bad state by other process
+pub fn reset() -> anyhow::Result<()> {
+    restore();
+
+    #[cfg(unix)]
+    if io::stdin().is_terminal() {
+        process::Command::new("stty")
+            .arg("sane")
+            .stdin(process::Stdio::inherit())
+            .status()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,3721 @@
+#[cfg(feature = "clipboard")]
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::future::{ready, Future};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use async_std::io;
+use async_std::task;
+use base64::prelude::{Engine, BASE64_STANDARD};
+#[cfg(feature = "tui")]
+use crossterm::terminal;
+use encoding_rs::UTF_8;
+#[cfg(feature = "clipboard")]
+use futures_util::future;
+use futures_util::stream;
+use futures_util::{AsyncReadExt, StreamExt, TryStreamExt};
+#[cfg(feature = "interactive")]
+use requestty::Question;
+#[cfg(feature = "interactive")]
+use rustyline::error::ReadlineError;
+#[cfg(feature = "interactive")]
+use rustyline::history::FileHistory;
+#[cfg(feature = "interactive")]
+use rustyline::Editor;
+use serde::{Deserialize, Serialize};
+use tencentcloud::{Auth, Client};
+
+use crate::api::file_translate::{
+    FileTranslate, FileTranslateRequest, GetFileTranslate, GetFileTranslateRequest, TaskState,
+    SOURCE_TYPE_DATA,
+};
+use crate::api::image_translate::{ImageTranslate, ImageTranslateRequest, ItemValue};
+use crate::api::speech_translate::{
+    SpeechTranslate, SpeechTranslateRequest, SpeechTranslateResponse, AUDIO_FORMAT_PCM,
+    RECOGNIZE_STATUS_DONE,
+};
+use crate::atomic;
+use crate::backend::{Backend, TextTooLong, Tmt, TranslateBackend};
+use crate::cache::{self, Cache};
+use crate::cancel::Cancelled;
+use crate::card::{self, Cards};
+#[cfg(feature = "clipboard")]
+use crate::clipboard;
+use crate::color::{Color, Colorize};
+use crate::config::{RateLimit, Substitution};
+#[cfg(feature = "credential-store")]
+use crate::credential::Profile;
+use crate::credential::{self, Credentials, Overrides};
+use crate::daemon::{self, Request};
+use crate::deepl::Deepl;
+use crate::diagnostic;
+use crate::dict::{self, Dict};
+use crate::diff;
+#[cfg(feature = "interactive")]
+use crate::disambiguate::{self, Choices};
+use crate::document::{self, Block, Directives, Format, FormatMarkers, Span};
+use crate::dry_run::DryRun;
+#[cfg(feature = "interactive")]
+use crate::editor;
+use crate::encoding::{self, BomStyle, FileStyle, NewlineStyle};
+use crate::error::Error;
+use crate::exec;
+use crate::explain::Explainer;
+use crate::filter::{self, Filter};
+use crate::glossary::Glossary;
+#[cfg(feature = "interactive")]
+use crate::glossary::Term;
+use crate::history::{self, History};
+use crate::inline_image::{self, ImageProtocol};
+use crate::interrupt::{self, Interrupted};
+use crate::lang::{self, AutoTarget, Language};
+#[cfg(feature = "interactive")]
+use crate::learn::{self, Corrections};
+use crate::memory::{MemoryMode, Segment, TranslationMemory};
+use crate::normalize::{self, Normalization};
+#[cfg(feature = "notify")]
+use crate::notify;
+use crate::output::{self, Output, Summary};
+#[cfg(feature = "tui")]
+use crate::pager;
+#[cfg(feature = "wasm-plugin")]
+use crate::plugin::WasmPlugin;
+use crate::protect;
+use crate::qa::{self, QaFailed, QaFormat};
+use crate::rate_limit::{LeakyBucket, NoticeClock};
+#[cfg(feature = "interactive")]
+use crate::repl;
+use crate::resource::{self, ResourceFormat};
+use crate::retry::RetryPolicy;
+#[cfg(feature = "interactive")]
+use crate::review::{self, Action};
+use crate::segment;
+use crate::slang::Slang;
+use crate::substitute;
+#[cfg(feature = "interactive")]
+use crate::terminal::TerminalGuard;
+use crate::usage::{self, Metered, Usage};
+#[cfg(feature = "clipboard")]
+use crate::watch::{self, Queue, Watcher};
+use crate::wav;
+use crate::whitespace;
+
+const MAX_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+/// interval of querying the document translation task status
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// the shorter waits for the rate limiter are not printed by `-v`
+const MIN_LOGGED_WAIT: Duration = Duration::from_millis(1);
+/// wait after a failed accept of the daemon, an error like too many open files fails at once again
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+/// the image translation only accepts png and jpg
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_SIGNATURE: &[u8] = &[0xff, 0xd8, 0xff];
+#[cfg(feature = "interactive")]
+const REPL_HISTORY_FILE: &str = "repl_history.txt";
+
+#[derive(Debug)]
+pub enum Mode {
+    Batch(Vec<String>),
+    Interact,
+    FromStdin,
+    #[cfg(feature = "clipboard")]
+    Clipboard,
+    /// translate a docx, pdf or xlsx document by the document translation task
+    File(PathBuf),
+    /// recognize and translate the text lines of a png or jpg image
+    Image(PathBuf),
+    /// recognize and translate the speech of a wav file
+    Audio(PathBuf),
+    /// translate a text file by paragraphs, output is none when printing to stdout
+    InputFile {
+        input: PathBuf,
+        output: Option<PathBuf>,
+    },
+    /// output is none when printing diffs
+    Dir {
+        input: PathBuf,
+        output: Option<PathBuf>,
+    },
+    /// run the command and translate its stderr, and stdout if all is true
+    Exec {
+        command: Vec<String>,
+        all: bool,
+    },
+    /// answer the json line requests of the local socket until killed
+    Serve,
+    /// translate the newly copied text until killed
+    #[cfg(feature = "clipboard")]
+    Watch {
+        primary: bool,
+        debounce: Duration,
+        max_chars: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Translation {
+    pub source_lang: String,
+    pub target_lang: String,
+    pub text: String,
+}
+
+impl Translation {
+    /// the languages are not detected, like a memory match, use the specified ones or `auto`
+    fn undetected(text: String, source: Option<Language>, target: Option<Language>) -> Self {
+        Self {
+            source_lang: source.map_or("auto", |source| source.as_str()).to_string(),
+            target_lang: target.map_or("auto", |target| target.as_str()).to_string(),
+            text,
+        }
+    }
+}
+
+/// where a translation comes from
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// tencent machine translation
+    Tmt,
+    /// deepl
+    Deepl,
+    /// offline dictionary
+    Dict,
+    /// local translation memory
+    Memory,
+    /// local translation cache
+    Cache,
+    /// whitespace only text is kept as is
+    Untranslated,
+    /// edited by user in review
+    #[cfg(feature = "interactive")]
+    Human,
+}
+
+impl Display for Provider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provider::Tmt => f.write_str("tmt"),
+            Provider::Deepl => f.write_str("deepl"),
+            Provider::Dict => f.write_str("dict"),
+            Provider::Memory => f.write_str("memory"),
+            Provider::Cache => f.write_str("cache"),
+            Provider::Untranslated => f.write_str("untranslated"),
+            #[cfg(feature = "interactive")]
+            Provider::Human => f.write_str("human"),
+        }
+    }
+}
+
+/// metadata of a translated document segment
+#[derive(Debug, Serialize)]
+struct SegmentRecord<'a> {
+    file: &'a Path,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<&'a Path>,
+    #[serde(flatten)]
+    span: Span,
+    source: &'a str,
+    target: &'a str,
+    provider: Provider,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub color: Color,
+    pub concise: bool,
+    pub memory_mode: MemoryMode,
+    pub memory_threshold: u8,
+    pub glossary: Option<Glossary>,
+    /// where the terms learned from user edits are added
+    #[cfg(feature = "interactive")]
+    pub glossary_path: Option<PathBuf>,
+    pub qa: Option<QaFormat>,
+    /// disable all prompts, read credentials from env and print json lines
+    pub ci: bool,
+    /// override the default ignore region markers of formats
+    pub ignore_markers: Vec<FormatMarkers>,
+    pub backend: Backend,
+    /// tencent terminology repository ids, the terms are enforced by server
+    pub term_repos: Vec<String>,
+    /// write translated files in the original encoding instead of utf-8
+    pub keep_encoding: bool,
+    pub newline: NewlineStyle,
+    pub bom: BomStyle,
+    #[cfg(feature = "wasm-plugin")]
+    pub plugins: Vec<WasmPlugin>,
+    /// the credentials of the profile in the store are used
+    #[cfg(feature = "credential-store")]
+    pub profile: Profile,
+    /// allow saving the asked credentials to the store
+    #[cfg(feature = "credential-store")]
+    pub save_credentials: bool,
+    /// warn when credentials are older than it
+    #[cfg(feature = "credential-store")]
+    pub credential_max_age: Duration,
+    /// never write the store, return error instead of asking credentials
+    #[cfg(feature = "credential-store")]
+    pub read_only: bool,
+    /// normalizations applied to source text before translating
+    pub normalizations: Vec<Normalization>,
+    /// filters applied to source text after the normalizations
+    pub filters: Vec<Filter>,
+    /// a hint of where the texts are used, sent to the backend to disambiguate short texts
+    pub context: Option<String>,
+    /// expand slang in source text before translating
+    pub slang: Option<Slang>,
+    /// explain grammar points and idioms below the translation
+    pub explainer: Option<Explainer>,
+    /// show the dictionary card of single english words below the translation
+    pub cards: Option<Cards>,
+    /// translate single words by the offline dictionary only
+    pub offline: bool,
+    /// offline dictionary, also used when the network is unavailable
+    pub dict: Option<PathBuf>,
+    /// regex replacements of translations
+    pub substitutions: Vec<Substitution>,
+    /// target languages of the detected source languages
+    pub auto_targets: Vec<AutoTarget>,
+    /// put the translations on the clipboard
+    #[cfg(feature = "clipboard")]
+    pub copy: bool,
+    /// draw the image in the terminal by the protocol
+    pub show_image: Option<ImageProtocol>,
+    /// show the translations as desktop notifications
+    #[cfg(feature = "notify")]
+    pub notify: bool,
+    /// print json metadata of every translated segment in document modes
+    pub segment_metadata: bool,
+    pub output: Output,
+    /// flush stdout after every translation
+    pub flush: bool,
+    /// printed after every translation, default is the newline
+    pub record_sep: Option<String>,
+    /// print the planned requests instead of sending them
+    pub dry_run: bool,
+    /// percent of the free characters of a month, a notice is printed after the run reaching
+    /// it, 0 disables the notice
+    pub usage_warning: u8,
+    /// credentials passed by the command line, environment variables or config
+    pub credentials: Overrides,
+    /// record translated queries to the history
+    pub history: bool,
+    /// cache translation results locally
+    pub cache: bool,
+    /// days before a cached translation expires
+    pub cache_ttl_days: u64,
+    /// default is the rate limit of the backend
+    pub rate_limit: Option<RateLimit>,
+    /// default is the max characters of the backend
+    pub max_chars: Option<usize>,
+    /// how the rate limited requests are retried
+    pub retry: RetryPolicy,
+    /// deadline of a request in batch, daemon and watch modes
+    pub timeout: Option<Duration>,
+    /// print the counts of batch runs
+    pub summary: bool,
+    /// review translated segments of documents before writing
+    #[cfg(feature = "interactive")]
+    pub review: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Translate {
+    backend: Arc<dyn TranslateBackend>,
+    color: Color,
+    concise: bool,
+    memory: Option<Arc<Mutex<TranslationMemory>>>,
+    memory_mode: MemoryMode,
+    cache: Option<Cache>,
+    history: Option<History>,
+    memory_threshold: u8,
+    glossary: Option<Arc<Glossary>>,
+    #[cfg(feature = "interactive")]
+    glossary_path: Option<PathBuf>,
+    qa: Option<QaFormat>,
+    ci: bool,
+    ignore_markers: Arc<Vec<FormatMarkers>>,
+    term_repos: Arc<Vec<String>>,
+    keep_encoding: bool,
+    newline: NewlineStyle,
+    bom: BomStyle,
+    #[cfg(feature = "wasm-plugin")]
+    plugins: Arc<Vec<Mutex<WasmPlugin>>>,
+    normalizations: Arc<Vec<Normalization>>,
+    filters: Arc<Vec<Filter>>,
+    context: Option<Arc<str>>,
+    slang: Option<Arc<Slang>>,
+    explainer: Option<Explainer>,
+    cards: Option<Cards>,
+    /// the fallback offline dictionary, none in offline mode
+    dict_path: Option<PathBuf>,
+    /// loaded when it's used at first
+    dict: Arc<OnceLock<Dict>>,
+    substitutions: Arc<Vec<Substitution>>,
+    auto_targets: Arc<Vec<AutoTarget>>,
+    #[cfg(feature = "clipboard")]
+    copy: bool,
+    show_image: Option<ImageProtocol>,
+    #[cfg(feature = "notify")]
+    notify: bool,
+    segment_metadata: bool,
+    output: Output,
+    flush: bool,
+    record_sep: Arc<str>,
+    dry_run: Option<Arc<DryRun>>,
+    /// counts the characters sent to tmt and deepl, saved to the monthly usage after a run
+    usage: Option<Arc<Metered>>,
+    usage_warning: u8,
+    #[cfg(feature = "interactive")]
+    review: bool,
+    rate_limit: RateLimit,
+    /// the rate limiter of all modes, built once and shared by the clones, so the requests of the
+    /// daemon connections or the chunks of a text don't get their own bucket
+    limiter: LeakyBucket,
+    /// the translations of the batch api of a batch run, by the texts sent to the backend
+    batched: Arc<HashMap<String, Translation>>,
+    /// max characters of a request, shrunk when the backend rejects a shorter text
+    max_chars: Arc<AtomicUsize>,
+    retry: Arc<RetryPolicy>,
+    timeout: Option<Duration>,
+    summary: bool,
+}
+
+impl Translate {
+    pub async fn new(from_stdin: bool, options: Options) -> anyhow::Result<Translate> {
+        // no credentials are needed to plan the requests
+        if options.dry_run {
+            let dry_run = Arc::new(DryRun::new(if options.offline {
+                &dict::CAPABILITIES
+            } else {
+                options.backend.capabilities()
+            }));
+
+            return Ok(Self {
+                dry_run: Some(dry_run.clone()),
+                ..Self::with_backend(dry_run, options)?
+            });
+        }
+
+        let backend: Arc<dyn TranslateBackend> = match options.backend {
+            _ if options.offline => {
+                let path = options.dict.as_deref().context(
+                    "offline mode needs a dictionary, pass --dict or put ecdict.csv under the txcv \
+                     data dir",
+                )?;
+
+                Arc::new(Dict::load(path)?)
+            }
+            Backend::Tmt => {
+                let (secret_id, secret_key, region) =
+                    Self::credentials(from_stdin, &options).await?;
+                let client =
+                    Client::new(region, Auth::new(secret_key, secret_id), MAX_RESPONSE_SIZE);
+
+                Arc::new(Tmt::new(client, options.term_repos.clone()))
+            }
+            Backend::Deepl => {
+                Arc::new(Deepl::new(Self::deepl_api_key(from_stdin, &options).await?))
+            }
+        };
+        // the offline dictionary has no quota
+        if options.offline {
+            return Self::with_backend(backend, options);
+        }
+
+        let usage = Arc::new(Metered::new(backend));
+
+        Ok(Self {
+            usage: Some(usage.clone()),
+            ..Self::with_backend(usage, options)?
+        })
+    }
+
+    /// create with the backend, the credentials of the backend are ready
+    pub fn with_backend(
+        backend: Arc<dyn TranslateBackend>,
+        options: Options,
+    ) -> anyhow::Result<Translate> {
+        let memory = match options.memory_mode {
+            MemoryMode::Off => None,
+            _ => Some(Arc::new(Mutex::new(TranslationMemory::open()?))),
+        };
+
+        // the cache dir is not writable, translate without it
+        let cache = if options.cache {
+            match Cache::open(options.cache_ttl_days) {
+                Err(err) => {
+                    diagnostic::warn(format!("{err:#}, cache is disabled"));
+
+                    None
+                }
+                Ok(cache) => Some(cache),
+            }
+        } else {
+            None
+        };
+
+        let history = if options.history {
+            Some(History::open()?)
+        } else {
+            None
+        };
+
+        let rate_limit = options.rate_limit.unwrap_or_else(|| backend.rate_limit());
+        // full at first
+        let limiter = LeakyBucket::builder()
+            .max(rate_limit.max_concurrent)
+            .refill_interval(rate_limit.refill_interval())
+            .tokens(rate_limit.max_concurrent)
+            .chars_per_token(rate_limit.chars_per_token)
+            .clock(NoticeClock)
+            .build();
+        let max_chars = options
+            .max_chars
+            .or_else(|| backend.max_chars())
+            .unwrap_or(usize::MAX);
+
+        Ok(Self {
+            backend,
+            color: if options.ci {
+                Color::Disable
+            } else {
+                options.color
+            },
+            concise: options.concise,
+            memory,
+            memory_mode: options.memory_mode,
+            cache,
+            history,
+            memory_threshold: options.memory_threshold,
+            glossary: options.glossary.map(Arc::new),
+            #[cfg(feature = "interactive")]
+            glossary_path: options.glossary_path,
+            qa: options.qa,
+            ci: options.ci,
+            ignore_markers: Arc::new(options.ignore_markers),
+            term_repos: Arc::new(options.term_repos),
+            keep_encoding: options.keep_encoding,
+            newline: options.newline,
+            bom: options.bom,
+            #[cfg(feature = "wasm-plugin")]
+            plugins: Arc::new(options.plugins.into_iter().map(Mutex::new).collect()),
+            normalizations: Arc::new(options.normalizations),
+            filters: Arc::new(options.filters),
+            context: options.context.map(Arc::from),
+            slang: options.slang.map(Arc::new),
+            explainer: options.explainer,
+            cards: options.cards,
+            dict_path: options.dict.filter(|_| !options.offline),
+            dict: Arc::new(OnceLock::new()),
+            substitutions: Arc::new(options.substitutions),
+            auto_targets: Arc::new(options.auto_targets),
+            #[cfg(feature = "clipboard")]
+            copy: options.copy,
+            show_image: options.show_image,
+            #[cfg(feature = "notify")]
+            notify: options.notify,
+            segment_metadata: options.segment_metadata,
+            flush: options.flush,
+            dry_run: None,
+            usage: None,
+            usage_warning: options.usage_warning,
+            record_sep: Arc::from(options.record_sep.as_deref().unwrap_or("\n")),
+            output: options.output,
+            rate_limit,
+            limiter,
+            batched: Default::default(),
+            max_chars: Arc::new(AtomicUsize::new(max_chars)),
+            retry: Arc::new(options.retry),
+            timeout: options.timeout,
+            summary: options.summary,
+            #[cfg(feature = "interactive")]
+            review: options.review,
+        })
+    }
+
+    #[cfg(feature = "credential-store")]
+    async fn credentials(from_stdin: bool, options: &Options) -> anyhow::Result<Credentials> {
+        let overrides = &options.credentials;
+        if let Some(credentials) = overrides.complete() {
+            Ok(credentials)
+        } else if options.ci {
+            overrides.require()
+        } else if options.read_only {
+            credential::stored(&options.profile, overrides)
+        } else {
+            credential::get_or_ask(
+                &options.profile,
+                overrides,
+                from_stdin,
+                options.save_credentials,
+                options.credential_max_age,
+            )
+            .await
+        }
+    }
+
+    /// without credential store, credentials can only be passed by the command line or
+    /// environment variables
+    #[cfg(not(feature = "credential-store"))]
+    async fn credentials(_from_stdin: bool, options: &Options) -> anyhow::Result<Credentials> {
+        options.credentials.require()
+    }
+
+    #[cfg(feature = "credential-store")]
+    async fn deepl_api_key(from_stdin: bool, options: &Options) -> anyhow::Result<String> {
+        if options.ci {
+            credential::deepl_from_env()
+        } else if options.read_only {
+            credential::stored_deepl(&options.profile)
+        } else {
+            credential::get_or_ask_deepl(&options.profile, from_stdin, options.save_credentials)
+                .await
+        }
+    }
+
+    #[cfg(not(feature = "credential-store"))]
+    async fn deepl_api_key(_from_stdin: bool, _options: &Options) -> anyhow::Result<String> {
+        credential::deepl_from_env()
+    }
+
+    pub async fn run(
+        &mut self,
+        mode: Mode,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let result = self.run_mode(mode, source, target).await;
+        if let Some(dry_run) = &self.dry_run {
+            diagnostic::info(dry_run.summary(self.rate_limit));
+        }
+        let metrics = self.limiter.metrics();
+        if metrics.waits > 0 {
+            diagnostic::debug(metrics);
+        }
+        self.record_usage();
+
+        result
+    }
+
+    /// add the characters sent by the run to the monthly usage, a notice is printed when the
+    /// month reaches the warning percent of the free tier, the usage isn't worth failing the run
+    fn record_usage(&self) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        let chars = usage.take();
+        if chars == 0 {
+            return;
+        }
+
+        let provider = self.backend.provider();
+        match Usage::open().and_then(|mut store| store.record(provider, chars as _)) {
+            Err(err) => diagnostic::warn(format!("{err:#}, usage isn't recorded")),
+            Ok(month_chars) => {
+                if let Some(notice) = usage::notice(provider, month_chars, self.usage_warning) {
+                    diagnostic::warn(notice);
+                }
+            }
+        }
+    }
+
+    async fn run_mode(
+        &mut self,
+        mode: Mode,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        match mode {
+            Mode::Batch(words) => self.run_batch(words, source, target).await,
+            Mode::Interact if self.ci => Err(anyhow::anyhow!(
+                "interactive mode is disabled in ci mode, pass words or pipe text to stdin"
+            )),
+            Mode::Interact if !std::io::stdout().is_terminal() => Err(anyhow::anyhow!(
+                "interactive mode needs stdin and stdout to be terminals, pass words as arguments \
+                 or pipe text to stdin"
+            )),
+            #[cfg(feature = "interactive")]
+            Mode::Interact => self.run_interact(source, target).await,
+            #[cfg(not(feature = "interactive"))]
+            Mode::Interact => Err(anyhow::anyhow!(
+                "txcv is built without the interactive feature, pass words as arguments or pipe \
+                 text to stdin"
+            )),
+            Mode::FromStdin => self.run_from_stdin(source, target).await,
+            #[cfg(feature = "clipboard")]
+            Mode::Clipboard => self.run_clipboard(source, target).await,
+            // documents are not queries, don't flood the history
+            Mode::File(input) => {
+                self.history = None;
+
+                self.run_file(&input, source, target).await
+            }
+            Mode::Image(input) => self.run_image(&input, source, target).await,
+            Mode::Audio(input) => {
+                self.history = None;
+
+                self.run_audio(&input, source, target).await
+            }
+            Mode::InputFile { input, output } => {
+                self.history = None;
+
+                self.run_input_file(&input, output.as_deref(), source, target)
+                    .await
+            }
+            Mode::Dir { input, output } => {
+                self.history = None;
+
+                self.run_dir(&input, output.as_deref(), source, target)
+                    .await
+            }
+            Mode::Exec { command, all } => {
+                self.history = None;
+
+                self.run_exec(&command, all, source, target).await
+            }
+            Mode::Serve => self.run_serve().await,
+            #[cfg(feature = "clipboard")]
+            Mode::Watch {
+                primary,
+                debounce,
+                max_chars,
+            } => {
+                self.run_watch(primary, debounce, max_chars, source, target)
+                    .await
+            }
+        }
+    }
+
+    async fn run_batch(
+        &self,
+        words: Vec<String>,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let started = Instant::now();
+        let mut summary = Summary::default();
+        let mut segments = vec![];
+        let result = self
+            .translate_words(words, source, target, &mut summary, &mut segments)
+            .await;
+        self.print_summary(summary, &result, started);
+        result?;
+
+        #[cfg(feature = "clipboard")]
+        self.copy_to_clipboard(segments.iter().map(|(_, translated)| translated))?;
+        self.check_segments(&segments)
+    }
+
+    /// translate and print the words of a batch run in order, counted by the summary
+    async fn translate_words(
+        &self,
+        words: Vec<String>,
+        source: Option<Language>,
+        target: Option<Language>,
+        summary: &mut Summary,
+        segments: &mut Vec<(String, String)>,
+    ) -> anyhow::Result<()> {
+        // the words go through the memory, the glossary and the cache like a single text, the
+        // misses are translated by the batch api first and taken from it instead of the backend
+        let translate = &Self {
+            batched: Arc::new(self.translate_batched(&words, source, target).await?),
+            ..self.clone()
+        };
+
+        ordered_batch(
+            words,
+            self.concurrency(),
+            |word| async move {
+                let (translation, provider) = translate
+                    .limited(&word, || {
+                        translate.translate_with_provider(word.clone(), source, target, false)
+                    })
+                    .await?;
+                let note = translate.explanation(&word, &translation).await?;
+
+                Ok((word, (translation, note, provider)))
+            },
+            |word, (translation, note, provider)| {
+                self.print(&word, &translation);
+                if let Some(note) = note {
+                    self.print_note(&note);
+                }
+                summary.count(&word, provider);
+                segments.push((word, translation.text));
+            },
+        )
+        .await
+    }
+
+    async fn run_from_stdin(
+        &self,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf).await?;
+        // stdin of a program started by gui launcher is not a terminal and is empty
+        if buf.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "stdin is not a terminal and has no text, pass words as arguments, pipe text to \
+                 stdin or run txcv in a terminal for interactive mode"
+            ));
+        }
+
+        let translation = self.translate_chunked(buf.clone(), source, target).await?;
+        self.print(&buf, &translation);
+        self.explain(&buf, &translation).await?;
+        #[cfg(feature = "clipboard")]
+        self.copy_to_clipboard([&translation.text])?;
+
+        // align segments by lines when translation keeps the line structure, or by sentences
+        let translated = translation.text.as_str();
+        let source_lines = buf.lines().collect::<Vec<_>>();
+        let translated_lines = translated.lines().collect::<Vec<_>>();
+        let source_sentences =
+            segment::sentences(&buf, Language::from_code(&translation.source_lang));
+        let translated_sentences =
+            segment::sentences(translated, Language::from_code(&translation.target_lang));
+        if source_lines.len() == translated_lines.len() {
+            let segments = source_lines
+                .into_iter()
+                .zip(translated_lines)
+                .collect::<Vec<_>>();
+            self.check_segments(&segments)
+        } else if source_sentences.len() == translated_sentences.len() {
+            let segments = source_sentences
+                .into_iter()
+                .map(str::trim)
+                .zip(translated_sentences.into_iter().map(str::trim))
+                .collect::<Vec<_>>();
+            self.check_segments(&segments)
+        } else {
+            self.check_segments(&[(buf.as_str(), translated)])
+        }
+    }
+
+    /// translate the paragraphs of a text file concurrently under the rate limiter, long
+    /// paragraphs are split by the max characters of a request, the blank lines between
+    /// paragraphs are kept. the json, yaml and po resource files are translated by strings
+    async fn run_input_file(
+        &self,
+        input: &Path,
+        output: Option<&Path>,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let data = async_std::fs::read(input)
+            .await
+            .with_context(|| format!("read {} failed", input.display()))?;
+        let (content, file_style) = encoding::decode(&data);
+        let content = content.replace("\r\n", "\n");
+
+        let started = Instant::now();
+        let mut summary = Summary::default();
+        let mut translated = String::new();
+        let mut segments = vec![];
+        let result = match ResourceFormat::from_path(input) {
+            // a partly translated resource is invalid, nothing is printed when it's interrupted
+            Some(format) => self
+                .translate_resource(&content, format, source, target, &mut summary)
+                .await
+                .map(|(blocks, resource_segments)| {
+                    translated = document::join(
+                        &blocks
+                            .iter()
+                            .map(|(_, translated)| translated)
+                            .collect::<Vec<_>>(),
+                        &content,
+                    );
+                    segments = resource_segments;
+                }),
+
+            None => {
+                let paragraphs = segment::paragraphs(&content)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+
+                ordered_batch(
+                    paragraphs,
+                    self.concurrency(),
+                    |paragraph| async {
+                        let translation = self
+                            .limited(&paragraph, || {
+                                self.translate_with_provider(
+                                    paragraph.clone(),
+                                    source,
+                                    target,
+                                    false,
+                                )
+                            })
+                            .await?;
+
+                        Ok((paragraph, translation))
+                    },
+                    |paragraph, (translation, provider)| {
+                        summary.count(&paragraph, provider);
+                        translated.push_str(&translation.text);
+                        segments.push((paragraph, translation.text));
+                    },
+                )
+                .await
+            }
+        };
+        self.print_summary(summary, &result, started);
+        // the interrupted file is left untouched, the translated paragraphs are printed
+        if let Err(err) = &result {
+            if err.is::<Interrupted>() && output.is_none() && self.dry_run.is_none() {
+                print!("{translated}");
+            }
+        }
+        result?;
+        if self.dry_run.is_some() {
+            return Ok(());
+        }
+
+        match output {
+            None => print!("{translated}"),
+            Some(output) => {
+                let translated = encoding::encode(
+                    &translated,
+                    file_style.with(self.newline, self.bom),
+                    self.keep_encoding,
+                );
+                atomic::write(output, &translated)
+                    .with_context(|| format!("write {} failed", output.display()))?;
+            }
+        }
+
+        self.check_segments(&segments)
+    }
+
+    /// print the summary to stderr if it's enabled, a failed batch stops at the first failure, an
+    /// interrupted one isn't failed
+    fn print_summary(&self, mut summary: Summary, result: &anyhow::Result<()>, started: Instant) {
+        if !self.summary {
+            return;
+        }
+
+        summary.failed = usize::from(matches!(result, Err(err) if !err.is::<Interrupted>()));
+        eprintln!("{}", summary.line(started.elapsed()).dimmed());
+    }
+
+    /// translate the output of the command line by line as it's printed, the untranslated lines
+    /// are passed through, return ChildFailed if the command fails
+    async fn run_exec(
+        &self,
+        command: &[String],
+        all: bool,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let (child, lines) = exec::spawn(command)?;
+
+        let mut warned = false;
+        while let Ok((stream, line)) = lines.recv().await {
+            let line = if line.trim().is_empty() || (stream == exec::Stream::Stdout && !all) {
+                line
+            } else {
+                match self
+                    .limited(&line, || {
+                        self.translate_text(line.clone(), source, target, false)
+                    })
+                    .await
+                {
+                    Ok(translation) => translation.text,
+                    // the output of the command must not be lost
+                    Err(err) => {
+                        if !warned {
+                            diagnostic::warn(format!("translate failed: {err}"));
+                            warned = true;
+                        }
+
+                        line
+                    }
+                }
+            };
+
+            match stream {
+                exec::Stream::Stdout => println!("{line}"),
+                exec::Stream::Stderr => eprintln!("{line}"),
+            }
+        }
+
+        task::spawn_blocking(move || exec::wait(child)).await
+    }
+
+    /// keep the client, the rate limiter and the cache warm for the clients of the socket, the
+    /// connections are served concurrently
+    async fn run_serve(&self) -> anyhow::Result<()> {
+        let listener = daemon::bind().await?;
+        diagnostic::info(format!("listening on {}", daemon::address()?));
+
+        // ctrl-c stops accepting connections, the in-flight requests of the served connections
+        // are cancelled by their tokens
+        let accept = async {
+            let mut incoming = listener.incoming();
+            while let Some(stream) = incoming.next().await {
+                // a failed accept, e.g. too many open files, is only logged
+                let stream = match stream {
+                    Err(err) => {
+                        diagnostic::warn(format!("accept connection failed: {err}"));
+                        task::sleep(ACCEPT_BACKOFF).await;
+
+                        continue;
+                    }
+                    Ok(stream) => stream,
+                };
+                let translate = self.clone();
+
+                task::spawn(async move {
+                    let translate = &translate;
+                    let result = daemon::serve(stream, move |request: Request| async move {
+                        let (source, target) = request.languages()?;
+
+                        translate
+                            .limited(&request.text, || {
+                                translate.translate_text(
+                                    request.text.clone(),
+                                    source,
+                                    target,
+                                    false,
+                                )
+                            })
+                            .await
+                    })
+                    .await;
+
+                    // a broken connection doesn't stop the daemon
+                    if let Err(err) = result {
+                        diagnostic::warn(format!("connection failed: {err}"));
+                    }
+                });
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        match interrupt::cancellable(accept).await {
+            Ok(result) => result,
+            Err(Interrupted) => {
+                diagnostic::info("the daemon is stopped");
+
+                Ok(())
+            }
+        }
+    }
+
+    /// poll the clipboard and print the translations, a failed translation doesn't stop watching,
+    /// the translations are never copied, or they would be translated again
+    #[cfg(feature = "clipboard")]
+    async fn run_watch(
+        &self,
+        primary: bool,
+        debounce: Duration,
+        max_chars: usize,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let mut watcher = Watcher::new(primary, debounce)?;
+        diagnostic::info("watching the copied text, press Ctrl-C to stop");
+
+        // the clipboard is still polled while translating, so the texts copied meanwhile are queued
+        let queue = RefCell::new(Queue::default());
+
+        let poll = async {
+            loop {
+                task::sleep(watch::POLL_INTERVAL).await;
+
+                let text = match watcher.poll() {
+                    Some(text) if !text.trim().is_empty() => text,
+                    _ => continue,
+                };
+                let chars = text.chars().count();
+                if chars > max_chars {
+                    diagnostic::info(format!(
+                        "skip the copied text of {chars} characters, longer than {max_chars}"
+                    ));
+
+                    continue;
+                }
+
+                let mut waiting = queue.borrow_mut();
+                if waiting.push(text).is_some() {
+                    diagnostic::warn("too many copied texts are waiting, drop the oldest one");
+                } else if waiting.len() > 1 {
+                    diagnostic::info(format!("{} copied texts are waiting", waiting.len()));
+                }
+            }
+        };
+
+        let translate = async {
+            loop {
+                let next = queue.borrow_mut().pop();
+                let Some(text) = next else {
+                    task::sleep(watch::POLL_INTERVAL).await;
+
+                    continue;
+                };
+
+                match self
+                    .limited(&text, || {
+                        self.translate_text(text.clone(), source, target, false)
+                    })
+                    .await
+                {
+                    Err(err) => diagnostic::warn(format!("translate failed: {err}")),
+                    Ok(translation) => {
+                        self.print(&text, &translation);
+                        #[cfg(feature = "notify")]
+                        self.notify(&text, &translation.text);
+                    }
+                }
+            }
+        };
+
+        // ctrl-c stops watching, the in-flight translation is dropped
+        if interrupt::cancellable(future::join(poll, translate))
+            .await
+            .is_err()
+        {
+            diagnostic::info("stop watching");
+        }
+
+        Ok(())
+    }
+
+    /// translate the clipboard text, or the text lines recognized from the clipboard image
+    #[cfg(feature = "clipboard")]
+    async fn run_clipboard(
+        &self,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let segments = match clipboard::read()? {
+            clipboard::Content::Text(text) => {
+                let translation = self
+                    .limited(&text, || {
+                        self.translate_text(text.clone(), source, target, false)
+                    })
+                    .await?;
+                self.print(&text, &translation);
+
+                vec![(text, translation.text)]
+            }
+
+            clipboard::Content::Image(image) => {
+                let lines = self.translate_image(&image, source, target).await?;
+                if lines.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "no text is recognized in the clipboard image"
+                    ));
+                }
+
+                self.print_image_lines(&image, lines, source, target)
+            }
+        };
+
+        #[cfg(feature = "clipboard")]
+        self.copy_to_clipboard(segments.iter().map(|(_, translated)| translated))?;
+        #[cfg(feature = "notify")]
+        self.notify(
+            &segments
+                .iter()
+                .map(|(source, _)| source.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            &segments
+                .iter()
+                .map(|(_, translated)| translated.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        self.check_segments(&segments)
+    }
+
+    /// recognize and translate the text lines of the image file, every line is printed with its
+    /// recognized source text
+    async fn run_image(
+        &self,
+        input: &Path,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let image = async_std::fs::read(input)
+            .await
+            .with_context(|| format!("read {} failed", input.display()))?;
+        if !image.starts_with(PNG_SIGNATURE) && !image.starts_with(JPEG_SIGNATURE) {
+            return Err(anyhow::anyhow!(
+                "{} is not a png or jpg image",
+                input.display()
+            ));
+        }
+
+        let lines = self.translate_image(&image, source, target).await?;
+        if lines.is_empty() {
+            return Err(anyhow::anyhow!(
+                "no text is recognized in {}",
+                input.display()
+            ));
+        }
+
+        let segments = self.print_image_lines(&image, lines, source, target);
+        self.check_segments(&segments)
+    }
+
+    /// recognize and translate the speech of the wav file by chunks of 200ms, the interim text of
+    /// a sentence is shown while recognizing, the final one is printed when the sentence ends
+    async fn run_audio(
+        &self,
+        input: &Path,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let data = async_std::fs::read(input)
+            .await
+            .with_context(|| format!("read {} failed", input.display()))?;
+        let samples =
+            wav::pcm(&data).with_context(|| format!("read {} failed", input.display()))?;
+        if samples.is_empty() {
+            return Err(anyhow::anyhow!("no audio in {}", input.display()));
+        }
+
+        let source_lang = source
+            .context("speech translation can't detect the language, pass --source")?
+            .as_str();
+        let target_lang = match target {
+            None => self.auto_target(source_lang).unwrap_or("zh"),
+            Some(target) => target.as_str(),
+        };
+
+        let client = self.tencent_client()?;
+        let session_uuid = session_uuid();
+        let chunks = samples.chunks(wav::CHUNK_SIZE).collect::<Vec<_>>();
+        let mut segments = vec![];
+        let mut sentence = None::<SpeechTranslateResponse>;
+        for (seq, chunk) in chunks.iter().enumerate() {
+            let request = SpeechTranslateRequest {
+                session_uuid: session_uuid.clone(),
+                source: source_lang.to_string(),
+                target: target_lang.to_string(),
+                audio_format: AUDIO_FORMAT_PCM,
+                seq: seq as _,
+                is_end: i64::from(seq + 1 == chunks.len()),
+                data: BASE64_STANDARD.encode(chunk),
+                project_id: 0,
+            };
+            let (resp, _) = self
+                .tencentcloud_api_retry("tmt SpeechTranslate", || {
+                    client.send::<SpeechTranslate>(&request)
+                })
+                .await?;
+
+            // a pause starts the next sentence, the last text of the previous one is final
+            if sentence
+                .as_ref()
+                .is_some_and(|sentence| sentence.vad_seq != resp.vad_seq)
+            {
+                segments.extend(
+                    sentence
+                        .take()
+                        .and_then(|sentence| self.print_sentence(sentence, source, target)),
+                );
+            }
+
+            let changed = sentence
+                .as_ref()
+                .is_none_or(|sentence| sentence.source_text != resp.source_text);
+            if changed
+                && !resp.source_text.is_empty()
+                && resp.recognize_status != RECOGNIZE_STATUS_DONE
+            {
+                diagnostic::info(format!("{} -> {}", resp.source_text, resp.target_text));
+            }
+            sentence = Some(resp);
+        }
+        segments
+            .extend(sentence.and_then(|sentence| self.print_sentence(sentence, source, target)));
+
+        if segments.is_empty() {
+            return Err(anyhow::anyhow!(
+                "no speech is recognized in {}",
+                input.display()
+            ));
+        }
+
+        self.check_segments(&segments)
+    }
+
+    /// print the final text of a recognized sentence, none if nothing is recognized
+    fn print_sentence(
+        &self,
+        sentence: SpeechTranslateResponse,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> Option<(String, String)> {
+        if sentence.source_text.trim().is_empty() {
+            return None;
+        }
+        let translation = Translation::undetected(sentence.target_text.clone(), source, target);
+        self.print(&sentence.source_text, &translation);
+
+        Some((sentence.source_text, sentence.target_text))
+    }
+
+    /// submit the document translation task and wait until it finishes, the translated document
+    /// is written next to the input as `<name>.<target>.<ext>`
+    async fn run_file(
+        &self,
+        input: &Path,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let document_type = input
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .with_context(|| format!("unknown document type of {}", input.display()))?;
+        let data = async_std::fs::read(input)
+            .await
+            .with_context(|| format!("read {} failed", input.display()))?;
+
+        let source_lang = source.map_or("auto", |source| source.as_str());
+        let target_lang = match target {
+            None => self.auto_target(source_lang).unwrap_or("zh"),
+            Some(target) => target.as_str(),
+        };
+        let stem = input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        let output = input.with_file_name(format!("{stem}.{target_lang}.{document_type}"));
+
+        let request = FileTranslateRequest {
+            source: source_lang.to_string(),
+            target: target_lang.to_string(),
+            document_type,
+            source_type: SOURCE_TYPE_DATA,
+            data: BASE64_STANDARD.encode(data),
+            project_id: 0,
+        };
+        let client = self.tencent_client()?;
+        let (resp, _) = self
+            .tencentcloud_api_retry("tmt FileTranslate", || {
+                client.send::<FileTranslate>(&request)
+            })
+            .await?;
+
+        let request = GetFileTranslateRequest {
+            task_id: resp.data.task_id,
+        };
+        let mut last_progress = None;
+        let status = loop {
+            async_std::task::sleep(FILE_POLL_INTERVAL).await;
+
+            let (resp, _) = self
+                .tencentcloud_api_retry("tmt GetFileTranslate", || {
+                    client.send::<GetFileTranslate>(&request)
+                })
+                .await?;
+            let status = resp.data;
+            match status.status {
+                TaskState::Success => break status,
+                TaskState::Fail => {
+                    return Err(anyhow::anyhow!(
+                        "translate {} failed: {}",
+                        input.display(),
+                        status.message
+                    ))
+                }
+                _ => {}
+            }
+
+            if last_progress != Some(status.progress) {
+                diagnostic::info(format!("{}: {}%", input.display(), status.progress));
+                last_progress = Some(status.progress);
+            }
+        };
+
+        let translated = BASE64_STANDARD
+            .decode(status.file_data.trim())
+            .context("decode translated document failed")?;
+        async_std::fs::write(&output, translated)
+            .await
+            .with_context(|| format!("write {} failed", output.display()))?;
+        diagnostic::info(format!("{} -> {}", input.display(), output.display()));
+
+        Ok(())
+    }
+
+    /// translate every file under input dir into output dir, keep the directory structure
+    async fn run_dir(
+        &self,
+        input: &Path,
+        output: Option<&Path>,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        // the output dir inside the input dir isn't translated again
+        let exclude = output.and_then(|output| output.canonicalize().ok());
+        let mut qa_issues = 0;
+        for path in collect_files(input, exclude.as_deref())? {
+            let relative_path = path.strip_prefix(input)?;
+            let output_path = output.map(|output| output.join(relative_path));
+
+            if let Err(err) = self
+                .translate_file(&path, relative_path, output_path.as_deref(), source, target)
+                .await
+            {
+                match err.downcast_ref::<QaFailed>().map(|qa_failed| qa_failed.0) {
+                    None => return Err(err),
+                    Some(issues) => qa_issues += issues,
+                }
+            }
+
+            match output_path {
+                None => diagnostic::info(path.display()),
+                Some(output_path) => {
+                    diagnostic::info(format!("{} -> {}", path.display(), output_path.display()))
+                }
+            }
+        }
+
+        if qa_issues > 0 {
+            return Err(QaFailed(qa_issues).into());
+        }
+
+        Ok(())
+    }
+
+    /// translate a file, the directives in the file override the command line options, the
+    /// unified diff is printed instead when output path is none
+    async fn translate_file(
+        &self,
+        path: &Path,
+        relative_path: &Path,
+        output_path: Option<&Path>,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let data = async_std::fs::read(path)
+            .await
+            .with_context(|| format!("read {} failed", path.display()))?;
+        let (content, file_style) = encoding::decode(&data);
+        if file_style.encoding != UTF_8 {
+            diagnostic::info(format!(
+                "{} is decoded as {}",
+                path.display(),
+                file_style.encoding.name()
+            ));
+        }
+        let directives = Directives::parse(&content)
+            .with_context(|| format!("parse directives of {} failed", path.display()))?;
+
+        let mut translate = self.clone();
+        if let Some(glossary) = &directives.glossary {
+            let glossary_path = path.parent().unwrap_or(Path::new(".")).join(glossary);
+            translate.glossary = Some(Arc::new(Glossary::load(&glossary_path)?));
+        }
+        let source = directives.source.or(source);
+        let target = directives.target.or(target);
+
+        if let Some(format) = ResourceFormat::from_path(path) {
+            let (blocks, segments) = translate
+                .translate_resource(&content, format, source, target, &mut Summary::default())
+                .await?;
+            let blocks = blocks
+                .iter()
+                .map(|(original, translated)| (original.as_str(), translated.as_str()))
+                .collect::<Vec<_>>();
+            self.write_blocks(relative_path, output_path, &content, file_style, &blocks)
+                .await?;
+
+            return translate.check_segments(&segments);
+        }
+
+        let format = Format::from_path(path);
+        let markers = self
+            .ignore_markers
+            .iter()
+            .rev()
+            .find(|format_markers| format_markers.format == format)
+            .map(|format_markers| format_markers.markers.clone())
+            .unwrap_or_else(|| format.default_markers());
+
+        let blocks = document::split(&content, &directives, &markers);
+        let spans = document::spans(&blocks, &content);
+        let mut segments = vec![];
+        // block index, span and provider of segments
+        let mut segment_metas = vec![];
+        let mut translated_blocks = vec![];
+        for (block, span) in blocks.iter().zip(spans) {
+            match block {
+                Block::Keep(text) => translated_blocks.push(text.clone()),
+                Block::Translate(text) => {
+                    let (translation, provider) = translate
+                        .limited(text, || {
+                            translate.translate_with_provider(text.clone(), source, target, false)
+                        })
+                        .await?;
+                    let translated = translation.text;
+
+                    segment_metas.push((translated_blocks.len(), span, provider));
+                    translated_blocks.push(translated.clone());
+                    segments.push((text.clone(), translated));
+                }
+            }
+        }
+
+        #[cfg(feature = "interactive")]
+        if self.review {
+            let mut providers = segment_metas
+                .iter()
+                .map(|(_, _, provider)| *provider)
+                .collect::<Vec<_>>();
+            translate
+                .review(&mut segments, &mut providers, source, target)
+                .await?;
+
+            for ((block_index, _, provider), ((_, translated), reviewed_provider)) in
+                segment_metas.iter_mut().zip(segments.iter().zip(providers))
+            {
+                translated_blocks[*block_index] = translated.clone();
+                *provider = reviewed_provider;
+            }
+        }
+
+        if self.segment_metadata {
+            for ((_, span, provider), (source, target)) in segment_metas.iter().zip(&segments) {
+                let record = SegmentRecord {
+                    file: path,
+                    output: output_path,
+                    span: *span,
+                    source,
+                    target,
+                    provider: *provider,
+                };
+                println!("{}", serde_json::to_string(&record)?);
+            }
+        }
+
+        let blocks = blocks
+            .iter()
+            .map(Block::text)
+            .zip(translated_blocks.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        self.write_blocks(relative_path, output_path, &content, file_style, &blocks)
+            .await?;
+
+        translate.check_segments(&segments)
+    }
+
+    /// write the translated blocks to the output path, print the unified diff of the original and
+    /// the translated blocks instead when output path is none
+    async fn write_blocks(
+        &self,
+        relative_path: &Path,
+        output_path: Option<&Path>,
+        content: &str,
+        file_style: FileStyle,
+        blocks: &[(&str, &str)],
+    ) -> anyhow::Result<()> {
+        if self.dry_run.is_some() {
+            return Ok(());
+        }
+
+        let output_path = match output_path {
+            None => {
+                let ops = diff::block_ops(blocks.iter().copied());
+                print!(
+                    "{}",
+                    diff::unified(
+                        &format!("a/{}", relative_path.display()),
+                        &format!("b/{}", relative_path.display()),
+                        &ops,
+                        content.ends_with('\n'),
+                    )
+                );
+
+                return Ok(());
+            }
+
+            Some(output_path) => output_path,
+        };
+
+        if let Some(dir) = output_path.parent() {
+            async_std::fs::create_dir_all(dir).await?;
+        }
+        let translated = document::join(
+            &blocks
+                .iter()
+                .map(|(_, translated)| *translated)
+                .collect::<Vec<_>>(),
+            content,
+        );
+        let translated = encoding::encode(
+            &translated,
+            file_style.with(self.newline, self.bom),
+            self.keep_encoding,
+        );
+        async_std::fs::write(output_path, translated)
+            .await
+            .with_context(|| format!("write {} failed", output_path.display()))?;
+
+        Ok(())
+    }
+
+    /// translate the strings of a json, yaml or po resource concurrently under the rate limiter,
+    /// the format placeholders are kept and the msgctxt of po is the context of its string, return
+    /// the line blocks of the original and the translated resource, and the segments
+    async fn translate_resource(
+        &self,
+        content: &str,
+        format: ResourceFormat,
+        source: Option<Language>,
+        target: Option<Language>,
+        summary: &mut Summary,
+    ) -> anyhow::Result<(Vec<(String, String)>, Vec<(String, String)>)> {
+        let content = content.replace("\r\n", "\n");
+        let entries = resource::parse(&content, format, target.map(|target| target.as_str()))
+            .with_context(|| format!("parse the {format} resource failed"))?;
+
+        let mut filters = self.filters.to_vec();
+        if !filters.contains(&Filter::Placeholders) {
+            filters.push(Filter::Placeholders);
+        }
+        let translate = &Self {
+            filters: Arc::new(filters),
+            ..self.clone()
+        };
+
+        // every plural variant is translated separately
+        let texts = entries
+            .iter()
+            .flat_map(|entry| entry.texts.iter().map(move |text| (entry, text)))
+            .collect();
+        let mut translations = vec![];
+        let mut segments = vec![];
+        ordered_batch(
+            texts,
+            self.concurrency(),
+            |(entry, text)| async move {
+                let contextual = entry.context.as_deref().map(|context| Self {
+                    context: Some(Arc::from(context)),
+                    ..translate.clone()
+                });
+                let translate = contextual.as_ref().unwrap_or(translate);
+                let translation = translate
+                    .limited(text, || {
+                        translate.translate_with_provider(text.clone(), source, target, false)
+                    })
+                    .await?;
+
+                Ok(((entry, text), translation))
+            },
+            |(_, text), (translation, provider)| {
+                summary.count(text, provider);
+                segments.push((text.clone(), translation.text.clone()));
+                translations.push(translation.text);
+            },
+        )
+        .await?;
+
+        let mut translations = translations.into_iter();
+        let translated = entries
+            .iter()
+            .map(|entry| {
+                let texts = translations.by_ref().take(entry.texts.len()).collect();
+
+                (entry, texts)
+            })
+            .collect::<Vec<_>>();
+
+        Ok((resource::replace(&content, &translated), segments))
+    }
+
+    /// ask user to accept, edit or retranslate every segment, the edits are saved to the
+    /// translation memory
+    #[cfg(feature = "interactive")]
+    async fn review(
+        &self,
+        segments: &mut [(String, String)],
+        providers: &mut [Provider],
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let _guard = TerminalGuard;
+        // retranslating must not reuse the memory or the cache
+        let fresh = Self {
+            memory: None,
+            cache: None,
+            ..self.clone()
+        };
+
+        let total = segments.len();
+        for (index, ((source_text, translated), provider)) in
+            segments.iter_mut().zip(providers).enumerate()
+        {
+            loop {
+                match review::ask(index + 1, total, source_text.clone(), translated.clone()).await?
+                {
+                    Action::Accept => break,
+
+                    Action::Editor => {
+                        let text = translated.clone();
+                        let edited = task::spawn_blocking(move || editor::edit(&text)).await?;
+                        self.remember_edit(source_text, translated, &edited, source, target)
+                            .await?;
+                        *translated = edited;
+                        *provider = Provider::Human;
+
+                        break;
+                    }
+
+                    Action::Edit(edited) => {
+                        self.remember_edit(source_text, translated, &edited, source, target)
+                            .await?;
+                        *translated = edited;
+                        *provider = Provider::Human;
+
+                        break;
+                    }
+
+                    Action::Retranslate => {
+                        let (translation, retranslated_provider) = fresh
+                            .limited(source_text, || {
+                                fresh.translate_with_provider(
+                                    source_text.clone(),
+                                    source,
+                                    target,
+                                    false,
+                                )
+                            })
+                            .await?;
+                        *translated = translation.text;
+                        *provider = retranslated_provider;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// save a human edited translation to the translation memory, even if memory is off, and
+    /// suggest a glossary entry when the same term is corrected repeatedly
+    #[cfg(feature = "interactive")]
+    async fn remember_edit(
+        &self,
+        word: &str,
+        translated_word: &str,
+        edited_word: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let (source_lang, target_lang) = self.resolve_langs(word, source, target).await?;
+        let segment = Segment {
+            source_lang,
+            target_lang: target_lang.to_string(),
+            source: word.to_string(),
+            target: edited_word.to_string(),
+        };
+
+        match &self.memory {
+            None => TranslationMemory::open()?.insert(segment)?,
+            Some(memory) => memory.lock().unwrap().insert(segment)?,
+        }
+
+        let (from, to) = match learn::changed_term(translated_word, edited_word) {
+            None => return Ok(()),
+            Some(change) => change,
+        };
+        let count = Corrections::open()?.record(from, to)?;
+        if count != learn::SUGGEST_AFTER {
+            return Ok(());
+        }
+
+        let glossary_path = match &self.glossary_path {
+            None => return Ok(()),
+            Some(path) => path.clone(),
+        };
+        // the changed term is in target language, it is also the source term when it is kept
+        // untranslated, such as names
+        let default_source = if word.contains(from) { from } else { "" }.to_string();
+        let message = format!("term {from} was changed to {to} {count} times — add to glossary?");
+        let to = to.to_string();
+
+        task::spawn_blocking(move || {
+            let question = Question::confirm("add_term")
+                .message(message)
+                .default(true)
+                .build();
+            if !requestty::prompt_one(question)?.as_bool().unwrap_or(false) {
+                return Ok(());
+            }
+
+            let question = Question::input("source_term")
+                .message(format!("source term translated to {to}"))
+                .default(default_source)
+                .build();
+            let answer = requestty::prompt_one(question)?;
+            let source_term = answer.as_string().unwrap_or("").trim();
+            if source_term.is_empty() {
+                return Ok(());
+            }
+
+            let term = Term {
+                source: source_term.to_string(),
+                target: to,
+            };
+            Glossary::append(&glossary_path, &term)?;
+            diagnostic::info(format!(
+                "added {},{} to {}",
+                term.source,
+                term.target,
+                glossary_path.display()
+            ));
+
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+    }
+
+    /// read lines by a readline editor with history and ctrl-r search, lines start with `:` are
+    /// commands which change the settings of the session
+    #[cfg(feature = "interactive")]
+    async fn run_interact(
+        &mut self,
+        mut source: Option<Language>,
+        mut target: Option<Language>,
+    ) -> anyhow::Result<()> {
+        let _guard = TerminalGuard;
+        let mut readline = Editor::<repl::Preview, FileHistory>::new()?;
+        readline.set_helper(Some(repl::Preview::new(self.previous_queries()?)));
+        let history_path = dirs::data_dir().map(|dir| dir.join("txcv").join(REPL_HISTORY_FILE));
+        if let Some(path) = &history_path {
+            // the history file doesn't exist before the first session
+            let _ = readline.load_history(path);
+        }
+        // the last word and its translation, which can be corrected by `:edit`
+        let mut last = None::<(String, Translation)>;
+        // the recovery of a failure is guided only once a session
+        let mut guided = false;
+        // the terms added by `:term`, they override the glossary file until the session ends
+        let mut session_terms: Vec<Term> = vec![];
+
+        loop {
+            // readline blocks, but nothing else runs in interactive mode
+            let word = match readline.readline("> ") {
+                Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
+                Ok(word) if word.trim().is_empty() => break,
+                Ok(word) => word,
+            };
+            readline.add_history_entry(word.as_str())?;
+
+            let command = match repl::parse(&word) {
+                None => {
+                    // ctrl-c aborts a slow request and returns to the prompt
+                    let translation = loop {
+                        let result = interrupt::abortable(self.limited(&word, || {
+                            self.translate_text(word.clone(), source, target, !self.ci)
+                        }))
+                        .await;
+                        match result {
+                            Err(Interrupted) => {
+                                diagnostic::info("the translation is aborted");
+
+                                break None;
+                            }
+                            Ok(Err(err)) => match self.guide(err, &mut guided).await? {
+                                None => break None,
+                                Some(language) => target = Some(language),
+                            },
+                            Ok(Ok(translation)) => break Some(translation),
+                        }
+                    };
+                    let Some(translation) = translation else {
+                        continue;
+                    };
+                    self.print(&word, &translation);
+                    self.explain(&word, &translation).await?;
+                    if let Some(preview) = readline.helper_mut() {
+                        preview.push(word.clone(), translation.text.clone());
+                    }
+
+                    last = Some((word, translation));
+
+                    continue;
+                }
+
+                Some(Err(err)) => {
+                    diagnostic::error(err);
+
+                    continue;
+                }
+
+                Some(Ok(command)) => command,
+            };
+
+            match command {
+                repl::Command::Edit => match &mut last {
+                    None => diagnostic::warn("nothing to edit, translate something first"),
+                    Some((word, translation)) => {
+                        let text = translation.text.clone();
+                        let edited = task::spawn_blocking(move || editor::edit(&text)).await?;
+                        self.remember_edit(word, &translation.text, &edited, source, target)
+                            .await?;
+
+                        translation.text = edited;
+                        self.print(word, translation);
+                    }
+                },
+
+                repl::Command::Source(language) => source = language,
+                repl::Command::Target(language) => target = language,
+                repl::Command::Concise(concise) => self.concise = concise,
+                repl::Command::Term(None) => {
+                    if session_terms.is_empty() {
+                        diagnostic::info(
+                            "no session terms, add one by `:term <term>=<translation>`",
+                        );
+                    }
+                    for term in &session_terms {
+                        eprintln!("{} -> {}", term.source, term.target);
+                    }
+                }
+                // the glossary file is untouched, the term is only kept by the placeholders of
+                // this session
+                repl::Command::Term(Some((source, target))) => {
+                    let term = Term { source, target };
+                    Arc::make_mut(self.glossary.get_or_insert_with(Default::default))
+                        .add(term.clone());
+                    session_terms.retain(|old| !old.source.eq_ignore_ascii_case(&term.source));
+                    session_terms.push(term);
+                }
+                repl::Command::Help => eprintln!("{}", repl::HELP),
+            }
+        }
+
+        if let Some(path) = &history_path {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            readline.save_history(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// guide the recovery of an unsupported target language or an auth failure instead of
+    /// exiting, the first unsupported target asks a supported one to translate again, the other
+    /// failures are returned. none keeps the session without translating
+    #[cfg(feature = "interactive")]
+    async fn guide(
+        &self,
+        err: anyhow::Error,
+        guided: &mut bool,
+    ) -> anyhow::Result<Option<Language>> {
+        let first = !std::mem::replace(guided, true);
+
+        match Error::from(err) {
+            Error::UnsupportedPair(err) => {
+                diagnostic::error(format!("{err:#}"));
+                if !first || self.ci {
+                    diagnostic::info("change the target language by `:target <language>`");
+
+                    return Ok(None);
+                }
+
+                let supported = self.backend.supported_languages();
+                let targets = Language::ALL
+                    .into_iter()
+                    .filter(|language| supported.contains(&language.as_str()))
+                    .collect::<Vec<_>>();
+                let names = targets
+                    .iter()
+                    .map(|language| format!("{} ({})", language.name(), language.as_str()))
+                    .collect::<Vec<_>>();
+                let index = task::spawn_blocking(move || {
+                    let question = Question::select("target")
+                        .message("translate to")
+                        .choices(names)
+                        .build();
+
+                    // escaping the question keeps the target
+                    requestty::prompt_one(question)
+                        .ok()
+                        .and_then(|answer| answer.as_list_item().map(|item| item.index))
+                })
+                .await;
+
+                Ok(index.and_then(|index| targets.get(index).copied()))
+            }
+
+            Error::Auth(err) => {
+                diagnostic::error(format!("{err:#}"));
+                if first {
+                    diagnostic::info(
+                        "check the credentials by `txcv auth show` and `txcv auth test` in \
+                         another terminal, save the right ones by `txcv auth set`, then start txcv \
+                         again, the translations from the memory and the cache still work",
+                    );
+                }
+
+                Ok(None)
+            }
+
+            err => Err(err.into()),
+        }
+    }
+
+    /// input and output of the queries in history, for previewing while typing
+    #[cfg(feature = "interactive")]
+    fn previous_queries(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let history = match &self.history {
+            None => return Ok(vec![]),
+            Some(history) => history,
+        };
+
+        Ok(history
+            .entries()?
+            .into_iter()
+            .map(|entry| (entry.input, entry.output))
+            .collect())
+    }
+
+    /// translate the text longer than the max characters of a request by chunks at sentence
+    /// boundaries, the chunks are translated concurrently under the rate limiter and joined in
+    /// order, the languages detected from the first chunk are used for all chunks, the chunks
+    /// translated before ctrl-c are printed
+    async fn translate_chunked(
+        &self,
+        text: String,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<Translation> {
+        let max_chars = self.max_chars.load(Ordering::Relaxed);
+        if text.chars().count() <= max_chars {
+            return self
+                .limited(&text, || {
+                    self.translate_text(text.clone(), source, target, false)
+                })
+                .await;
+        }
+
+        let chunks = segment::chunks(&text, source, max_chars)
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let (source_lang, target_lang) = self.resolve_langs(&chunks[0], source, target).await?;
+        let source = Language::from_code(&source_lang).or(source);
+        let target = Language::from_code(target_lang).or(target);
+
+        let mut translated_chunks = String::new();
+        let mut translated = String::new();
+        let result = ordered_batch(
+            chunks,
+            self.concurrency(),
+            |chunk| async {
+                let translation = self
+                    .limited(&chunk, || {
+                        self.translate_text(chunk.clone(), source, target, false)
+                    })
+                    .await?;
+
+                Ok((chunk, translation))
+            },
+            |chunk, translation| {
+                translated_chunks.push_str(&chunk);
+                translated.push_str(&translation.text);
+            },
+        )
+        .await;
+
+        let translation = Translation {
+            source_lang,
+            target_lang: target_lang.to_string(),
+            text: translated,
+        };
+        match result {
+            // print the translated chunks before exiting
+            Err(err) if err.is::<Interrupted>() && !translated_chunks.is_empty() => {
+                self.print(&translated_chunks, &translation);
+
+                Err(err)
+            }
+            result => result.map(|_| translation),
+        }
+    }
+
+    /// translate text, the leading and trailing whitespace of lines are kept
+    async fn translate_text(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+        prompt: bool,
+    ) -> anyhow::Result<Translation> {
+        let (translation, _) = self
+            .translate_with_provider(word, source, target, prompt)
+            .await?;
+
+        Ok(translation)
+    }
+
+    /// same as translate_text, also return where the translation comes from
+    async fn translate_with_provider(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+        prompt: bool,
+    ) -> anyhow::Result<(Translation, Provider)> {
+        let (core, layout) = whitespace::strip(&word);
+        if core.trim().is_empty() {
+            return Ok((
+                Translation::undetected(word, source, target),
+                Provider::Untranslated,
+            ));
+        }
+
+        let (translation, provider) = self.translate_core(core, source, target, prompt).await?;
+        let translation = Translation {
+            text: whitespace::restore(&translation.text, &layout),
+            ..translation
+        };
+
+        Ok((translation, provider))
+    }
+
+    async fn translate_core(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+        prompt: bool,
+    ) -> anyhow::Result<(Translation, Provider)> {
+        let word = self.preprocess(word)?;
+
+        if let Some(translated_word) = self.lookup_memory(&word, source, target, prompt).await? {
+            diagnostic::debug("translation memory hit");
+            let translation = Translation::undetected(translated_word, source, target);
+            self.record_history(&word, &translation)?;
+
+            return Ok((translation, Provider::Memory));
+        }
+
+        let source = match source {
+            None if prompt => self.disambiguate(&word).await?,
+            source => source,
+        };
+
+        let protected = self.protect(&word);
+        let (translation, provider) = if protected.is_empty() {
+            self.translate_remote(word.clone(), source, target).await?
+        } else {
+            let (translation, provider) = self
+                .translate_remote(protected.text.clone(), source, target)
+                .await?;
+            let translation = Translation {
+                text: protect::restore(&translation.text, &protected),
+                ..translation
+            };
+
+            (translation, provider)
+        };
+        let translation = self.substitute(translation);
+        self.remember(&word, &translation)?;
+        self.record_history(&word, &translation)?;
+
+        #[cfg(feature = "wasm-plugin")]
+        let translation = Translation {
+            text: self.run_plugins(translation.text, WasmPlugin::post)?,
+            ..translation
+        };
+
+        Ok((translation, provider))
+    }
+
+    /// normalize, rewrite by the filters and expand the slang of the text, then run the pre
+    /// plugins
+    fn preprocess(&self, word: String) -> anyhow::Result<String> {
+        let word = if self.normalizations.is_empty() {
+            word
+        } else {
+            normalize::normalize(&word, &self.normalizations)
+        };
+        let word = if self.filters.is_empty() {
+            word
+        } else {
+            filter::rewrite(&word, &self.filters)
+        };
+        let word = match &self.slang {
+            None => word,
+            Some(slang) => slang.apply(&word),
+        };
+
+        #[cfg(feature = "wasm-plugin")]
+        let word = self.run_plugins(word, WasmPlugin::pre)?;
+
+        Ok(word)
+    }
+
+    /// the glossary terms are replaced by placeholders and restored to their translations, so are
+    /// the emojis and the spans kept by the filters
+    fn protect(&self, word: &str) -> protect::Protected {
+        let mut spans = filter::spans(word, &self.filters);
+        spans.extend(protect::emoji_spans(word));
+        if let Some(glossary) = &self.glossary {
+            spans.extend(glossary.spans(word));
+        }
+
+        protect::protect(word, protect::merge_spans(spans))
+    }
+
+    /// translate word by the backend, fall back to the offline dictionary when the network is
+    /// unavailable
+    async fn translate_remote(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<(Translation, Provider)> {
+        match self.translate_cached(word.clone(), source, target).await {
+            Err(err) => self.fallback(err, &word, source, target).await,
+            result => result,
+        }
+    }
+
+    /// look up the word in the offline dictionary if the err is caused by network, return the err
+    /// otherwise
+    async fn fallback(
+        &self,
+        err: anyhow::Error,
+        word: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<(Translation, Provider)> {
+        let path = match &self.dict_path {
+            Some(path) if network_unavailable(&err) => path,
+            _ => return Err(err),
+        };
+        let dict = match self.dict.get() {
+            Some(dict) => dict,
+            None => {
+                let _ = self.dict.set(Dict::load(path)?);
+
+                self.dict
+                    .get()
+                    .context("offline dictionary is not loaded")?
+            }
+        };
+
+        let source_lang = source.map_or("auto", |source| source.as_str());
+        let target_lang = target.map_or("auto", |target| target.as_str());
+        match dict.translate(word, source_lang, target_lang).await {
+            Err(_) => Err(err),
+            Ok(translation) => {
+                diagnostic::warn(format!("{err}, looked up in the offline dictionary"));
+
+                Ok((translation, Provider::Dict))
+            }
+        }
+    }
+
+    /// translate word by the cache if it's enabled and the word is cached
+    async fn translate_cached(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<(Translation, Provider)> {
+        let cache = match &self.cache {
+            None => {
+                let translation = self.translate_uncached(word, source, target).await?;
+
+                return Ok((translation, self.backend.provider()));
+            }
+            Some(cache) => cache,
+        };
+
+        let text = word.clone();
+        if let Some(translation) = self.cached(&text, source, target)? {
+            diagnostic::debug("cache hit");
+            diagnostic::trace(format_args!("{text:?} -> {:?}", translation.text));
+
+            return Ok((translation, Provider::Cache));
+        }
+
+        let source_lang = source.map_or("auto", |source| source.as_str());
+        let target_lang = target.map_or("auto", |target| target.as_str());
+        let key = self.cache_key(&text, source_lang, target_lang);
+        let translation = self.translate_uncached(word, source, target).await?;
+        cache
+            .insert(
+                &key,
+                &translation.source_lang,
+                &translation.target_lang,
+                &translation.text,
+            )
+            .map_err(Error::Cache)?;
+
+        Ok((translation, self.backend.provider()))
+    }
+
+    /// the cached translation of the text, none if the cache is disabled or the text isn't cached
+    fn cached(
+        &self,
+        text: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<Option<Translation>> {
+        let Some(cache) = &self.cache else {
+            return Ok(None);
+        };
+
+        let source_lang = source.map_or("auto", |source| source.as_str());
+        let target_lang = target.map_or("auto", |target| target.as_str());
+        let entry = cache
+            .get(&self.cache_key(text, source_lang, target_lang))
+            .map_err(Error::Cache)?;
+
+        Ok(entry.map(|entry| Translation {
+            source_lang: entry.source_lang,
+            target_lang: entry.target_lang,
+            text: entry.text,
+        }))
+    }
+
+    fn cache_key<'a>(&'a self, text: &'a str, source: &'a str, target: &'a str) -> cache::Key<'a> {
+        cache::Key {
+            text,
+            source,
+            target,
+            term_repos: &self.term_repos,
+            context: self.context.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// take the translation of the batch api if the text is translated by the batch of the run,
+    /// translate it by the backend otherwise
+    async fn translate_uncached(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<Translation> {
+        match self.batched.get(&word) {
+            Some(translation) => Ok(translation.clone()),
+            None => self.translate_word(word, source, target).await,
+        }
+    }
+
+    #[cfg(feature = "wasm-plugin")]
+    fn run_plugins(
+        &self,
+        text: String,
+        filter: fn(&mut WasmPlugin, &str) -> anyhow::Result<String>,
+    ) -> anyhow::Result<String> {
+        self.plugins.iter().try_fold(text, |text, plugin| {
+            filter(&mut plugin.lock().unwrap(), &text)
+        })
+    }
+
+    /// put the translations on the clipboard if copy is enabled, a translation per line
+    #[cfg(feature = "clipboard")]
+    fn copy_to_clipboard<T: AsRef<str>>(
+        &self,
+        translations: impl IntoIterator<Item = T>,
+    ) -> anyhow::Result<()> {
+        if !self.copy {
+            return Ok(());
+        }
+
+        let text = translations
+            .into_iter()
+            .map(|translation| translation.as_ref().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        clipboard::write(&text)
+    }
+
+    /// draw the image in the terminal if it's enabled and stdout is a terminal, return if the
+    /// image is drawn, a failed drawing only warns
+    fn show_image(&self, image: &[u8]) -> bool {
+        let Some(protocol) = self.show_image else {
+            return false;
+        };
+        // the escape sequences would break the machine readable outputs
+        if self.output != Output::Plain || !std::io::stdout().is_terminal() {
+            return false;
+        }
+        let Some(protocol) = protocol.resolve() else {
+            diagnostic::warn(
+                "the terminal doesn't support inline images, pass --show-image=<protocol> to force one",
+            );
+
+            return false;
+        };
+
+        match inline_image::render(image, protocol, std::io::stdout().lock()) {
+            Err(err) => {
+                diagnostic::warn(format!("draw image by {protocol} failed: {err:#}"));
+
+                false
+            }
+
+            Ok(()) => true,
+        }
+    }
+
+    /// show the translation as a desktop notification if it's enabled, a failed notification
+    /// only warns
+    #[cfg(feature = "notify")]
+    fn notify(&self, source: &str, translated: &str) {
+        if !self.notify {
+            return;
+        }
+
+        if let Err(err) = notify::show(source, translated) {
+            diagnostic::warn(err);
+        }
+    }
+
+    /// run the glossary and qa checks on translated segments, return error when qa finds issues
+    fn check_segments<S: AsRef<str>, T: AsRef<str>>(
+        &self,
+        segments: &[(S, T)],
+    ) -> anyhow::Result<()> {
+        // the texts of a dry run aren't translated
+        if self.dry_run.is_some() {
+            return Ok(());
+        }
+
+        self.check_terms(segments);
+
+        if let Some(format) = self.qa {
+            let issues = qa::check(segments);
+            qa::print(&issues, format)?;
+
+            if !issues.is_empty() {
+                return Err(QaFailed(issues.len()).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// print the glossary terms which are not translated as the glossary defines
+    fn check_terms<S: AsRef<str>, T: AsRef<str>>(&self, segments: &[(S, T)]) {
+        let glossary = match &self.glossary {
+            None => return,
+            Some(glossary) => glossary,
+        };
+
+        let deviations = glossary.check(segments);
+        if deviations.is_empty() {
+            return;
+        }
+
+        eprintln!("terminology deviations:");
+        for deviation in deviations {
+            eprintln!(
+                "  segment {}: \"{}\" should be translated as \"{}\"",
+                deviation.segment, deviation.term.source, deviation.term.target
+            );
+        }
+    }
+
+    /// search the translation memory, when the match is not exact and memory mode is offer, ask
+    /// user to reuse it if prompt is allowed
+    async fn lookup_memory(
+        &self,
+        word: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+        prompt: bool,
+    ) -> anyhow::Result<Option<String>> {
+        let memory = match &self.memory {
+            None => return Ok(None),
+            Some(memory) => memory,
+        };
+
+        let (translated_word, similarity) = {
+            let memory = memory.lock().unwrap();
+            match memory.lookup(
+                word,
+                source.map(|source| source.as_str()),
+                target.map(|target| target.as_str()),
+                self.memory_threshold,
+            ) {
+                None => return Ok(None),
+                Some(m) => (m.segment.target.clone(), m.similarity),
+            }
+        };
+
+        if similarity == 100 || self.memory_mode == MemoryMode::Force {
+            return Ok(Some(translated_word));
+        }
+        if !prompt {
+            return Ok(None);
+        }
+
+        self.ask_reuse(similarity, translated_word).await
+    }
+
+    #[cfg(feature = "interactive")]
+    async fn ask_reuse(
+        &self,
+        similarity: u8,
+        translated_word: String,
+    ) -> anyhow::Result<Option<String>> {
+        let message = format!("reuse {similarity}% match \"{translated_word}\"");
+        let reuse = task::spawn_blocking(move || {
+            let question = Question::confirm("reuse")
+                .message(message)
+                .default(true)
+                .build();
+            let answer = requestty::prompt_one(question)?;
+
+            Ok::<_, anyhow::Error>(answer.as_bool().unwrap_or(false))
+        })
+        .await?;
+
+        Ok(reuse.then_some(translated_word))
+    }
+
+    /// prompts are never allowed without the interactive feature
+    #[cfg(not(feature = "interactive"))]
+    async fn ask_reuse(
+        &self,
+        _similarity: u8,
+        _translated_word: String,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// ask which source language is meant when the local guess and the detected language
+    /// disagree, or the text is a homograph like `chat`, none leaves the detection to the backend
+    #[cfg(feature = "interactive")]
+    async fn disambiguate(&self, word: &str) -> anyhow::Result<Option<Language>> {
+        let candidates = disambiguate::candidates(word);
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        let mut choices = Choices::open()?;
+        if let Some(lang) = choices.get(word) {
+            return Ok(Some(lang));
+        }
+
+        let detected = Language::from_code(&self.backend.detect_language(word).await?);
+        let options = disambiguate::ambiguous(&candidates, detected);
+        if options.is_empty() {
+            return Ok(detected);
+        }
+
+        let message = format!("which language is \"{}\"", word.trim());
+        let names = options
+            .iter()
+            .map(|lang| lang.as_str().to_string())
+            .collect::<Vec<_>>();
+        let (index, remember) = task::spawn_blocking(move || {
+            let question = Question::select("source")
+                .message(message)
+                .choices(names)
+                .build();
+            let answer = requestty::prompt_one(question)?;
+            let index = answer.as_list_item().map_or(0, |item| item.index);
+
+            let question = Question::confirm("remember")
+                .message("remember this choice")
+                .default(false)
+                .build();
+            let remember = requestty::prompt_one(question)?.as_bool().unwrap_or(false);
+
+            Ok::<_, anyhow::Error>((index, remember))
+        })
+        .await?;
+
+        let lang = options[index.min(options.len() - 1)];
+        if remember {
+            choices.remember(word, lang)?;
+        }
+
+        Ok(Some(lang))
+    }
+
+    /// prompts are never allowed without the interactive feature
+    #[cfg(not(feature = "interactive"))]
+    async fn disambiguate(&self, _word: &str) -> anyhow::Result<Option<Language>> {
+        Ok(None)
+    }
+
+    fn record_history(&self, word: &str, translation: &Translation) -> anyhow::Result<()> {
+        if let Some(history) = &self.history {
+            history.append(&history::Entry::new(
+                &translation.source_lang,
+                &translation.target_lang,
+                word,
+                &translation.text,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// the target language when it's not specified, the configured rules take precedence
+    fn auto_target(&self, source_lang: &str) -> Option<&'static str> {
+        lang::auto_target(&self.auto_targets, source_lang)
+            .map(|target| target.as_str())
+            .or_else(|| get_target_lang(source_lang))
+    }
+
+    /// apply the configured regex replacements to the translation
+    fn substitute(&self, translation: Translation) -> Translation {
+        if self.substitutions.is_empty() {
+            return translation;
+        }
+
+        Translation {
+            text: substitute::apply(
+                &self.substitutions,
+                &translation.text,
+                &translation.source_lang,
+                &translation.target_lang,
+            ),
+            ..translation
+        }
+    }
+
+    fn remember(&self, word: &str, translation: &Translation) -> anyhow::Result<()> {
+        if let Some(memory) = &self.memory {
+            memory.lock().unwrap().insert(Segment {
+                source_lang: translation.source_lang.clone(),
+                target_lang: translation.target_lang.clone(),
+                source: word.to_string(),
+                target: translation.text.clone(),
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn print(&self, word: &str, translation: &Translation) {
+        let record = output::Record {
+            source: word,
+            source_lang: &translation.source_lang,
+            target_lang: &translation.target_lang,
+            target: &translation.text,
+        };
+        if let Some(line) = self.output.format(&record) {
+            self.emit(line);
+
+            return;
+        }
+
+        let translated_word = translation.text.as_str();
+        if self.ci {
+            self.emit(serde_json::json!({ "source": word, "target": translated_word }));
+
+            return;
+        }
+
+        #[cfg(feature = "tui")]
+        if self.page(word, translated_word) {
+            return;
+        }
+
+        if translated_word.contains('\n') || too_wide(word, translated_word) {
+            self.print_newline(word, translated_word);
+
+            return;
+        }
+
+        self.print_one_line(word, translated_word);
+    }
+
+    /// draw the image if it's enabled and print its translated text lines, return the source and
+    /// translated lines
+    fn print_image_lines(
+        &self,
+        image: &[u8],
+        lines: Vec<ItemValue>,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> Vec<(String, String)> {
+        let shown = self.show_image(image);
+
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let translation = Translation::undetected(line.target_text.clone(), source, target);
+                self.print_region(i + 1, &line, &translation, shown);
+
+                (line.source_text, line.target_text)
+            })
+            .collect()
+    }
+
+    /// print a text line recognized from an image, the json output has its bounding box, and the
+    /// number and position are printed before it if the image is drawn
+    fn print_region(
+        &self,
+        number: usize,
+        line: &ItemValue,
+        translation: &Translation,
+        shown: bool,
+    ) {
+        if self.output == Output::Json {
+            let record = output::RegionRecord {
+                region: number,
+                record: output::Record {
+                    source: &line.source_text,
+                    source_lang: &translation.source_lang,
+                    target_lang: &translation.target_lang,
+                    target: &translation.text,
+                },
+                bbox: output::BoundingBox {
+                    x: line.x,
+                    y: line.y,
+                    width: line.w,
+                    height: line.h,
+                },
+            };
+            self.emit(serde_json::to_string(&record).expect("record is serializable"));
+
+            return;
+        }
+
+        // number the regions so they can be matched in the image
+        if shown {
+            println!(
+                "{}",
+                format!("[{number}] {},{} {}x{}", line.x, line.y, line.w, line.h).dimmed()
+            );
+        }
+        self.print(&line.source_text, translation);
+    }
+
+    /// page the translation which is too long for the terminal, like the senses of a dictionary
+    /// lookup, return false if it's not paged
+    #[cfg(feature = "tui")]
+    fn page(&self, word: &str, translated_word: &str) -> bool {
+        let lines = translated_word.lines().collect::<Vec<_>>();
+        if self.concise
+            || !std::io::stdin().is_terminal()
+            || !std::io::stdout().is_terminal()
+            || !pager::needs_paging(lines.len())
+        {
+            return false;
+        }
+
+        match pager::page(word, &lines) {
+            Err(err) => {
+                diagnostic::warn(format!("page failed: {err}"));
+
+                false
+            }
+            Ok(_) => true,
+        }
+    }
+
+    /// print the dictionary card and the explanation of the word below the translation
+    async fn explain(&self, word: &str, translation: &Translation) -> anyhow::Result<()> {
+        if let Some(note) = self.explanation(word, translation).await? {
+            self.print_note(&note);
+        }
+
+        Ok(())
+    }
+
+    async fn explanation(
+        &self,
+        word: &str,
+        translation: &Translation,
+    ) -> anyhow::Result<Option<String>> {
+        // the source language of a translation from the memory is unknown
+        let card = match &self.cards {
+            Some(cards)
+                if card::is_word(word)
+                    && matches!(translation.source_lang.as_str(), "en" | "auto") =>
+            {
+                match cards.lookup(word.trim()).await {
+                    Ok(card) => card.map(|card| card.render()),
+                    Err(err) => {
+                        diagnostic::warn(format!("look up {} failed: {err}", word.trim()));
+
+                        None
+                    }
+                }
+            }
+
+            _ => None,
+        };
+        let explanation = match &self.explainer {
+            None => None,
+            Some(explainer) => explainer.explain(word, &translation.target_lang).await?,
+        };
+
+        Ok(match (card, explanation) {
+            (Some(card), Some(explanation)) => Some(format!("{card}\n{explanation}")),
+            (card, explanation) => card.or(explanation),
+        })
+    }
+
+    fn print_note(&self, note: &str) {
+        let color_output = match self.color {
+            Color::Always => true,
+            Color::Auto => std::io::stdout().is_terminal(),
+            Color::Disable => false,
+        };
+
+        if color_output {
+            println!("{}", note.dimmed());
+        } else {
+            println!("{note}");
+        }
+    }
+
+    fn print_newline(&self, word: &str, translated_word: &str) {
+        let color_output = match self.color {
+            Color::Always => true,
+            Color::Auto => std::io::stdout().is_terminal(),
+            Color::Disable => false,
+        };
+
+        if !color_output {
+            if !self.concise {
+                self.emit(format!("{word}\n↓\n{translated_word}"));
+            } else {
+                self.emit(translated_word);
+            }
+        } else if !self.concise {
+            self.emit(format!(
+                "{}\n{}\n{}",
+                word.blue(),
+                "↓".white(),
+                translated_word.green()
+            ));
+        } else {
+            self.emit(translated_word.green());
+        }
+    }
+
+    fn print_one_line(&self, word: &str, translated_word: &str) {
+        let color_output = match self.color {
+            Color::Always => true,
+            Color::Auto => std::io::stdout().is_terminal(),
+            Color::Disable => false,
+        };
+
+        if !color_output {
+            if !self.concise {
+                self.emit(format!("{word} -> {translated_word}"));
+            } else {
+                self.emit(translated_word);
+            }
+        } else if !self.concise {
+            self.emit(format!(
+                "{} {} {}",
+                word.blue(),
+                "->".white(),
+                translated_word.green()
+            ));
+        } else {
+            self.emit(translated_word.green());
+        }
+    }
+
+    /// print a translation followed by the record separator, flushed at once by `--flush`
+    fn emit(&self, record: impl Display) {
+        // the planned requests are the output of a dry run
+        if self.dry_run.is_some() {
+            return;
+        }
+
+        diagnostic::erase_notice();
+        print!("{record}{}", self.record_sep);
+        if self.flush {
+            let _ = std::io::stdout().flush();
+        }
+    }
+
+    /// translate the words of the known source language by the batch api of the backend, the
+    /// words are grouped by the max batch size and the max characters, so n words take a few
+    /// requests instead of n. the words are preprocessed and protected like a single text, and
+    /// the translations are keyed by the texts sent to the backend. the words in the memory or the
+    /// cache and the too long ones are skipped, and the words of a failed batch are left to be
+    /// translated one by one
+    async fn translate_batched(
+        &self,
+        words: &[String],
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<HashMap<String, Translation>> {
+        let mut translated = HashMap::new();
+        let max_batch = self.backend.max_batch();
+        let (Some(source), Some(first)) = (source, words.first()) else {
+            return Ok(translated);
+        };
+        // the batch api has no context
+        if max_batch <= 1 || words.len() <= 1 || self.context.is_some() {
+            return Ok(translated);
+        }
+        // the plugins run once a text
+        #[cfg(feature = "wasm-plugin")]
+        if !self.plugins.is_empty() {
+            return Ok(translated);
+        }
+        let (source_lang, target_lang) = self.resolve_langs(first, Some(source), target).await?;
+
+        let max_chars = self.max_chars.load(Ordering::Relaxed);
+        let mut seen = HashSet::new();
+        let mut batches: Vec<Vec<String>> = vec![];
+        let mut batch_chars = 0;
+        for word in words {
+            let (core, _) = whitespace::strip(word);
+            if core.trim().is_empty() {
+                continue;
+            }
+            let word = self.preprocess(core)?;
+            if self
+                .lookup_memory(&word, source, target, false)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            let protected = self.protect(&word);
+            let word = if protected.is_empty() {
+                word
+            } else {
+                protected.text
+            };
+
+            let chars = word.chars().count();
+            if chars > max_chars
+                || seen.contains(&word)
+                || self.cached(&word, Some(source), target)?.is_some()
+            {
+                continue;
+            }
+            seen.insert(word.clone());
+
+            match batches.last_mut() {
+                Some(batch) if batch.len() < max_batch && batch_chars + chars <= max_chars => {
+                    batch_chars += chars;
+                    batch.push(word);
+                }
+
+                _ => {
+                    batch_chars = chars;
+                    batches.push(vec![word]);
+                }
+            }
+        }
+
+        for batch in batches {
+            match self
+                .limited(&batch.concat(), || {
+                    self.backend
+                        .translate_batch(&batch, &source_lang, target_lang)
+                })
+                .await
+            {
+                Err(err) if err.is::<Interrupted>() => return Err(err),
+                Err(err) => diagnostic::warn(format!(
+                    "batch translation failed, translate the words one by one: {err}"
+                )),
+                Ok(translations) => translated.extend(batch.into_iter().zip(translations)),
+            }
+        }
+
+        Ok(translated)
+    }
+
+    async fn translate_word(
+        &self,
+        word: String,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<Translation> {
+        let (source_lang, target_lang) = self.resolve_langs(&word, source, target).await?;
+        let lang = Language::from_code(&source_lang);
+
+        let word_chars = word.chars().count();
+        if word_chars <= self.max_chars.load(Ordering::Relaxed) {
+            let result = match &self.context {
+                None => {
+                    self.backend
+                        .translate(&word, &source_lang, target_lang)
+                        .await
+                }
+                Some(context) => {
+                    self.backend
+                        .translate_in_context(&word, context, &source_lang, target_lang)
+                        .await
+                }
+            };
+            match result {
+                Err(err) if err.is::<TextTooLong>() && word_chars > 1 => {
+                    self.shrink_max_chars(&err, word_chars / 2)
+                }
+                result => return result,
+            }
+        }
+
+        // split the text into chunks by sentences, a rejected chunk is split again with the half
+        // limit, which is kept for the later requests
+        let max_chars = self.max_chars.load(Ordering::Relaxed);
+        let mut chunks = segment::chunks(&word, lang, max_chars)
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        let mut translation = Translation {
+            source_lang: source_lang.clone(),
+            target_lang: target_lang.to_string(),
+            text: String::new(),
+        };
+        while let Some(chunk) = chunks.pop_front() {
+            let chunk_chars = chunk.chars().count();
+            let chunk_translation = match self
+                .backend
+                .translate(chunk, &source_lang, target_lang)
+                .await
+            {
+                Err(err) if err.is::<TextTooLong>() && chunk_chars > 1 => {
+                    let limit = chunk_chars / 2;
+                    self.shrink_max_chars(&err, limit);
+                    for chunk in segment::chunks(chunk, lang, limit).into_iter().rev() {
+                        chunks.push_front(chunk);
+                    }
+
+                    continue;
+                }
+
+                result => result?,
+            };
+
+            translation.source_lang = chunk_translation.source_lang;
+            translation.text.push_str(chunk_translation.text.trim_end());
+            translation
+                .text
+                .push_str(chunk_separator(chunk, target_lang));
+        }
+        translation.text.truncate(translation.text.trim_end().len());
+
+        Ok(translation)
+    }
+
+    /// lower the max characters of a request after the backend rejects a longer text
+    fn shrink_max_chars(&self, err: &anyhow::Error, limit: usize) {
+        if self.max_chars.fetch_min(limit, Ordering::Relaxed) > limit {
+            diagnostic::warn(format!(
+                "{err}, split it into requests of at most {limit} characters"
+            ));
+        }
+    }
+
+    /// the requests of a batch sent at the same time
+    fn concurrency(&self) -> usize {
+        self.rate_limit.max_concurrent as _
+    }
+
+    /// run the request of the text under the shared rate limit and retry it by the retry policy,
+    /// the text weighs the request by its characters. the permit is held until the request is
+    /// done, so draining waits for it. the deadline of --timeout covers the wait for the permit
+    /// and the retries, and ctrl-c cancels it, the request is dropped in both cases
+    async fn limited<T, Fut, F>(&self, text: &str, f: F) -> anyhow::Result<T>
+    where
+        Fut: Future<Output = anyhow::Result<T>>,
+        F: Fn() -> Fut,
+    {
+        let request = self.retry.run(
+            || async {
+                let started = Instant::now();
+                let _permit = self.limiter.acquire_chars(text.chars().count()).await?;
+                let waited = started.elapsed();
+                if waited >= MIN_LOGGED_WAIT {
+                    diagnostic::debug(format_args!(
+                        "waited {}ms for the rate limiter",
+                        waited.as_millis()
+                    ));
+                }
+
+                f().await
+            },
+            |err| self.retry.retryable(err),
+        );
+
+        match interrupt::shutdown()
+            .child_with_timeout(self.timeout)
+            .run(request)
+            .await
+        {
+            Ok(result) => result,
+            Err(Cancelled::Cancelled) => Err(Interrupted.into()),
+            Err(Cancelled::DeadlineExceeded) => Err(anyhow::anyhow!(
+                "request timed out after {}s",
+                self.timeout.unwrap_or_default().as_secs()
+            )),
+        }
+    }
+
+    async fn tencentcloud_api_retry<Fut, T, F>(
+        &self,
+        action: &str,
+        mut f: F,
+    ) -> Result<T, tencentcloud::Error>
+    where
+        Fut: Future<Output = Result<T, tencentcloud::Error>>,
+        F: FnMut() -> Fut,
+    {
+        self.retry
+            .run(
+                || diagnostic::timed(action, f()),
+                |err| self.retry.retryable_api_error(err),
+            )
+            .await
+    }
+
+    /// the client of the apis only tmt provides
+    fn tencent_client(&self) -> anyhow::Result<&Client> {
+        self.backend
+            .tencent_client()
+            .context("only the tmt backend supports image, speech and document translation")
+    }
+
+    /// recognize and translate the text lines of a png or jpg image
+    async fn translate_image(
+        &self,
+        image: &[u8],
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<Vec<ItemValue>> {
+        let source_lang = source.map_or("auto", |source| source.as_str());
+        // the source language of an image is unknown before recognizing, screenshots are usually
+        // foreign text, so translate into chinese by default
+        let target_lang = match target {
+            None => self.auto_target(source_lang).unwrap_or("zh"),
+            Some(target) => target.as_str(),
+        };
+        let request = ImageTranslateRequest {
+            session_uuid: session_uuid(),
+            scene: "doc".to_string(),
+            data: BASE64_STANDARD.encode(image),
+            source: source_lang.to_string(),
+            target: target_lang.to_string(),
+            project_id: 0,
+        };
+
+        let client = self.tencent_client()?;
+        let (resp, _) = self
+            .tencentcloud_api_retry("tmt ImageTranslate", || {
+                client.send::<ImageTranslate>(&request)
+            })
+            .await?;
+
+        Ok(resp.image_record.value)
+    }
+
+    /// detect the source language if not specified, the default target language depends on the
+    /// source language
+    async fn resolve_langs(
+        &self,
+        word: &str,
+        source: Option<Language>,
+        target: Option<Language>,
+    ) -> anyhow::Result<(String, &'static str)> {
+        let source_lang = match source {
+            None => self.backend.detect_language(word).await?,
+            Some(source) => source.as_str().to_string(),
+        };
+        let target_lang = match target {
+            None => self.auto_target(&source_lang).unwrap_or("en"),
+            Some(target) => target.as_str(),
+        };
+        if !self.backend.supported_languages().contains(&target_lang) {
+            return Err(Error::UnsupportedPair(anyhow::anyhow!(
+                "target language {target_lang} is not supported by the backend"
+            ))
+            .into());
+        }
+
+        Ok((source_lang, target_lang))
+    }
+}
+
+/// translate at most concurrency words at the same time, output is called in the order of words,
+/// ctrl-c cancels the pending words and returns Interrupted after the translated ones are output
+async fn ordered_batch<W, T, F, Fut, O>(
+    words: Vec<W>,
+    concurrency: usize,
+    translate: F,
+    mut output: O,
+) -> anyhow::Result<()>
+where
+    F: FnMut(W) -> Fut,
+    Fut: Future<Output = anyhow::Result<(W, T)>>,
+    O: FnMut(W, T),
+{
+    interrupt::cancellable(
+        stream::iter(words)
+            .map(translate)
+            .buffered(concurrency)
+            .try_for_each(|(word, translated)| {
+                output(word, translated);
+
+                ready(Ok(()))
+            }),
+    )
+    .await?
+}
+
+/// identify the requests of an image or a speech, unique in the process
+fn session_uuid() -> String {
+    format!(
+        "txcv-{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+    )
+}
+
+/// the request fails before the api responds
+fn network_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<tencentcloud::Error>()
+        .is_some_and(|err| !matches!(err, tencentcloud::Error::Api { .. }))
+        || err
+            .downcast_ref::<ureq::Error>()
+            .is_some_and(|err| matches!(err, ureq::Error::Transport(_)))
+}
+
+/// the whitespace after a translated chunk, line breaks are kept, the space between sentences is
+/// dropped in languages written without spaces
+fn chunk_separator<'a>(chunk: &'a str, target_lang: &str) -> &'a str {
+    let whitespace = &chunk[chunk.trim_end().len()..];
+    if whitespace.contains('\n') || !matches!(target_lang, "zh" | "zh-TW" | "jp") {
+        whitespace
+    } else {
+        ""
+    }
+}
+
+/// the text files under the dir, the symlinked dirs, the binary files and the excluded dir are
+/// skipped
+fn collect_files(dir: &Path, exclude: Option<&Path>) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if exclude.is_some_and(|exclude| path.canonicalize().is_ok_and(|path| path == exclude))
+            {
+                continue;
+            }
+
+            files.extend(collect_files(&path, exclude)?);
+        } else if file_type.is_symlink() && !path.is_file() {
+            diagnostic::warn(format!("skip symlink {}", path.display()));
+        } else if !encoding::is_text_file(&path)
+            .with_context(|| format!("read {} failed", path.display()))?
+        {
+            diagnostic::warn(format!("skip binary file {}", path.display()));
+        } else {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// whether the one line output is wider than the terminal
+#[cfg(feature = "tui")]
+fn too_wide(word: &str, translated_word: &str) -> bool {
+    match terminal::size() {
+        Err(_) => false,
+        Ok((_, rows)) => word.chars().count() + translated_word.chars().count() > rows as _,
+    }
+}
+
+/// the terminal size is unknown without tui, always print in one line
+#[cfg(not(feature = "tui"))]
+fn too_wide(_word: &str, _translated_word: &str) -> bool {
+    false
+}
+
+/// chinese is translated to english, other languages are translated to chinese
+fn get_target_lang(source: &str) -> Option<&'static str> {
+    match Language::from_code(source)? {
+        Language::Chinese | Language::TraditionalChinese => Some("en"),
+        _ => Some("zh"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::time::{Duration, Instant};
+
+    use async_std::task;
+    use futures_util::future::{self, BoxFuture, FutureExt};
+
+    use super::*;
+
+    /// deterministic xorshift random, so failures can be reproduced
+    struct Random(u64);
+
+    impl Random {
+        fn next_millis(&mut self, max: u64) -> Duration {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+
+            Duration::from_millis(self.0 % max)
+        }
+    }
+
+    #[async_std::test]
+    async fn ordered_output_with_random_latency() {
+        let words = (0..30).map(|i| format!("word{i}")).collect::<Vec<_>>();
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        let latencies = words
+            .iter()
+            .map(|_| random.next_millis(20))
+            .collect::<Vec<_>>();
+        let index = Cell::new(0);
+        let mut outputs = vec![];
+
+        ordered_batch(
+            words.clone(),
+            4,
+            |word| {
+                let latency = latencies[index.get()];
+                index.set(index.get() + 1);
+
+                async move {
+                    task::sleep(latency).await;
+
+                    Ok((word.clone(), word.to_uppercase()))
+                }
+            },
+            |word, translated| outputs.push((word, translated)),
+        )
+        .await
+        .unwrap();
+
+        let expect = words
+            .iter()
+            .map(|word| (word.clone(), word.to_uppercase()))
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, expect);
+    }
+
+    #[async_std::test]
+    async fn batch_adheres_rate_limit() {
+        const MAX: u32 = 2;
+        const INTERVAL: Duration = Duration::from_millis(50);
+        // timer may fire a little earlier than the bucket computed
+        const TOLERANCE: Duration = Duration::from_millis(5);
+
+        let bucket = LeakyBucket::builder()
+            .max(MAX)
+            .refill_interval(INTERVAL)
+            .tokens(MAX)
+            .build();
+        let acquired_at = RefCell::new(vec![]);
+        let start = Instant::now();
+
+        ordered_batch(
+            (0..8).map(|i| i.to_string()).collect(),
+            MAX as _,
+            |word| async {
+                let _permit = bucket.acquire_one().await?;
+                acquired_at.borrow_mut().push(start.elapsed());
+
+                Ok((word, ()))
+            },
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        for (i, elapsed) in acquired_at.into_inner().into_iter().enumerate() {
+            let refills = (i as u32 + 1).saturating_sub(MAX);
+
+            assert!(
+                elapsed + TOLERANCE >= INTERVAL * refills,
+                "request {i} is sent at {elapsed:?}"
+            );
+        }
+    }
+
+    /// uppercase the text, the source language is always detected as en
+    #[derive(Debug)]
+    struct MockBackend;
+
+    impl TranslateBackend for MockBackend {
+        fn translate<'a>(
+            &'a self,
+            text: &'a str,
+            source: &'a str,
+            target: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+            future::ready(Ok(Translation {
+                source_lang: source.to_string(),
+                target_lang: target.to_string(),
+                text: text.to_uppercase(),
+            }))
+            .boxed()
+        }
+
+        fn detect_language<'a>(&'a self, _text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+            future::ready(Ok("en".to_string())).boxed()
+        }
+
+        fn supported_languages(&self) -> &'static [&'static str] {
+            &["en", "zh"]
+        }
+
+        fn provider(&self) -> Provider {
+            Provider::Tmt
+        }
+    }
+
+    /// rejects the text longer than the max characters
+    #[derive(Debug)]
+    struct LimitedBackend(usize);
+
+    impl TranslateBackend for LimitedBackend {
+        fn translate<'a>(
+            &'a self,
+            text: &'a str,
+            source: &'a str,
+            target: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+            if text.chars().count() > self.0 {
+                return future::ready(Err(TextTooLong.into())).boxed();
+            }
+
+            MockBackend.translate(text, source, target)
+        }
+
+        fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+            MockBackend.detect_language(text)
+        }
+
+        fn supported_languages(&self) -> &'static [&'static str] {
+            MockBackend.supported_languages()
+        }
+
+        fn provider(&self) -> Provider {
+            Provider::Tmt
+        }
+    }
+
+    /// translates by batches of 2 words, and counts the batch requests
+    #[derive(Debug, Default)]
+    struct BatchBackend {
+        batches: AtomicUsize,
+        /// the texts sent by both apis
+        texts: AtomicUsize,
+    }
+
+    impl TranslateBackend for BatchBackend {
+        fn translate<'a>(
+            &'a self,
+            text: &'a str,
+            source: &'a str,
+            target: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+            self.texts.fetch_add(1, Ordering::Relaxed);
+
+            MockBackend.translate(text, source, target)
+        }
+
+        fn translate_batch<'a>(
+            &'a self,
+            texts: &'a [String],
+            source: &'a str,
+            target: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Vec<Translation>>> {
+            self.batches.fetch_add(1, Ordering::Relaxed);
+            self.texts.fetch_add(texts.len(), Ordering::Relaxed);
+
+            MockBackend.translate_batch(texts, source, target)
+        }
+
+        fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+            MockBackend.detect_language(text)
+        }
+
+        fn max_batch(&self) -> usize {
+            2
+        }
+
+        fn supported_languages(&self) -> &'static [&'static str] {
+            MockBackend.supported_languages()
+        }
+
+        fn provider(&self) -> Provider {
+            Provider::Tmt
+        }
+    }
+
+    #[test]
+    fn auto_target_lang() {
+        assert_eq!(get_target_lang("zh"), Some("en"));
+        assert_eq!(get_target_lang("zh-TW"), Some("en"));
+        assert_eq!(get_target_lang("ko"), Some("zh"));
+        assert_eq!(get_target_lang("ja"), Some("zh"));
+        assert_eq!(get_target_lang("xx"), None);
+    }
+
+    #[async_std::test]
+    async fn translate_with_mock_backend() {
+        let translate = Translate::with_backend(Arc::new(MockBackend), Options::default()).unwrap();
+
+        let translation = translate
+            .translate_text("  hello\n".to_string(), None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(translation.text, "  HELLO\n");
+        assert_eq!(translation.source_lang, "en");
+        assert_eq!(translation.target_lang, "zh");
+
+        assert!(translate
+            .translate_text("hello".to_string(), None, Some(Language::Japanese), false)
+            .await
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_text_files() {
+        let dir = std::env::temp_dir().join(format!("txcv-collect-{}", std::process::id()));
+        let input = dir.join("input");
+        std::fs::create_dir_all(input.join("docs")).unwrap();
+        std::fs::create_dir_all(input.join("out")).unwrap();
+        std::fs::write(input.join("a.txt"), "hello").unwrap();
+        std::fs::write(input.join("docs/b.md"), "world").unwrap();
+        std::fs::write(input.join("out/a.txt"), "你好").unwrap();
+        std::fs::write(
+            input.join("image.png"),
+            [0x89, b'P', b'N', b'G', 0, 0, 0, 0x0d],
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(&dir, input.join("parent")).unwrap();
+
+        let out = input.join("out").canonicalize().unwrap();
+        let files = collect_files(&input, Some(&out)).unwrap();
+        assert_eq!(files, [input.join("a.txt"), input.join("docs/b.md")]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[async_std::test]
+    async fn translate_words_by_batches() {
+        let backend = Arc::new(BatchBackend::default());
+        let translate = Translate::with_backend(backend.clone(), Options::default()).unwrap();
+        let words = ["one", "two", "three", "one"].map(String::from);
+
+        let translated = translate
+            .translate_batched(&words, Some(Language::English), None)
+            .await
+            .unwrap();
+        assert_eq!(backend.batches.load(Ordering::Relaxed), 2);
+        assert_eq!(translated.len(), 3);
+        assert_eq!(translated["three"].text, "THREE");
+        assert_eq!(translated["one"].target_lang, "zh");
+
+        // the source language must be known
+        let translated = translate
+            .translate_batched(&words, None, None)
+            .await
+            .unwrap();
+        assert!(translated.is_empty());
+    }
+
+    #[async_std::test]
+    async fn batch_words_hit_the_cache() {
+        let dir = std::env::temp_dir().join(format!("txcv-cache-{}-batch", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let backend = Arc::new(BatchBackend::default());
+        let translate = Translate {
+            cache: Some(Cache::in_dir(dir.clone())),
+            ..Translate::with_backend(backend.clone(), Options::default()).unwrap()
+        };
+        let words = ["one", "two", "three"].map(String::from).to_vec();
+
+        translate
+            .run_batch(words.clone(), Some(Language::English), None)
+            .await
+            .unwrap();
+        assert_eq!(backend.texts.load(Ordering::Relaxed), 3);
+
+        // the cached words are neither batched nor translated again
+        translate
+            .run_batch(words, Some(Language::English), None)
+            .await
+            .unwrap();
+        assert_eq!(backend.batches.load(Ordering::Relaxed), 2);
+        assert_eq!(backend.texts.load(Ordering::Relaxed), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[async_std::test]
+    async fn count_cached_batch_words() {
+        let dir = std::env::temp_dir().join(format!("txcv-cache-{}-summary", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let translate = Translate {
+            cache: Some(Cache::in_dir(dir.clone())),
+            ..Translate::with_backend(Arc::new(BatchBackend::default()), Options::default())
+                .unwrap()
+        };
+        let words = ["one", "two", "three"].map(String::from).to_vec();
+
+        let mut summary = Summary::default();
+        translate
+            .translate_words(
+                words[..2].to_vec(),
+                Some(Language::English),
+                None,
+                &mut summary,
+                &mut vec![],
+            )
+            .await
+            .unwrap();
+        assert_eq!((summary.translated, summary.cached), (2, 0));
+
+        let mut summary = Summary::default();
+        let mut segments = vec![];
+        translate
+            .translate_words(
+                words,
+                Some(Language::English),
+                None,
+                &mut summary,
+                &mut segments,
+            )
+            .await
+            .unwrap();
+        assert_eq!((summary.translated, summary.cached), (1, 2));
+        assert_eq!(summary.chars, "three".len());
+        assert_eq!(segments[2], ("three".to_string(), "THREE".to_string()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[async_std::test]
+    async fn batch_words_keep_glossary_terms() {
+        let mut glossary = Glossary::default();
+        glossary.add(crate::glossary::Term {
+            source: "pull".to_string(),
+            target: "拉取".to_string(),
+        });
+        let backend = Arc::new(BatchBackend::default());
+        let translate = Translate {
+            glossary: Some(Arc::new(glossary)),
+            ..Translate::with_backend(backend.clone(), Options::default()).unwrap()
+        };
+        let words = ["pull it", "push it"].map(String::from).to_vec();
+
+        // the backend gets the terms as placeholders
+        let batched = translate
+            .translate_batched(&words, Some(Language::English), None)
+            .await
+            .unwrap();
+        assert_eq!(batched["{{0}} it"].text, "{{0}} IT");
+        assert_eq!(batched["push it"].text, "PUSH IT");
+
+        let translate = Translate {
+            batched: Arc::new(batched),
+            ..translate
+        };
+        let (translation, _) = translate
+            .translate_with_provider("pull it".to_string(), Some(Language::English), None, false)
+            .await
+            .unwrap();
+        assert_eq!(translation.text, "拉取 IT");
+        assert_eq!(backend.texts.load(Ordering::Relaxed), 2);
+    }
+
+    #[async_std::test]
+    async fn clones_share_the_limiter() {
+        let options = Options {
+            rate_limit: Some(RateLimit {
+                max_concurrent: 1,
+                refill_interval: 20,
+                chars_per_token: None,
+            }),
+            ..Options::default()
+        };
+        let translate = Translate::with_backend(Arc::new(MockBackend), options).unwrap();
+        let cloned = translate.clone();
+
+        translate
+            .translate_chunked("hello".to_string(), None, None)
+            .await
+            .unwrap();
+        cloned
+            .translate_chunked("world".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let metrics = translate.limiter.metrics();
+        assert_eq!(metrics.acquired, 2);
+        assert_eq!(metrics.waits, 1);
+    }
+
+    #[async_std::test]
+    async fn split_too_long_text() {
+        let translate =
+            Translate::with_backend(Arc::new(LimitedBackend(10)), Options::default()).unwrap();
+
+        let translation = translate
+            .translate_word(
+                "One two. Three four. Five.".to_string(),
+                None,
+                Some(Language::English),
+            )
+            .await
+            .unwrap();
+        assert_eq!(translation.text, "ONE TWO. THREE FOUR. FIVE.");
+        assert_eq!(translate.max_chars.load(Ordering::Relaxed), 6);
+    }
+
+    #[async_std::test]
+    async fn translate_long_text_by_chunks() {
+        let translate = Translate::with_backend(
+            Arc::new(MockBackend),
+            Options {
+                max_chars: Some(12),
+                ..Options::default()
+            },
+        )
+        .unwrap();
+
+        let translation = translate
+            .translate_chunked("One two. Three four. Five.\n".to_string(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(translation.text, "ONE TWO. THREE FOUR. FIVE.\n");
+        assert_eq!(translation.source_lang, "en");
+        assert_eq!(translation.target_lang, "zh");
+    }
+}
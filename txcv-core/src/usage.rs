@@ -0,0 +1,323 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::Context;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use tencentcloud::Client;
+
+use crate::atomic;
+use crate::backend::TranslateBackend;
+use crate::config::RateLimit;
+use crate::translate::{Provider, Translation};
+
+const USAGE_FILE: &str = "usage.json";
+/// the older months are removed
+const KEPT_MONTHS: usize = 12;
+/// percent of the free characters of a month, a notice is printed after the run reaching it
+pub const DEFAULT_WARNING: u8 = 80;
+
+/// the characters sent to a provider in a month
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+struct MonthUsage {
+    /// `2024-05`, utc
+    month: String,
+    provider: Provider,
+    chars: u64,
+}
+
+/// the characters sent per month, the free tiers of the providers are counted by characters
+#[derive(Debug)]
+pub struct Usage {
+    path: PathBuf,
+    months: Vec<MonthUsage>,
+}
+
+impl Usage {
+    pub fn open() -> anyhow::Result<Self> {
+        let path = dirs::data_dir()
+            .context("can't find data dir")?
+            .join("txcv")
+            .join(USAGE_FILE);
+
+        let months = load(&path)?;
+
+        Ok(Self { path, months })
+    }
+
+    /// add the characters sent this month and save, return the characters of the month, the
+    /// usage saved by other txcv since opening is kept
+    pub fn record(&mut self, provider: Provider, chars: u64) -> anyhow::Result<u64> {
+        let _lock = atomic::lock(&self.path)?;
+        self.months = load(&self.path)?;
+
+        let chars = add(&mut self.months, &current_month(), provider, chars);
+
+        atomic::write(&self.path, &serde_json::to_vec(&self.months)?)
+            .with_context(|| format!("write {} failed", self.path.display()))?;
+
+        Ok(chars)
+    }
+
+    /// the characters sent to the provider this month
+    pub fn month_chars(&self, provider: Provider) -> u64 {
+        let month = current_month();
+
+        self.months
+            .iter()
+            .find(|usage| usage.month == month && usage.provider == provider)
+            .map_or(0, |usage| usage.chars)
+    }
+
+    /// print the characters of every month, and the percent of the free tier, 0 warning disables
+    /// the warning marks
+    pub fn print<W: Write>(&self, warning: u8, mut writer: W) -> anyhow::Result<()> {
+        if self.months.is_empty() {
+            writeln!(writer, "no characters are sent yet")?;
+        }
+
+        for usage in &self.months {
+            let line = format!(
+                "{} {:<6} {:>10} characters",
+                usage.month, usage.provider, usage.chars
+            );
+
+            match free_tier(usage.provider) {
+                None => writeln!(writer, "{line}")?,
+                Some(free) => {
+                    let mark = match reached(usage.chars, free, warning) {
+                        true => ", approaching the limit",
+                        false => "",
+                    };
+                    writeln!(
+                        writer,
+                        "{line}, {:.1}% of the free {free}{mark}",
+                        percent(usage.chars, free)
+                    )?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn load(path: &Path) -> anyhow::Result<Vec<MonthUsage>> {
+    match fs::read(path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+        Ok(data) => serde_json::from_slice(&data)
+            .with_context(|| format!("parse usage {} failed", path.display())),
+    }
+}
+
+/// add the characters to the month, the months older than the last KEPT_MONTHS are removed,
+/// return the characters of the month
+fn add(months: &mut Vec<MonthUsage>, month: &str, provider: Provider, chars: u64) -> u64 {
+    let total = match months
+        .iter_mut()
+        .find(|usage| usage.month == month && usage.provider == provider)
+    {
+        None => {
+            months.push(MonthUsage {
+                month: month.to_string(),
+                provider,
+                chars,
+            });
+
+            chars
+        }
+
+        Some(usage) => {
+            usage.chars += chars;
+
+            usage.chars
+        }
+    };
+
+    // the months are sorted for printing
+    let newest = index(month);
+    months.retain(|usage| {
+        index(&usage.month).is_some_and(|index| Some(index + KEPT_MONTHS) > newest)
+    });
+    months.sort_by(|a, b| a.month.cmp(&b.month));
+
+    total
+}
+
+/// the months since year 0 of `2024-05`
+fn index(month: &str) -> Option<usize> {
+    let (year, month) = month.split_once('-')?;
+
+    Some(year.parse::<usize>().ok()? * 12 + month.parse::<usize>().ok()?)
+}
+
+/// the month of now in utc, like `2024-05`
+fn current_month() -> String {
+    humantime::format_rfc3339_seconds(SystemTime::now()).to_string()[..7].to_string()
+}
+
+/// the free characters per month
+pub fn free_tier(provider: Provider) -> Option<u64> {
+    match provider {
+        Provider::Tmt => Some(5_000_000),
+        Provider::Deepl => Some(500_000),
+        _ => None,
+    }
+}
+
+pub fn percent(chars: u64, free: u64) -> f64 {
+    chars as f64 * 100.0 / free as f64
+}
+
+fn reached(chars: u64, free: u64, warning: u8) -> bool {
+    warning > 0 && chars * 100 >= free * u64::from(warning)
+}
+
+/// the notice when the characters of the month reach the warning percent of the free tier
+pub fn notice(provider: Provider, chars: u64, warning: u8) -> Option<String> {
+    let free = free_tier(provider)?;
+
+    reached(chars, free, warning).then(|| {
+        format!(
+            "{chars} characters are sent to {provider} this month, {:.1}% of the free {free}",
+            percent(chars, free)
+        )
+    })
+}
+
+/// counts the characters of the translate requests, the language detection is free
+#[derive(Debug)]
+pub struct Metered {
+    backend: Arc<dyn TranslateBackend>,
+    chars: AtomicUsize,
+}
+
+impl Metered {
+    pub fn new(backend: Arc<dyn TranslateBackend>) -> Self {
+        Self {
+            backend,
+            chars: AtomicUsize::new(0),
+        }
+    }
+
+    /// the characters counted since the last take
+    pub fn take(&self) -> usize {
+        self.chars.swap(0, Ordering::Relaxed)
+    }
+
+    fn count<T>(&self, chars: usize, result: &anyhow::Result<T>) {
+        if result.is_ok() {
+            self.chars.fetch_add(chars, Ordering::Relaxed);
+        }
+    }
+}
+
+impl TranslateBackend for Metered {
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        async move {
+            let result = self.backend.translate(text, source, target).await;
+            self.count(text.chars().count(), &result);
+
+            result
+        }
+        .boxed()
+    }
+
+    fn translate_in_context<'a>(
+        &'a self,
+        text: &'a str,
+        context: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Translation>> {
+        async move {
+            let result = self
+                .backend
+                .translate_in_context(text, context, source, target)
+                .await;
+            self.count(text.chars().count(), &result);
+
+            result
+        }
+        .boxed()
+    }
+
+    fn translate_batch<'a>(
+        &'a self,
+        texts: &'a [String],
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<Vec<Translation>>> {
+        async move {
+            let result = self.backend.translate_batch(texts, source, target).await;
+            let chars = texts.iter().map(|text| text.chars().count()).sum();
+            self.count(chars, &result);
+
+            result
+        }
+        .boxed()
+    }
+
+    fn detect_language<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<String>> {
+        self.backend.detect_language(text)
+    }
+
+    fn max_batch(&self) -> usize {
+        self.backend.max_batch()
+    }
+
+    fn supported_languages(&self) -> &'static [&'static str] {
+        self.backend.supported_languages()
+    }
+
+    fn provider(&self) -> Provider {
+        self.backend.provider()
+    }
+
+    fn rate_limit(&self) -> RateLimit {
+        self.backend.rate_limit()
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        self.backend.max_chars()
+    }
+
+    fn tencent_client(&self) -> Option<&Client> {
+        self.backend.tencent_client()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monthly_usage() {
+        let mut months = vec![];
+
+        assert_eq!(add(&mut months, "2024-05", Provider::Tmt, 100), 100);
+        assert_eq!(add(&mut months, "2024-05", Provider::Deepl, 20), 20);
+        assert_eq!(add(&mut months, "2024-05", Provider::Tmt, 50), 150);
+        assert_eq!(add(&mut months, "2024-06", Provider::Tmt, 10), 10);
+        assert_eq!(months.len(), 3);
+
+        // a year later, only the last 12 months are kept
+        add(&mut months, "2025-05", Provider::Tmt, 1);
+        assert_eq!(months[0].month, "2024-06");
+
+        assert_eq!(notice(Provider::Tmt, 3_999_999, 80), None);
+        assert!(notice(Provider::Tmt, 4_000_000, 80).is_some());
+        assert_eq!(notice(Provider::Tmt, 5_000_000, 0), None);
+        assert_eq!(notice(Provider::Dict, 5_000_000, 80), None);
+    }
+}
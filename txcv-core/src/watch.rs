@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+
+use arboard::Clipboard;
+#[cfg(all(unix, not(target_os = "macos")))]
+use arboard::{GetExtLinux, LinuxClipboardKind};
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+use crate::clipboard;
+
+/// interval of reading the clipboard
+pub const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// copied texts waiting for translation, the oldest ones are dropped beyond it
+const MAX_QUEUED: usize = 32;
+
+/// polls the clipboard or the primary selection for newly copied text, the text copied before
+/// watching is ignored
+pub struct Watcher {
+    clipboard: Clipboard,
+    primary: bool,
+    debounce: Debounce,
+}
+
+impl Debug for Watcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher")
+            .field("primary", &self.primary)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Watcher {
+    /// primary is the x11 selection, the text is copied by selecting it
+    pub fn new(primary: bool, debounce: Duration) -> anyhow::Result<Self> {
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        if primary {
+            return Err(anyhow::anyhow!(
+                "primary selection is only supported on linux"
+            ));
+        }
+
+        let mut clipboard = Clipboard::new()?;
+        let current = read_text(&mut clipboard, primary);
+
+        Ok(Self {
+            clipboard,
+            primary,
+            debounce: Debounce::new(current, debounce),
+        })
+    }
+
+    /// the new text if it's unchanged for the debounce time
+    pub fn poll(&mut self) -> Option<String> {
+        let text = read_text(&mut self.clipboard, self.primary);
+
+        self.debounce.update(text, Instant::now())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_text(clipboard: &mut Clipboard, primary: bool) -> Option<String> {
+    let kind = if primary {
+        LinuxClipboardKind::Primary
+    } else {
+        LinuxClipboardKind::Clipboard
+    };
+
+    clipboard.get().clipboard(kind).text().ok()
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn read_text(clipboard: &mut Clipboard, _primary: bool) -> Option<String> {
+    clipboard::unoccupied(|| clipboard.get_text()).ok()
+}
+
+/// a text is emitted once after it's unchanged for the delay, so a selection being dragged or a
+/// burst of copies is translated once
+#[derive(Debug)]
+struct Debounce {
+    delay: Duration,
+    /// the text of the last poll and when it's first seen
+    seen: Option<String>,
+    since: Instant,
+    emitted: Option<String>,
+}
+
+impl Debounce {
+    /// the current text is treated as emitted
+    fn new(current: Option<String>, delay: Duration) -> Self {
+        Self {
+            delay,
+            seen: current.clone(),
+            since: Instant::now(),
+            emitted: current,
+        }
+    }
+
+    fn update(&mut self, text: Option<String>, now: Instant) -> Option<String> {
+        if text != self.seen {
+            self.seen = text;
+            self.since = now;
+
+            return None;
+        }
+
+        match &self.seen {
+            Some(text)
+                if now.duration_since(self.since) >= self.delay
+                    && self.emitted.as_ref() != Some(text) =>
+            {
+                self.emitted = Some(text.clone());
+
+                Some(text.clone())
+            }
+
+            _ => None,
+        }
+    }
+}
+
+/// the copied texts waiting for translation, a burst of copies is translated in order through the
+/// rate limit instead of dropped, identical consecutive texts are coalesced
+#[derive(Debug, Default)]
+pub struct Queue {
+    texts: VecDeque<String>,
+    /// the last pushed text, it may be translated already
+    last: Option<String>,
+}
+
+impl Queue {
+    /// push the text unless it's the same as the last one, return the oldest text dropped when the
+    /// queue is full
+    pub fn push(&mut self, text: String) -> Option<String> {
+        if self.last.as_ref() == Some(&text) {
+            return None;
+        }
+        self.last = Some(text.clone());
+        self.texts.push_back(text);
+
+        if self.texts.len() > MAX_QUEUED {
+            self.texts.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.texts.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emit_stable_text_once() {
+        let delay = Duration::from_millis(500);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let text = |text: &str| Some(text.to_string());
+        let mut debounce = Debounce::new(text("old"), delay);
+        debounce.since = start;
+
+        assert_eq!(debounce.update(text("old"), at(600)), None);
+        assert_eq!(debounce.update(text("sel"), at(700)), None);
+        assert_eq!(debounce.update(text("selected"), at(800)), None);
+        assert_eq!(debounce.update(text("selected"), at(1000)), None);
+        assert_eq!(
+            debounce.update(text("selected"), at(1300)),
+            text("selected")
+        );
+        assert_eq!(debounce.update(text("selected"), at(1500)), None);
+        assert_eq!(debounce.update(None, at(1600)), None);
+        assert_eq!(debounce.update(text("selected"), at(1700)), None);
+        assert_eq!(debounce.update(text("selected"), at(2300)), None);
+    }
+
+    #[test]
+    fn queue_coalesces_and_drops_oldest() {
+        let mut queue = Queue::default();
+        assert_eq!(queue.push("a".to_string()), None);
+        assert_eq!(queue.push("a".to_string()), None);
+        assert_eq!(queue.push("b".to_string()), None);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop().as_deref(), Some("a"));
+        assert_eq!(queue.pop().as_deref(), Some("b"));
+        // the last text is coalesced even after it's taken
+        assert_eq!(queue.push("b".to_string()), None);
+        assert_eq!(queue.pop(), None);
+
+        for i in 0..MAX_QUEUED {
+            queue.push(i.to_string());
+        }
+        assert_eq!(queue.push("last".to_string()).as_deref(), Some("0"));
+        assert_eq!(queue.len(), MAX_QUEUED);
+    }
+}
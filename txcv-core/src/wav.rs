@@ -0,0 +1,102 @@
+use anyhow::Context;
+
+/// the speech translation only accepts 16k sample rate, 16 bit, mono pcm
+const SAMPLE_RATE: u32 = 16_000;
+const BITS_PER_SAMPLE: u16 = 16;
+const CHANNELS: u16 = 1;
+const FORMAT_PCM: u16 = 1;
+/// bytes of 200ms audio, the chunk size recommended by the speech translation
+pub const CHUNK_SIZE: usize = (SAMPLE_RATE as usize) * (BITS_PER_SAMPLE as usize / 8) / 5;
+
+/// the pcm samples of a wav file, the format is checked to be 16k sample rate, 16 bit and mono
+pub fn pcm(data: &[u8]) -> anyhow::Result<&[u8]> {
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(anyhow::anyhow!("not a wav file"));
+    }
+
+    let mut format = None;
+    let mut rest = &data[12..];
+    while rest.len() >= 8 {
+        let id = &rest[..4];
+        let size = u32::from_le_bytes(rest[4..8].try_into()?) as usize;
+        let body = rest.get(8..8 + size).unwrap_or(&rest[8..]);
+
+        match id {
+            b"fmt " => {
+                let field = |offset: usize| {
+                    body.get(offset..offset + 2)
+                        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+                        .context("broken wav format chunk")
+                };
+                let sample_rate = body
+                    .get(4..8)
+                    .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .context("broken wav format chunk")?;
+
+                format = Some((field(0)?, field(2)?, sample_rate, field(14)?));
+            }
+
+            b"data" => {
+                let (tag, channels, sample_rate, bits) =
+                    format.context("wav data chunk before the format chunk")?;
+                if tag != FORMAT_PCM
+                    || channels != CHANNELS
+                    || sample_rate != SAMPLE_RATE
+                    || bits != BITS_PER_SAMPLE
+                {
+                    return Err(anyhow::anyhow!(
+                        "only 16k sample rate, 16 bit, mono pcm wav is supported, got format \
+                         {tag}, {channels} channels, {sample_rate} sample rate, {bits} bit, \
+                         convert it like `ffmpeg -i memo.m4a -ar 16000 -ac 1 memo.wav`"
+                    ));
+                }
+
+                return Ok(body);
+            }
+
+            _ => {}
+        }
+
+        // chunks are padded to even sizes
+        let next = 8 + size + size % 2;
+        rest = rest.get(next..).unwrap_or_default();
+    }
+
+    Err(anyhow::anyhow!("no data chunk in the wav file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(sample_rate: u32, channels: u16, samples: &[u8]) -> Vec<u8> {
+        let mut data = b"RIFF\0\0\0\0WAVE".to_vec();
+        data.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        data.extend_from_slice(b"fmt \x10\0\0\0");
+        data.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+        data.extend_from_slice(&channels.to_le_bytes());
+        data.extend_from_slice(&sample_rate.to_le_bytes());
+        data.extend_from_slice(&(sample_rate * 2 * channels as u32).to_le_bytes());
+        data.extend_from_slice(&(2 * channels).to_le_bytes());
+        data.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        data.extend_from_slice(samples);
+
+        data
+    }
+
+    #[test]
+    fn read_pcm_samples() {
+        let data = wav(16_000, 1, &[1, 2, 3, 4]);
+        assert_eq!(pcm(&data).unwrap(), &[1, 2, 3, 4]);
+        assert_eq!(CHUNK_SIZE, 6400);
+    }
+
+    #[test]
+    fn reject_other_formats() {
+        assert!(pcm(&wav(44_100, 1, &[0, 0])).is_err());
+        assert!(pcm(&wav(16_000, 2, &[0, 0])).is_err());
+        assert!(pcm(b"OggS").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+/// leading and trailing whitespace of every line
+#[derive(Debug, Clone)]
+pub struct Layout {
+    lines: Vec<(String, String)>,
+}
+
+/// strip the leading and trailing whitespace of every line, so only the core text is translated
+pub fn strip(text: &str) -> (String, Layout) {
+    let mut lines = vec![];
+    let mut cores = vec![];
+    for line in text.split('\n') {
+        let core = line.trim();
+        let leading_len = line.len() - line.trim_start().len();
+        let trailing_start = leading_len + core.len();
+
+        lines.push((
+            line[..leading_len].to_string(),
+            line[trailing_start..].to_string(),
+        ));
+        cores.push(core);
+    }
+
+    (cores.join("\n"), Layout { lines })
+}
+
+/// reattach the whitespace, when translation changes the line count, only the whitespace around
+/// the whole text is reattached
+pub fn restore(translated: &str, layout: &Layout) -> String {
+    let translated_lines = translated.split('\n').collect::<Vec<_>>();
+    if translated_lines.len() == layout.lines.len() {
+        return translated_lines
+            .iter()
+            .zip(&layout.lines)
+            .map(|(line, (leading, trailing))| format!("{leading}{line}{trailing}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    let leading = layout
+        .lines
+        .first()
+        .map(|(leading, _)| leading.as_str())
+        .unwrap_or_default();
+    let trailing = layout
+        .lines
+        .last()
+        .map(|(_, trailing)| trailing.as_str())
+        .unwrap_or_default();
+
+    format!("{leading}{}{trailing}", translated.trim())
+}